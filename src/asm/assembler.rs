@@ -1,15 +1,31 @@
-use crate::asm::ast::{AsmProgram, Immediate, Operand as AsmOperand, Statement};
+use crate::asm::ast::{AsmProgram, Immediate, Operand as AsmOperand, Statement, StructDecl};
 use crate::asm::error::AsmError;
 use crate::asm::lexer::Lexer;
 use crate::asm::parser::Parser;
 use crate::opcode::OpCode;
-use crate::program::{Function, Program, SourceLocation, SourceMap, Variable};
-use crate::types::{DataType, Operand, Value};
+use crate::program::{Function, Program, SourceLocation, SourceMap, StructLayout, Variable};
+use crate::simd::VectorType;
+use crate::types::{Address, DataType, Endian, Operand, Value};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Settings that change the bytecode produced from the same source.
+#[derive(Debug, Clone, Default)]
+pub struct AssembleOptions {
+    /// Drop `assert` instructions, for release builds.
+    pub strip_asserts: bool,
+}
+
 pub fn assemble(source: &str, filename: String) -> Result<Program, AsmError> {
+    assemble_with_options(source, filename, &AssembleOptions::default())
+}
+
+pub fn assemble_with_options(
+    source: &str,
+    filename: String,
+    options: &AssembleOptions,
+) -> Result<Program, AsmError> {
     let mut included_files = HashSet::new();
     let base_path = Path::new(&filename)
         .parent()
@@ -18,7 +34,7 @@ pub fn assemble(source: &str, filename: String) -> Result<Program, AsmError> {
 
     let ast = parse_with_includes(source, &filename, &base_path, &mut included_files)?;
 
-    let mut assembler = Assembler::new(filename);
+    let mut assembler = Assembler::new(filename, options.clone());
     assembler.assemble_program(ast)
 }
 
@@ -46,7 +62,7 @@ fn parse_with_includes(
     // Parse the current file
     let mut lexer = Lexer::new(source, filename.to_string());
     let tokens = lexer.tokenize()?;
-    let mut parser = Parser::new(tokens);
+    let mut parser = Parser::with_positions(tokens, lexer.positions().to_vec());
     let mut ast = parser.parse()?;
 
     // Process includes
@@ -93,6 +109,11 @@ fn resolve_and_parse_include(
     parse_with_includes(&source, &filename, &new_base_path, included_files)
 }
 
+// `.be` selects big-endian for memory mnemonics; plain and `.le` are little-endian
+fn mnemonic_endian(name: &str) -> Endian {
+    if name.ends_with(".be") { Endian::Big } else { Endian::Little }
+}
+
 struct Assembler {
     program: Program,
     filename: String,
@@ -100,11 +121,29 @@ struct Assembler {
     function_starts: HashMap<String, usize>,
     current_function: Option<String>,
     instruction_locations: HashMap<usize, SourceLocation>,
+    field_refs: HashMap<usize, Vec<String>>,
     defines: HashMap<String, crate::asm::ast::DefineValue>,
+    options: AssembleOptions,
+    // Signature of the function being assembled; `None` parameters means untyped
+    current_signature: Option<Signature>,
+    // Declared types of the current function's parameters and locals
+    local_types: HashMap<String, DataType>,
+    call_sites: Vec<CallSite>,
+}
+
+// Return type and, for typed functions, the parameter list
+type Signature = (DataType, Option<Vec<(String, DataType)>>);
+
+// A call recorded for checking once every function's signature is known.
+// Whether the first operand is a destination is only settled then, too.
+struct CallSite {
+    ip: usize,
+    operands: Vec<Operand>,
+    types: Vec<Option<DataType>>,
 }
 
 impl Assembler {
-    fn new(filename: String) -> Self {
+    fn new(filename: String, options: AssembleOptions) -> Self {
         Self {
             program: Program::new(),
             filename,
@@ -112,7 +151,12 @@ impl Assembler {
             function_starts: HashMap::new(),
             current_function: None,
             instruction_locations: HashMap::new(),
+            field_refs: HashMap::new(),
             defines: HashMap::new(),
+            options,
+            current_signature: None,
+            local_types: HashMap::new(),
+            call_sites: Vec::new(),
         }
     }
 
@@ -135,6 +179,11 @@ impl Assembler {
             self.defines.insert(define.name, define.value);
         }
 
+        // Lay out structs and expose their field offsets as constants
+        for decl in ast.structs {
+            self.assemble_struct(decl)?;
+        }
+
         // Process data section
         for decl in ast.data_section {
             // Check if this is a string literal
@@ -143,6 +192,15 @@ impl Assembler {
                 self.program.add_string(content.clone(), decl.name.clone());
             }
 
+            if let Some(count) = decl.array_len {
+                self.assemble_array(&decl.name, decl.dtype, count, decl.value.as_ref())?;
+
+                // Arrays are bound to a pointer to their memory block
+                let var = Variable::new(decl.name.clone(), DataType::Ptr, true);
+                self.program.add_global(var);
+                continue;
+            }
+
             // Add global variable (Ptr type for strings)
             let var = Variable::new(decl.name.clone(), decl.dtype, true);
             self.program.add_global(var);
@@ -153,16 +211,164 @@ impl Assembler {
         }
 
         self.resolve_labels()?;
+        self.check_calls()?;
 
         let source_map = SourceMap {
             file: PathBuf::from(self.filename.clone()),
             instruction_locations: self.instruction_locations.clone(),
+            field_refs: self.field_refs.clone(),
         };
         self.program.source_map = Some(source_map);
 
         Ok(self.program.clone())
     }
 
+    fn assemble_struct(&mut self, decl: StructDecl) -> Result<(), AsmError> {
+        use crate::asm::ast::DefineValue;
+
+        if self.program.structs.iter().any(|s| s.name == decl.name) {
+            return Err(AsmError::AssemblyError {
+                message: format!("Duplicate struct definition: {}", decl.name),
+                location: None,
+            });
+        }
+
+        let mut fields = Vec::with_capacity(decl.fields.len());
+        for field in decl.fields {
+            if fields.iter().any(|(name, _, _)| *name == field.name) {
+                return Err(AsmError::AssemblyError {
+                    message: format!("Duplicate field '{}' in struct '{}'", field.name, decl.name),
+                    location: None,
+                });
+            }
+            // `Name.size` is taken by the struct size constant
+            if field.name == "size" {
+                return Err(AsmError::AssemblyError {
+                    message: format!(
+                        "Field '{}.size' collides with the struct size constant; rename the field",
+                        decl.name
+                    ),
+                    location: None,
+                });
+            }
+            if field.dtype == DataType::Void {
+                return Err(AsmError::AssemblyError {
+                    message: format!("Field '{}.{}' cannot have type void", decl.name, field.name),
+                    location: None,
+                });
+            }
+            fields.push((field.name, field.dtype, field.array_len.unwrap_or(1)));
+        }
+
+        let layout = StructLayout::new(decl.name, fields);
+
+        // `Point.y` is the byte offset of field y, `Point.size` the padded struct size
+        for field in &layout.fields {
+            self.defines.insert(
+                format!("{}.{}", layout.name, field.name),
+                DefineValue::Integer(field.offset as i64),
+            );
+        }
+        self.defines.insert(
+            format!("{}.size", layout.name),
+            DefineValue::Integer(layout.size as i64),
+        );
+
+        self.program.structs.push(layout);
+        Ok(())
+    }
+
+    fn assemble_array(
+        &mut self,
+        name: &str,
+        dtype: DataType,
+        count: usize,
+        init: Option<&Immediate>,
+    ) -> Result<(), AsmError> {
+        use crate::asm::ast::DefineValue;
+
+        if dtype == DataType::Void {
+            return Err(AsmError::AssemblyError {
+                message: format!("Array '{}' cannot have element type void", name),
+                location: None,
+            });
+        }
+        // Strings and references live outside raw memory
+        let elem_size = Variable::size_of(dtype);
+        if elem_size == 0 {
+            return Err(AsmError::AssemblyError {
+                message: format!(
+                    "Array '{}' cannot have element type {}: it has no memory image",
+                    name,
+                    if dtype == DataType::Str { "string" } else { "ref" }
+                ),
+                location: None,
+            });
+        }
+
+        let items = match init {
+            None => Vec::new(),
+            Some(Immediate::Array(items)) => items.clone(),
+            Some(other) => {
+                return Err(AsmError::AssemblyError {
+                    message: format!("Array '{}' must be initialized with a {{...}} list, got {:?}", name, other),
+                    location: None,
+                });
+            }
+        };
+
+        if items.len() > count {
+            return Err(AsmError::AssemblyError {
+                message: format!(
+                    "Array '{}' has {} elements but {} initializers",
+                    name,
+                    count,
+                    items.len()
+                ),
+                location: None,
+            });
+        }
+
+        let mut bytes = Vec::with_capacity(count * elem_size);
+        for item in &items {
+            let value = match item {
+                Immediate::Integer(v) => Value::I64(*v),
+                Immediate::Float(v) => Value::F64(*v),
+                Immediate::Char(v) => Value::Char(*v),
+                _ => {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("Invalid initializer in array '{}': {:?}", name, item),
+                        location: None,
+                    });
+                }
+            };
+            // Elements must hold the literal exactly rather than wrap or saturate
+            let value = value.cast_exact(dtype).ok_or_else(|| AsmError::AssemblyError {
+                message: format!(
+                    "Array '{}': initializer out of range for {}: {:?}",
+                    name,
+                    format!("{:?}", dtype).to_lowercase(),
+                    item
+                ),
+                location: None,
+            })?;
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.resize(count * elem_size, 0);
+
+        self.program.add_data_block(name.to_string(), dtype, count, bytes);
+
+        // Element count and byte size are available as `name.len` and `name.size`
+        self.defines
+            .insert(format!("{}.len", name), DefineValue::Integer(count as i64));
+        self.defines.insert(
+            format!("{}.size", name),
+            DefineValue::Integer((count * elem_size) as i64),
+        );
+
+        Ok(())
+    }
+
     fn assemble_statement(&mut self, statement: Statement) -> Result<(), AsmError> {
         match statement {
             Statement::Label(name) => {
@@ -172,12 +378,16 @@ impl Assembler {
                 self.program.emit(OpCode::Label { name: qualified_name });
             },
             Statement::LocalDecl(decl) => {
+                self.local_types.insert(decl.name.clone(), decl.dtype);
                 self.program.emit(OpCode::CreateLocal {
                     dtype: decl.dtype,
                     name: decl.name,
                 });
             },
             Statement::Instruction(instr) => {
+                if self.options.strip_asserts && instr.opcode.eq_ignore_ascii_case("assert") {
+                    return Ok(());
+                }
                 let ip = self.program.instructions.len();
                 let loc = SourceLocation {
                     line: instr.location.line,
@@ -185,6 +395,10 @@ impl Assembler {
                     snippet: String::new(),
                 };
                 self.instruction_locations.insert(ip, loc);
+                let refs = self.struct_refs(&instr.operands);
+                if !refs.is_empty() {
+                    self.field_refs.insert(ip, refs);
+                }
                 self.assemble_instruction(instr)?;
             },
         }
@@ -199,7 +413,20 @@ impl Assembler {
 
         match opcode_name.as_str() {
             "func_begin" => {
-                if instr.operands.len() != 1 {
+                let params = match instr.operands.get(1) {
+                    Some(AsmOperand::Parameters(params)) if instr.operands.len() == 2 => Some(params.clone()),
+                    None if instr.operands.len() == 1 => None,
+                    _ => {
+                        return Err(AsmError::AssemblyError {
+                            message: format!(
+                                "func_begin expects a return type and an optional parameter list, got {} operands",
+                                instr.operands.len()
+                            ),
+                            location: None,
+                        });
+                    }
+                };
+                if instr.operands.is_empty() {
                     return Err(AsmError::AssemblyError {
                         message: format!(
                             "func_begin expects 1 operand (return type), got {}",
@@ -220,6 +447,21 @@ impl Assembler {
 
                 let return_type = self.operand_to_datatype(&instr.operands[0])?;
 
+                self.local_types.clear();
+                if let Some(params) = &params {
+                    let mut seen = HashSet::new();
+                    for (name, dtype) in params {
+                        if !seen.insert(name.as_str()) {
+                            return Err(AsmError::AssemblyError {
+                                message: format!("Duplicate parameter '{}' in function '{}'", name, func_name),
+                                location: None,
+                            });
+                        }
+                        self.local_types.insert(name.clone(), *dtype);
+                    }
+                }
+                self.current_signature = Some((return_type, params));
+
                 self.current_function = Some(func_name.clone());
                 self.function_starts
                     .insert(func_name.clone(), self.program.instructions.len());
@@ -234,10 +476,16 @@ impl Assembler {
                     let start_ip = *self.function_starts.get(func_name).unwrap();
                     let end_ip = self.program.instructions.len();
 
+                    let (return_type, params) = self.current_signature.take().unwrap_or((DataType::I32, None));
                     let func = Function {
                         name: func_name.clone(),
-                        return_type: DataType::I32,
-                        parameters: Vec::new(),
+                        return_type,
+                        typed: params.is_some(),
+                        parameters: params
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|(name, dtype)| Variable::new(name, dtype, false))
+                            .collect(),
                         locals: Vec::new(),
                         start_ip,
                         end_ip,
@@ -275,7 +523,7 @@ impl Assembler {
                 self.program.emit(OpCode::CopyVar { dest, source });
             },
             "add" | "sub" | "mul" | "div" | "mod" | "and" | "or" | "xor" | "shl" | "shr"
-            | "eq" | "ne" | "lt" | "le" | "gt" | "ge" => {
+            | "rotl" | "rotr" | "eq" | "ne" | "lt" | "le" | "gt" | "ge" => {
                 if instr.operands.len() != 3 {
                     return Err(AsmError::AssemblyError {
                         message: format!(
@@ -302,6 +550,8 @@ impl Assembler {
                     "xor" => OpCode::Xor { dest, left, right },
                     "shl" => OpCode::Shl { dest, left, right },
                     "shr" => OpCode::Shr { dest, left, right },
+                    "rotl" => OpCode::Rotl { dest, left, right },
+                    "rotr" => OpCode::Rotr { dest, left, right },
                     "eq" => OpCode::Eq { dest, left, right },
                     "ne" => OpCode::Ne { dest, left, right },
                     "lt" => OpCode::Lt { dest, left, right },
@@ -313,7 +563,7 @@ impl Assembler {
 
                 self.program.emit(opcode);
             },
-            "neg" | "not" => {
+            "neg" | "not" | "popcnt" | "clz" | "ctz" | "bswap" => {
                 if instr.operands.len() != 2 {
                     return Err(AsmError::AssemblyError {
                         message: format!(
@@ -331,11 +581,44 @@ impl Assembler {
                 let opcode = match opcode_name.as_str() {
                     "neg" => OpCode::Neg { dest, source },
                     "not" => OpCode::Not { dest, source },
+                    "popcnt" => OpCode::Popcnt { dest, source },
+                    "clz" => OpCode::Clz { dest, source },
+                    "ctz" => OpCode::Ctz { dest, source },
+                    "bswap" => OpCode::Bswap { dest, source },
                     _ => unreachable!(),
                 };
 
                 self.program.emit(opcode);
             },
+            "bfext" => {
+                if instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("bfext expects 4 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let source = self.operand_to_operand(&instr.operands[1])?;
+                let start = self.operand_to_operand(&instr.operands[2])?;
+                let len = self.operand_to_operand(&instr.operands[3])?;
+                self.program.emit(OpCode::BitExtract { dest, source, start, len });
+            },
+            "bfins" => {
+                if instr.operands.len() != 5 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("bfins expects 5 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let source = self.operand_to_operand(&instr.operands[1])?;
+                let field = self.operand_to_operand(&instr.operands[2])?;
+                let start = self.operand_to_operand(&instr.operands[3])?;
+                let len = self.operand_to_operand(&instr.operands[4])?;
+                self.program.emit(OpCode::BitInsert { dest, source, field, start, len });
+            },
             "jmp" => {
                 if instr.operands.len() != 1 {
                     return Err(AsmError::AssemblyError {
@@ -372,22 +655,139 @@ impl Assembler {
 
                 self.program.emit(opcode);
             },
+            "switch" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("switch expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let value = self.operand_to_operand(&instr.operands[0])?;
+                let default = self.qualify_label(&self.operand_to_string(&instr.operands[1])?);
+                let targets = match &instr.operands[2] {
+                    AsmOperand::List(labels) => labels
+                        .iter()
+                        .map(|label| Ok(self.qualify_label(&self.operand_to_string(label)?)))
+                        .collect::<Result<Vec<_>, AsmError>>()?,
+                    other => {
+                        return Err(AsmError::AssemblyError {
+                            message: format!("switch expects a [label, ...] table, got {:?}", other),
+                            location: None,
+                        });
+                    }
+                };
+                self.program.emit(OpCode::Switch { value, default, targets });
+            },
+            "jeq" | "jne" | "jlt" | "jle" | "jgt" | "jge" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 3 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let left = self.operand_to_operand(&instr.operands[0])?;
+                let right = self.operand_to_operand(&instr.operands[1])?;
+                let label = self.operand_to_string(&instr.operands[2])?;
+                let label = self.qualify_label(&label);
+
+                let opcode = match opcode_name.as_str() {
+                    "jeq" => OpCode::Jeq { left, right, label },
+                    "jne" => OpCode::Jne { left, right, label },
+                    "jlt" => OpCode::Jlt { left, right, label },
+                    "jle" => OpCode::Jle { left, right, label },
+                    "jgt" => OpCode::Jgt { left, right, label },
+                    "jge" => OpCode::Jge { left, right, label },
+                    _ => unreachable!(),
+                };
+
+                self.program.emit(opcode);
+            },
+            "select" => {
+                if instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("select expects 4 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let cond = self.operand_to_operand(&instr.operands[1])?;
+                let a = self.operand_to_operand(&instr.operands[2])?;
+                let b = self.operand_to_operand(&instr.operands[3])?;
+                self.program.emit(OpCode::Select { dest, cond, a, b });
+            },
             "call" => {
-                if instr.operands.len() < 2 {
+                if instr.operands.is_empty() {
                     return Err(AsmError::AssemblyError {
-                        message: format!("call expects at least 2 operands, got {}", instr.operands.len()),
+                        message: "call expects a function name".to_string(),
                         location: None,
                     });
                 }
 
-                let result = Some(self.operand_to_string(&instr.operands[0])?);
-                let func = self.operand_to_string(&instr.operands[1])?;
-                let args = instr.operands[2..]
+                // Both `call dest, f, args...` and `call f, args...`; the form is
+                // resolved in `check_calls` once every function is known
+                self.operand_to_string(&instr.operands[0])?;
+                let operands = instr
+                    .operands
                     .iter()
                     .map(|op| self.operand_to_operand(op))
                     .collect::<Result<Vec<_>, _>>()?;
+                let types = operands
+                    .iter()
+                    .map(|op| match op {
+                        Operand::Variable(name) => self.declared_type(name),
+                        _ => None,
+                    })
+                    .collect();
+                self.call_sites.push(CallSite {
+                    ip: self.program.instructions.len(),
+                    operands,
+                    types,
+                });
+
+                // Placeholder, replaced by `check_calls`
+                self.program.emit(OpCode::Call {
+                    result: None,
+                    func: String::new(),
+                    args: Vec::new(),
+                });
+            },
+            "assert" => {
+                if instr.operands.is_empty() || instr.operands.len() > 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("assert expects 1 or 2 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let cond = self.operand_to_operand(&instr.operands[0])?;
+                let message = match instr.operands.get(1) {
+                    Some(AsmOperand::Immediate(Immediate::String(message))) => Some(message.clone()),
+                    Some(other) => {
+                        return Err(AsmError::AssemblyError {
+                            message: format!("assert message must be a string literal, got {:?}", other),
+                            location: None,
+                        });
+                    }
+                    None => None,
+                };
+                self.program.emit(OpCode::Assert { cond, message });
+            },
+            "trap" => {
+                if instr.operands.len() != 1 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("trap expects 1 operand, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
 
-                self.program.emit(OpCode::Call { result, func, args });
+                let code = self.operand_to_operand(&instr.operands[0])?;
+                self.program.emit(OpCode::Trap { code });
+            },
+            "unreachable" => {
+                self.program.emit(OpCode::Unreachable);
             },
             "ret" => {
                 let value = if instr.operands.len() > 0 {
@@ -396,6 +796,18 @@ impl Assembler {
                     None
                 };
 
+                if let (Some((return_type, _)), Some(func)) = (&self.current_signature, &self.current_function) {
+                    let message = match (return_type, &value) {
+                        (DataType::Void, Some(_)) => format!("Void function '{}' cannot return a value", func),
+                        (DataType::Void, None) => String::new(),
+                        (_, None) => format!("Function '{}' must return a {:?} value", func, return_type),
+                        _ => String::new(),
+                    };
+                    if !message.is_empty() {
+                        return Err(self.error_at(instr.location.line, instr.location.column, message));
+                    }
+                }
+
                 self.program.emit(OpCode::Return { value });
             },
             "pop_arg" => {
@@ -431,146 +843,572 @@ impl Assembler {
                 let var = self.operand_to_string(&instr.operands[0])?;
                 self.program.emit(OpCode::Print { var });
             },
-            "alloc" => {
-                if instr.operands.len() != 2 {
+            "print_str" | "eprint_str" => {
+                if instr.operands.len() != 1 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("alloc expects 2 operands, got {}", instr.operands.len()),
+                        message: format!("{} expects 1 operand, got {}", opcode_name, instr.operands.len()),
                         location: None,
                     });
                 }
 
-                let dest = self.operand_to_string(&instr.operands[0])?;
-                let size = self.operand_to_operand(&instr.operands[1])?;
-
-                self.program.emit(OpCode::Alloc { dest, size });
+                let ptr = self.operand_to_string(&instr.operands[0])?;
+                if opcode_name == "print_str" {
+                    self.program.emit(OpCode::PrintStr { ptr });
+                } else {
+                    self.program.emit(OpCode::EPrintStr { ptr });
+                }
             },
-            "free" => {
+            "print_char" | "eprint_char" => {
                 if instr.operands.len() != 1 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("free expects 1 operand, got {}", instr.operands.len()),
+                        message: format!("{} expects 1 operand, got {}", opcode_name, instr.operands.len()),
                         location: None,
                     });
                 }
 
-                let ptr = self.operand_to_string(&instr.operands[0])?;
-                self.program.emit(OpCode::Free { ptr });
+                let value = self.operand_to_operand(&instr.operands[0])?;
+                if opcode_name == "print_char" {
+                    self.program.emit(OpCode::PrintChar { value });
+                } else {
+                    self.program.emit(OpCode::EPrintChar { value });
+                }
             },
-            "load" => {
+            "read_line" => {
                 if instr.operands.len() != 3 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("load expects 3 operands, got {}", instr.operands.len()),
+                        message: format!("read_line expects 3 operands, got {}", instr.operands.len()),
                         location: None,
                     });
                 }
 
                 let dest = self.operand_to_string(&instr.operands[0])?;
-                let ptr = self.operand_to_string(&instr.operands[1])?;
-                let dtype = self.operand_to_datatype(&instr.operands[2])?;
-
-                self.program.emit(OpCode::Load { dest, ptr, dtype });
+                let buf = self.operand_to_string(&instr.operands[1])?;
+                let cap = self.operand_to_operand(&instr.operands[2])?;
+                self.program.emit(OpCode::ReadLine { dest, buf, cap });
             },
-            "store" => {
-                if instr.operands.len() != 3 {
+            "input" | "read" => {
+                // input dest[, type[, status]] prompts; read dest, type[, status] does not
+                let min = if opcode_name == "read" { 2 } else { 1 };
+                if instr.operands.len() < min || instr.operands.len() > 3 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("store expects 3 operands, got {}", instr.operands.len()),
+                        message: format!(
+                            "{} expects {} to 3 operands, got {}",
+                            opcode_name,
+                            min,
+                            instr.operands.len()
+                        ),
                         location: None,
                     });
                 }
 
-                let ptr = self.operand_to_string(&instr.operands[0])?;
-                let source = self.operand_to_string(&instr.operands[1])?;
-                let dtype = self.operand_to_datatype(&instr.operands[2])?;
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let dtype = match instr.operands.get(1) {
+                    Some(operand) => self.operand_to_datatype(operand)?,
+                    None => DataType::I32,
+                };
+                if dtype == DataType::Void {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} cannot read a void value", opcode_name),
+                        location: None,
+                    });
+                }
+                let status = instr.operands.get(2).map(|s| self.operand_to_string(s)).transpose()?;
+                let prompt = opcode_name == "input";
+                self.program.emit(OpCode::Input { dest, dtype, status, prompt });
+            },
+            "printf" => {
+                if instr.operands.is_empty() {
+                    return Err(AsmError::AssemblyError {
+                        message: "printf expects a format string operand".to_string(),
+                        location: None,
+                    });
+                }
 
-                self.program.emit(OpCode::Store { ptr, source, dtype });
+                let fmt = self.operand_to_string(&instr.operands[0])?;
+                let args = instr.operands[1..]
+                    .iter()
+                    .map(|arg| self.operand_to_operand(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.program.emit(OpCode::Printf { fmt, args });
             },
-            "cast" => {
+            "fopen" => {
                 if instr.operands.len() != 3 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("cast expects 3 operands, got {}", instr.operands.len()),
+                        message: format!("fopen expects 3 operands, got {}", instr.operands.len()),
                         location: None,
                     });
                 }
 
                 let dest = self.operand_to_string(&instr.operands[0])?;
-                let source = self.operand_to_string(&instr.operands[1])?;
-                let target_type = self.operand_to_datatype(&instr.operands[2])?;
-
-                self.program.emit(OpCode::Cast {
-                    dest,
-                    source,
-                    target_type,
-                });
+                let path = self.operand_to_string(&instr.operands[1])?;
+                let mode = self.operand_to_operand(&instr.operands[2])?;
+                self.program.emit(OpCode::FileOpen { dest, path, mode });
             },
-            "sqrt" => {
-                if instr.operands.len() != 2 {
+            "fread" | "fwrite" => {
+                if instr.operands.len() != 4 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("sqrt expects 2 operands, got {}", instr.operands.len()),
+                        message: format!("{} expects 4 operands, got {}", opcode_name, instr.operands.len()),
                         location: None,
                     });
                 }
+
                 let dest = self.operand_to_string(&instr.operands[0])?;
-                let source = self.operand_to_operand(&instr.operands[1])?;
-                self.program.emit(OpCode::Sqrt { dest, source });
+                let handle = self.operand_to_operand(&instr.operands[1])?;
+                let buf = self.operand_to_string(&instr.operands[2])?;
+                let len = self.operand_to_operand(&instr.operands[3])?;
+                if opcode_name == "fread" {
+                    self.program.emit(OpCode::FileRead { dest, handle, buf, len });
+                } else {
+                    self.program.emit(OpCode::FileWrite { dest, handle, buf, len });
+                }
             },
-            "pow" => {
-                if instr.operands.len() != 3 {
+            "fseek" => {
+                if instr.operands.len() != 4 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("pow expects 3 operands, got {}", instr.operands.len()),
+                        message: format!("fseek expects 4 operands, got {}", instr.operands.len()),
                         location: None,
                     });
                 }
+
                 let dest = self.operand_to_string(&instr.operands[0])?;
-                let base = self.operand_to_operand(&instr.operands[1])?;
-                let exp = self.operand_to_operand(&instr.operands[2])?;
-                self.program.emit(OpCode::Pow { dest, base, exp });
+                let handle = self.operand_to_operand(&instr.operands[1])?;
+                let offset = self.operand_to_operand(&instr.operands[2])?;
+                let whence = self.operand_to_operand(&instr.operands[3])?;
+                self.program.emit(OpCode::FileSeek { dest, handle, offset, whence });
             },
-            "abs" => {
+            "fclose" => {
                 if instr.operands.len() != 2 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("abs expects 2 operands, got {}", instr.operands.len()),
+                        message: format!("fclose expects 2 operands, got {}", instr.operands.len()),
                         location: None,
                     });
                 }
+
                 let dest = self.operand_to_string(&instr.operands[0])?;
-                let source = self.operand_to_operand(&instr.operands[1])?;
-                self.program.emit(OpCode::Abs { dest, source });
+                let handle = self.operand_to_operand(&instr.operands[1])?;
+                self.program.emit(OpCode::FileClose { dest, handle });
             },
-            "min" => {
-                if instr.operands.len() != 3 {
+            "clock" => {
+                if instr.operands.len() != 1 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("min expects 3 operands, got {}", instr.operands.len()),
+                        message: format!("clock expects 1 operand, got {}", instr.operands.len()),
                         location: None,
                     });
                 }
+
                 let dest = self.operand_to_string(&instr.operands[0])?;
-                let a = self.operand_to_operand(&instr.operands[1])?;
-                let b = self.operand_to_operand(&instr.operands[2])?;
-                self.program.emit(OpCode::Min { dest, a, b });
+                self.program.emit(OpCode::Clock { dest });
             },
-            "max" => {
-                if instr.operands.len() != 3 {
+            "sleep" | "srand" => {
+                if instr.operands.len() != 1 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("max expects 3 operands, got {}", instr.operands.len()),
+                        message: format!("{} expects 1 operand, got {}", opcode_name, instr.operands.len()),
                         location: None,
                     });
                 }
-                let dest = self.operand_to_string(&instr.operands[0])?;
-                let a = self.operand_to_operand(&instr.operands[1])?;
-                let b = self.operand_to_operand(&instr.operands[2])?;
-                self.program.emit(OpCode::Max { dest, a, b });
+
+                let value = self.operand_to_operand(&instr.operands[0])?;
+                if opcode_name == "sleep" {
+                    self.program.emit(OpCode::Sleep { millis: value });
+                } else {
+                    self.program.emit(OpCode::Srand { seed: value });
+                }
             },
-            "sin" => {
-                if instr.operands.len() != 2 {
+            "rand" => {
+                if instr.operands.is_empty() || instr.operands.len() > 2 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("sin expects 2 operands, got {}", instr.operands.len()),
+                        message: format!("rand expects 1 or 2 operands, got {}", instr.operands.len()),
                         location: None,
                     });
                 }
+
                 let dest = self.operand_to_string(&instr.operands[0])?;
-                let source = self.operand_to_operand(&instr.operands[1])?;
-                self.program.emit(OpCode::Sin { dest, source });
+                let bound = instr.operands.get(1).map(|b| self.operand_to_operand(b)).transpose()?;
+                self.program.emit(OpCode::Rand { dest, bound });
             },
-            "cos" => {
+            "getenv" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("getenv expects 2 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let name = self.operand_to_string(&instr.operands[1])?;
+                self.program.emit(OpCode::GetEnv { dest, name });
+            },
+            "tcp_connect" | "tcp_listen" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 2 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let addr = self.operand_to_string(&instr.operands[1])?;
+                if opcode_name == "tcp_connect" {
+                    self.program.emit(OpCode::TcpConnect { dest, addr });
+                } else {
+                    self.program.emit(OpCode::TcpListen { dest, addr });
+                }
+            },
+            "tcp_accept" | "tcp_close" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 2 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let handle = self.operand_to_operand(&instr.operands[1])?;
+                if opcode_name == "tcp_accept" {
+                    self.program.emit(OpCode::TcpAccept { dest, handle });
+                } else {
+                    self.program.emit(OpCode::TcpClose { dest, handle });
+                }
+            },
+            "tcp_send" | "tcp_recv" => {
+                if instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 4 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let handle = self.operand_to_operand(&instr.operands[1])?;
+                let buf = self.operand_to_string(&instr.operands[2])?;
+                let len = self.operand_to_operand(&instr.operands[3])?;
+                if opcode_name == "tcp_send" {
+                    self.program.emit(OpCode::TcpSend { dest, handle, buf, len });
+                } else {
+                    self.program.emit(OpCode::TcpRecv { dest, handle, buf, len });
+                }
+            },
+            "tcp_nonblock" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("tcp_nonblock expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let handle = self.operand_to_operand(&instr.operands[1])?;
+                let enabled = self.operand_to_operand(&instr.operands[2])?;
+                self.program.emit(OpCode::TcpNonblock { dest, handle, enabled });
+            },
+            "getkey" | "pollkey" => {
+                if instr.operands.len() != 1 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 1 operand, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let wait = opcode_name == "getkey";
+                self.program.emit(OpCode::ReadKey { dest, wait });
+            },
+            "cursor" | "color" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 2 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let first = self.operand_to_operand(&instr.operands[0])?;
+                let second = self.operand_to_operand(&instr.operands[1])?;
+                if opcode_name == "cursor" {
+                    self.program.emit(OpCode::SetCursor { row: first, col: second });
+                } else {
+                    self.program.emit(OpCode::SetColor { fg: first, bg: second });
+                }
+            },
+            "clear" => {
+                if !instr.operands.is_empty() {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("clear expects no operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                self.program.emit(OpCode::ClearScreen);
+            },
+            "alloc" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("alloc expects 2 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let size = self.operand_to_operand(&instr.operands[1])?;
+
+                self.program.emit(OpCode::Alloc { dest, size });
+            },
+            "free" => {
+                if instr.operands.len() != 1 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("free expects 1 operand, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let ptr = self.operand_to_string(&instr.operands[0])?;
+                self.program.emit(OpCode::Free { ptr });
+            },
+            "load" | "load.le" | "load.be" => {
+                // load dest, addr, type  |  load dest, ptr, index, type
+                if instr.operands.len() != 3 && instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 3 or 4 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let dtype = self.operand_to_datatype(instr.operands.last().unwrap())?;
+                let index = if instr.operands.len() == 4 { Some(&instr.operands[2]) } else { None };
+                let addr = self.operand_to_address(&instr.operands[1], index, dtype)?;
+
+                let endian = mnemonic_endian(&opcode_name);
+                self.program.emit(OpCode::Load { dest, addr, dtype, endian });
+            },
+            "store" | "store.le" | "store.be" => {
+                // store addr, src, type  |  store ptr, index, src, type
+                if instr.operands.len() != 3 && instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 3 or 4 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dtype = self.operand_to_datatype(instr.operands.last().unwrap())?;
+                let index = if instr.operands.len() == 4 { Some(&instr.operands[1]) } else { None };
+                let addr = self.operand_to_address(&instr.operands[0], index, dtype)?;
+                let source = match self.operand_to_operand(&instr.operands[instr.operands.len() - 2])? {
+                    Operand::Immediate(value) => {
+                        let value = value.cast(dtype).map_err(|e| AsmError::AssemblyError {
+                            message: format!("store: {}", e),
+                            location: None,
+                        })?;
+                        Operand::Immediate(value)
+                    },
+                    source => source,
+                };
+
+                let endian = mnemonic_endian(&opcode_name);
+                self.program.emit(OpCode::Store { addr, source, dtype, endian });
+            },
+            "loadv" | "loadv.le" | "loadv.be" | "storev" | "storev.le" | "storev.be" => {
+                // loadv buf, addr, count, type  |  storev addr, buf, count, type
+                if instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 4 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dtype = self.operand_to_datatype(&instr.operands[3])?;
+                let count = self.operand_to_operand(&instr.operands[2])?;
+                let endian = mnemonic_endian(&opcode_name);
+                if opcode_name.starts_with("loadv") {
+                    let buf = self.operand_to_string(&instr.operands[0])?;
+                    let addr = self.operand_to_address(&instr.operands[1], None, dtype)?;
+                    self.program.emit(OpCode::LoadVec { buf, addr, count, dtype, endian });
+                } else {
+                    let addr = self.operand_to_address(&instr.operands[0], None, dtype)?;
+                    let buf = self.operand_to_string(&instr.operands[1])?;
+                    self.program.emit(OpCode::StoreVec { addr, buf, count, dtype, endian });
+                }
+            },
+            "memcpy" | "memmove" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!(
+                            "{} expects 3 operands, got {}",
+                            opcode_name,
+                            instr.operands.len()
+                        ),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let src = self.operand_to_string(&instr.operands[1])?;
+                let len = self.operand_to_operand(&instr.operands[2])?;
+
+                let opcode = match opcode_name.as_str() {
+                    "memcpy" => OpCode::MemCopy { dest, src, len },
+                    "memmove" => OpCode::MemMove { dest, src, len },
+                    _ => unreachable!(),
+                };
+
+                self.program.emit(opcode);
+            },
+            "memset" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("memset expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let value = self.operand_to_operand(&instr.operands[1])?;
+                let len = self.operand_to_operand(&instr.operands[2])?;
+
+                self.program.emit(OpCode::MemSet { dest, value, len });
+            },
+            "memcmp" => {
+                if instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("memcmp expects 4 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let result = self.operand_to_string(&instr.operands[0])?;
+                let a = self.operand_to_string(&instr.operands[1])?;
+                let b = self.operand_to_string(&instr.operands[2])?;
+                let len = self.operand_to_operand(&instr.operands[3])?;
+
+                self.program.emit(OpCode::MemCmp { result, a, b, len });
+            },
+            "sizeof" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("sizeof expects 2 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                // Primitive types stay symbolic; struct and array sizes are known here
+                let target = match &instr.operands[1] {
+                    AsmOperand::Variable(name) => match self.operand_to_datatype(&instr.operands[1]) {
+                        Ok(dtype) => Operand::Type(dtype),
+                        Err(_) => Operand::Immediate(Value::I32(self.sizeof_operand(name)? as i32)),
+                    },
+                    other => self.operand_to_operand(other)?,
+                };
+
+                self.program.emit(OpCode::SizeOf { dest, target });
+            },
+            "alloc_array" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("alloc_array expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let elem = self.operand_to_datatype(&instr.operands[1])?;
+                if elem == DataType::Void {
+                    return Err(AsmError::AssemblyError {
+                        message: "alloc_array element type cannot be void".to_string(),
+                        location: None,
+                    });
+                }
+                let count = self.operand_to_operand(&instr.operands[2])?;
+
+                self.program.emit(OpCode::AllocArray { dest, elem: Operand::Type(elem), count });
+            },
+            "cast" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("cast expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let source = self.operand_to_string(&instr.operands[1])?;
+                let target_type = self.operand_to_datatype(&instr.operands[2])?;
+
+                self.program.emit(OpCode::Cast {
+                    dest,
+                    source,
+                    target_type,
+                });
+            },
+            "sqrt" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("sqrt expects 2 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let source = self.operand_to_operand(&instr.operands[1])?;
+                self.program.emit(OpCode::Sqrt { dest, source });
+            },
+            "pow" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("pow expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let base = self.operand_to_operand(&instr.operands[1])?;
+                let exp = self.operand_to_operand(&instr.operands[2])?;
+                self.program.emit(OpCode::Pow { dest, base, exp });
+            },
+            "abs" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("abs expects 2 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let source = self.operand_to_operand(&instr.operands[1])?;
+                self.program.emit(OpCode::Abs { dest, source });
+            },
+            "min" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("min expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let a = self.operand_to_operand(&instr.operands[1])?;
+                let b = self.operand_to_operand(&instr.operands[2])?;
+                self.program.emit(OpCode::Min { dest, a, b });
+            },
+            "max" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("max expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let a = self.operand_to_operand(&instr.operands[1])?;
+                let b = self.operand_to_operand(&instr.operands[2])?;
+                self.program.emit(OpCode::Max { dest, a, b });
+            },
+            "sin" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("sin expects 2 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let source = self.operand_to_operand(&instr.operands[1])?;
+                self.program.emit(OpCode::Sin { dest, source });
+            },
+            "cos" => {
                 if instr.operands.len() != 2 {
                     return Err(AsmError::AssemblyError {
                         message: format!("cos expects 2 operands, got {}", instr.operands.len()),
@@ -579,18 +1417,316 @@ impl Assembler {
                 }
                 let dest = self.operand_to_string(&instr.operands[0])?;
                 let source = self.operand_to_operand(&instr.operands[1])?;
-                self.program.emit(OpCode::Cos { dest, source });
+                self.program.emit(OpCode::Cos { dest, source });
+            },
+            "tan" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("tan expects 2 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let source = self.operand_to_operand(&instr.operands[1])?;
+                self.program.emit(OpCode::Tan { dest, source });
+            },
+            "floor" | "ceil" | "round" | "trunc" | "exp" | "ln" | "log2" | "log10" | "asin" | "acos"
+            | "atan" | "isnan" | "isinf" | "isfinite" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 2 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let source = self.operand_to_operand(&instr.operands[1])?;
+
+                let opcode = match opcode_name.as_str() {
+                    "floor" => OpCode::Floor { dest, source },
+                    "ceil" => OpCode::Ceil { dest, source },
+                    "round" => OpCode::Round { dest, source },
+                    "trunc" => OpCode::Trunc { dest, source },
+                    "exp" => OpCode::Exp { dest, source },
+                    "ln" => OpCode::Ln { dest, source },
+                    "log2" => OpCode::Log2 { dest, source },
+                    "log10" => OpCode::Log10 { dest, source },
+                    "asin" => OpCode::Asin { dest, source },
+                    "acos" => OpCode::Acos { dest, source },
+                    "atan" => OpCode::Atan { dest, source },
+                    "isnan" => OpCode::IsNan { dest, source },
+                    "isinf" => OpCode::IsInf { dest, source },
+                    "isfinite" => OpCode::IsFinite { dest, source },
+                    _ => unreachable!(),
+                };
+
+                self.program.emit(opcode);
+            },
+            "atan2" | "hypot" | "copysign" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 3 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let first = self.operand_to_operand(&instr.operands[1])?;
+                let second = self.operand_to_operand(&instr.operands[2])?;
+
+                let opcode = match opcode_name.as_str() {
+                    "atan2" => OpCode::Atan2 { dest, y: first, x: second },
+                    "hypot" => OpCode::Hypot { dest, x: first, y: second },
+                    "copysign" => OpCode::CopySign { dest, magnitude: first, sign: second },
+                    _ => unreachable!(),
+                };
+
+                self.program.emit(opcode);
+            },
+            "fma" => {
+                if instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("fma expects 4 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let a = self.operand_to_operand(&instr.operands[1])?;
+                let b = self.operand_to_operand(&instr.operands[2])?;
+                let c = self.operand_to_operand(&instr.operands[3])?;
+                self.program.emit(OpCode::Fma { dest, a, b, c });
+            },
+            "vsplat" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("vsplat expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let value = self.operand_to_operand(&instr.operands[1])?;
+                let dtype = self.operand_to_datatype(&instr.operands[2])?;
+                if !matches!(dtype, DataType::Vector(_)) {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("vsplat expects a vector type, got {:?}", dtype),
+                        location: None,
+                    });
+                }
+                self.program.emit(OpCode::VecSplat { dest, value, dtype });
+            },
+            "vextract" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("vextract expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let vector = self.operand_to_operand(&instr.operands[1])?;
+                let lane = self.operand_to_operand(&instr.operands[2])?;
+                self.program.emit(OpCode::VecExtract { dest, vector, lane });
+            },
+            "vinsert" => {
+                if instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("vinsert expects 4 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let vector = self.operand_to_operand(&instr.operands[1])?;
+                let lane = self.operand_to_operand(&instr.operands[2])?;
+                let value = self.operand_to_operand(&instr.operands[3])?;
+                self.program.emit(OpCode::VecInsert { dest, vector, lane, value });
+            },
+            "vshuffle" => {
+                if instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("vshuffle expects 4 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let a = self.operand_to_operand(&instr.operands[1])?;
+                let b = self.operand_to_operand(&instr.operands[2])?;
+                let lanes = match &instr.operands[3] {
+                    AsmOperand::List(items) => items
+                        .iter()
+                        .map(|item| match item {
+                            AsmOperand::Immediate(Immediate::Integer(lane)) => u8::try_from(*lane).ok(),
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<u8>>>(),
+                    _ => None,
+                };
+                let lanes = lanes.ok_or_else(|| AsmError::AssemblyError {
+                    message: format!("vshuffle expects a [lane, ...] index list, got {:?}", instr.operands[3]),
+                    location: None,
+                })?;
+                self.program.emit(OpCode::VecShuffle { dest, a, b, lanes });
+            },
+            "veq" | "vne" | "vlt" | "vle" | "vgt" | "vge" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 3 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let left = self.operand_to_operand(&instr.operands[1])?;
+                let right = self.operand_to_operand(&instr.operands[2])?;
+
+                let opcode = match opcode_name.as_str() {
+                    "veq" => OpCode::VecEq { dest, left, right },
+                    "vne" => OpCode::VecNe { dest, left, right },
+                    "vlt" => OpCode::VecLt { dest, left, right },
+                    "vle" => OpCode::VecLe { dest, left, right },
+                    "vgt" => OpCode::VecGt { dest, left, right },
+                    "vge" => OpCode::VecGe { dest, left, right },
+                    _ => unreachable!(),
+                };
+
+                self.program.emit(opcode);
+            },
+            "str_len" | "str_to_int" | "int_to_str" | "float_to_str" | "ptr_to_str" | "str_to_ptr" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 2 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let source = self.operand_to_operand(&instr.operands[1])?;
+
+                let opcode = match opcode_name.as_str() {
+                    "str_len" => OpCode::StrLen { dest, source },
+                    "str_to_int" => OpCode::StrToInt { dest, source },
+                    "int_to_str" => OpCode::IntToStr { dest, source },
+                    "float_to_str" => OpCode::FloatToStr { dest, source },
+                    "ptr_to_str" => OpCode::PtrToStr { dest, ptr: source },
+                    "str_to_ptr" => OpCode::StrToPtr { dest, source },
+                    _ => unreachable!(),
+                };
+
+                self.program.emit(opcode);
             },
-            "tan" => {
+            "str_concat" | "str_find" | "str_cmp" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 3 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let first = self.operand_to_operand(&instr.operands[1])?;
+                let second = self.operand_to_operand(&instr.operands[2])?;
+
+                let opcode = match opcode_name.as_str() {
+                    "str_concat" => OpCode::StrConcat { dest, left: first, right: second },
+                    "str_find" => OpCode::StrFind { dest, haystack: first, needle: second },
+                    "str_cmp" => OpCode::StrCmp { dest, left: first, right: second },
+                    _ => unreachable!(),
+                };
+
+                self.program.emit(opcode);
+            },
+            "str_sub" => {
+                if instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 4 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let source = self.operand_to_operand(&instr.operands[1])?;
+                let start = self.operand_to_operand(&instr.operands[2])?;
+                let len = self.operand_to_operand(&instr.operands[3])?;
+                self.program.emit(OpCode::StrSub { dest, source, start, len });
+            },
+            "array_new" | "map_new" => {
+                if instr.operands.len() != 1 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 1 operand, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let opcode = if opcode_name == "array_new" {
+                    OpCode::ArrayNew { dest }
+                } else {
+                    OpCode::MapNew { dest }
+                };
+                self.program.emit(opcode);
+            },
+            "array_push" => {
                 if instr.operands.len() != 2 {
                     return Err(AsmError::AssemblyError {
-                        message: format!("tan expects 2 operands, got {}", instr.operands.len()),
+                        message: format!("array_push expects 2 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let array = self.operand_to_operand(&instr.operands[0])?;
+                let value = self.operand_to_operand(&instr.operands[1])?;
+                self.program.emit(OpCode::ArrayPush { array, value });
+            },
+            "array_pop" | "obj_len" => {
+                if instr.operands.len() != 2 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 2 operands, got {}", opcode_name, instr.operands.len()),
                         location: None,
                     });
                 }
                 let dest = self.operand_to_string(&instr.operands[0])?;
                 let source = self.operand_to_operand(&instr.operands[1])?;
-                self.program.emit(OpCode::Tan { dest, source });
+                let opcode = if opcode_name == "array_pop" {
+                    OpCode::ArrayPop { dest, array: source }
+                } else {
+                    OpCode::ObjLen { dest, object: source }
+                };
+                self.program.emit(opcode);
+            },
+            "map_has" | "map_key" | "obj_get" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("{} expects 3 operands, got {}", opcode_name, instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let first = self.operand_to_operand(&instr.operands[1])?;
+                let second = self.operand_to_operand(&instr.operands[2])?;
+
+                let opcode = match opcode_name.as_str() {
+                    "map_has" => OpCode::MapHas { dest, map: first, key: second },
+                    "map_key" => OpCode::MapKey { dest, map: first, index: second },
+                    "obj_get" => OpCode::ObjGet { dest, object: first, key: second },
+                    _ => unreachable!(),
+                };
+
+                self.program.emit(opcode);
+            },
+            "obj_set" => {
+                if instr.operands.len() != 3 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("obj_set expects 3 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let object = self.operand_to_operand(&instr.operands[0])?;
+                let key = self.operand_to_operand(&instr.operands[1])?;
+                let value = self.operand_to_operand(&instr.operands[2])?;
+                self.program.emit(OpCode::ObjSet { object, key, value });
+            },
+            "vselect" => {
+                if instr.operands.len() != 4 {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("vselect expects 4 operands, got {}", instr.operands.len()),
+                        location: None,
+                    });
+                }
+                let dest = self.operand_to_string(&instr.operands[0])?;
+                let mask = self.operand_to_operand(&instr.operands[1])?;
+                let a = self.operand_to_operand(&instr.operands[2])?;
+                let b = self.operand_to_operand(&instr.operands[3])?;
+                self.program.emit(OpCode::VecSelect { dest, mask, a, b });
             },
             _ => {
                 return Err(AsmError::AssemblyError {
@@ -603,6 +1739,180 @@ impl Assembler {
         Ok(())
     }
 
+    // Struct constants referenced by an instruction, kept so the disassembler can show field names
+    fn struct_refs(&self, operands: &[AsmOperand]) -> Vec<String> {
+        use crate::asm::ast::AddressFactor;
+
+        let is_field = |name: &str| {
+            name.split_once('.').is_some_and(|(struct_name, _)| {
+                self.program.structs.iter().any(|s| s.name == struct_name)
+            }) && self.defines.contains_key(name)
+        };
+        let mut refs = Vec::new();
+        for operand in operands {
+            match operand {
+                AsmOperand::Variable(name) => {
+                    if is_field(name) {
+                        refs.push(name.clone());
+                    } else if let Some((_, field)) = name.split_once('.')
+                        && is_field(field)
+                    {
+                        refs.push(field.to_string());
+                    }
+                },
+                AsmOperand::Memory { terms, .. } => {
+                    for term in terms {
+                        for factor in &term.factors {
+                            if let AddressFactor::Name(name) = factor
+                                && is_field(name)
+                            {
+                                refs.push(name.clone());
+                            }
+                        }
+                    }
+                },
+                AsmOperand::SizeOf(name) if self.program.structs.iter().any(|s| s.name == *name) => {
+                    refs.push(format!("sizeof({})", name));
+                },
+                _ => {},
+            }
+        }
+        refs
+    }
+
+    fn sizeof_operand(&self, name: &str) -> Result<usize, AsmError> {
+        use crate::asm::ast::DefineValue;
+
+        if let Some(layout) = self.program.structs.iter().find(|s| s.name == name) {
+            return Ok(layout.size);
+        }
+        if let Some(DefineValue::Integer(size)) = self.defines.get(&format!("{}.size", name)) {
+            return Ok(*size as usize);
+        }
+        if let Ok(dtype) = self.operand_to_datatype(&AsmOperand::Variable(name.to_string())) {
+            return Ok(Variable::size_of(dtype));
+        }
+        Err(AsmError::AssemblyError {
+            message: format!("sizeof: unknown type, struct or array '{}'", name),
+            location: None,
+        })
+    }
+
+    // Resolves a load/store address. Bracketed terms fold `.define` constants into
+    // the offset and allow at most one index variable; the separate `index`
+    // operand form scales by the element size of `dtype`.
+    fn operand_to_address(
+        &self,
+        operand: &AsmOperand,
+        index: Option<&AsmOperand>,
+        dtype: DataType,
+    ) -> Result<Address, AsmError> {
+        use crate::asm::ast::{AddressFactor, DefineValue};
+
+        let overflow = || AsmError::AssemblyError {
+            message: format!("Address offset overflows in {:?}", operand),
+            location: None,
+        };
+
+        let mut addr = match operand {
+            AsmOperand::Variable(name) => {
+                // `p.Point.y` is shorthand for `p[Point.y]`
+                match name.split_once('.') {
+                    Some((base, field)) if !self.defines.contains_key(name) => {
+                        match self.defines.get(field) {
+                            Some(DefineValue::Integer(offset)) => {
+                                let mut addr = Address::new(base);
+                                addr.offset = *offset;
+                                addr
+                            },
+                            _ => Address::new(name.as_str()),
+                        }
+                    },
+                    _ => Address::new(name.as_str()),
+                }
+            },
+            AsmOperand::Memory { base, terms } => {
+                let mut addr = Address::new(base.as_str());
+                for term in terms {
+                    let mut constant: i64 = 1;
+                    let mut index_name = None;
+                    for factor in &term.factors {
+                        let value = match factor {
+                            AddressFactor::Integer(val) => *val,
+                            AddressFactor::Name(name) => match self.defines.get(name) {
+                                Some(DefineValue::Integer(val)) => *val,
+                                Some(_) => {
+                                    return Err(AsmError::AssemblyError {
+                                        message: format!("Cannot use non-integer define '{}' in an address", name),
+                                        location: None,
+                                    });
+                                },
+                                None => {
+                                    if index_name.is_some() {
+                                        return Err(AsmError::AssemblyError {
+                                            message: format!("Address term multiplies two variables in {:?}", operand),
+                                            location: None,
+                                        });
+                                    }
+                                    index_name = Some(name.clone());
+                                    continue;
+                                },
+                            },
+                        };
+                        constant = constant.checked_mul(value).ok_or_else(overflow)?;
+                    }
+                    match index_name {
+                        Some(name) => {
+                            if addr.index.is_some() {
+                                return Err(AsmError::AssemblyError {
+                                    message: format!("Address {:?} uses more than one index variable", operand),
+                                    location: None,
+                                });
+                            }
+                            addr.index = Some(name);
+                            addr.scale = constant;
+                        },
+                        None => addr.offset = addr.offset.checked_add(constant).ok_or_else(overflow)?,
+                    }
+                }
+                addr
+            },
+            _ => {
+                return Err(AsmError::AssemblyError {
+                    message: format!("Expected pointer or address, got {:?}", operand),
+                    location: None,
+                });
+            },
+        };
+
+        if let Some(index) = index {
+            if !addr.is_plain() {
+                return Err(AsmError::AssemblyError {
+                    message: "Cannot combine a bracketed address with an index operand".to_string(),
+                    location: None,
+                });
+            }
+            let scale = Variable::size_of(dtype) as i64;
+            match self.operand_to_operand(index)? {
+                Operand::Variable(name) => {
+                    addr.index = Some(name);
+                    addr.scale = scale;
+                },
+                Operand::Immediate(Value::I32(val)) => {
+                    addr.offset = (val as i64).checked_mul(scale).ok_or_else(overflow)?;
+                },
+                other => {
+                    return Err(AsmError::AssemblyError {
+                        message: format!("Expected integer index, got {:?}", other),
+                        location: None,
+                    });
+                },
+            }
+        }
+
+        Ok(addr)
+    }
+
     fn operand_to_string(&self, operand: &AsmOperand) -> Result<String, AsmError> {
         match operand {
             AsmOperand::Variable(name) => Ok(name.clone()),
@@ -637,13 +1947,31 @@ impl Assembler {
                 }
             },
             AsmOperand::Label(name) => Ok(Operand::Label(name.clone())),
+            AsmOperand::Memory { .. } => Err(AsmError::AssemblyError {
+                message: format!("Address operands are only valid in load/store, got {:?}", operand),
+                location: None,
+            }),
+            AsmOperand::List(_) => Err(AsmError::AssemblyError {
+                message: format!("Label lists are only valid in switch, got {:?}", operand),
+                location: None,
+            }),
+            AsmOperand::Parameters(_) => Err(AsmError::AssemblyError {
+                message: format!("Parameter lists are only valid in func_begin, got {:?}", operand),
+                location: None,
+            }),
+            AsmOperand::SizeOf(name) => {
+                let size = self.sizeof_operand(name)?;
+                Ok(Operand::Immediate(Value::I32(size as i32)))
+            },
             AsmOperand::Immediate(imm) => {
                 let value = match imm {
                     Immediate::Integer(val) => Value::I32(*val as i32),
                     Immediate::Float(val) => Value::F32(*val as f32),
-                    Immediate::String(_) => {
+                    Immediate::Char(val) => Value::Char(*val),
+                    Immediate::String(val) => Value::Str(val.as_str().into()),
+                    Immediate::Array(_) => {
                         return Err(AsmError::AssemblyError {
-                            message: "String immediates are not supported as instruction operands".to_string(),
+                            message: "Array immediates are not supported as instruction operands".to_string(),
                             location: None,
                         });
                     }
@@ -661,14 +1989,26 @@ impl Assembler {
                     "i16" => Ok(DataType::I16),
                     "i32" => Ok(DataType::I32),
                     "i64" => Ok(DataType::I64),
+                    "i128" => Ok(DataType::I128),
                     "u8" => Ok(DataType::U8),
                     "u16" => Ok(DataType::U16),
                     "u32" => Ok(DataType::U32),
                     "u64" => Ok(DataType::U64),
+                    "u128" => Ok(DataType::U128),
                     "f32" => Ok(DataType::F32),
                     "f64" => Ok(DataType::F64),
                     "ptr" => Ok(DataType::Ptr),
                     "void" => Ok(DataType::Void),
+                    "bool" => Ok(DataType::Bool),
+                    "char" => Ok(DataType::Char),
+                    "string" => Ok(DataType::Str),
+                    "ref" => Ok(DataType::Ref),
+                    "v16u8" => Ok(DataType::Vector(VectorType::V16U8)),
+                    "v8i16" => Ok(DataType::Vector(VectorType::V8I16)),
+                    "v4i32" => Ok(DataType::Vector(VectorType::V4I32)),
+                    "v2i64" => Ok(DataType::Vector(VectorType::V2I64)),
+                    "v4f32" => Ok(DataType::Vector(VectorType::V4F32)),
+                    "v2f64" => Ok(DataType::Vector(VectorType::V2F64)),
                     _ => Err(AsmError::AssemblyError {
                         message: format!("Unknown data type: {}", name),
                         location: None,
@@ -694,6 +2034,128 @@ impl Assembler {
     fn resolve_labels(&mut self) -> Result<(), AsmError> {
         Ok(())
     }
+
+    // Declared type of a variable visible at this point, if it has one
+    fn declared_type(&self, name: &str) -> Option<DataType> {
+        self.local_types.get(name).copied().or_else(|| {
+            self.program
+                .globals
+                .iter()
+                .find(|var| var.name == name)
+                .map(|var| var.dtype)
+        })
+    }
+
+    fn error_at(&self, line: usize, column: usize, message: String) -> AsmError {
+        AsmError::AssemblyError {
+            message,
+            location: Some(crate::asm::error::SourceLocation {
+                line,
+                column,
+                file: self.filename.clone(),
+            }),
+        }
+    }
+
+    fn call_error(&self, ip: usize, message: String) -> AsmError {
+        match self.instruction_locations.get(&ip) {
+            Some(loc) => self.error_at(loc.line, loc.column, message),
+            None => AsmError::AssemblyError { message, location: None },
+        }
+    }
+
+    // Settle the form of each call, then check it against the callee's signature
+    fn check_calls(&mut self) -> Result<(), AsmError> {
+        let name = |op: &Operand| match op {
+            Operand::Variable(name) | Operand::Label(name) => Some(name.clone()),
+            _ => None,
+        };
+
+        for site in std::mem::take(&mut self.call_sites) {
+            // The first operand is the callee if it names a function and is not a
+            // declared variable; otherwise it is the destination of the second
+            let first = name(&site.operands[0]).unwrap_or_default();
+            let second = site.operands.get(1).and_then(name).unwrap_or_default();
+            let is_function = |name: &str| self.program.functions.contains_key(name);
+            let (result, func, args, arg_types) = if site.types[0].is_none() && is_function(&first) {
+                (None, first, &site.operands[1..], &site.types[1..])
+            } else if is_function(&second) {
+                (Some(first), second, &site.operands[2..], &site.types[2..])
+            } else {
+                let message = if site.operands.len() >= 2 && !second.is_empty() {
+                    format!("Unknown function: neither '{}' nor '{}' is a function", first, second)
+                } else {
+                    format!("Unknown function: {}", first)
+                };
+                return Err(self.call_error(site.ip, message));
+            };
+
+            let callee = &self.program.functions[&func];
+            let result_type = result.as_ref().and(site.types[0]);
+            self.check_call(callee, site.ip, result.as_deref(), result_type, args, arg_types)?;
+
+            self.program.instructions[site.ip] = OpCode::Call {
+                result,
+                func,
+                args: args.to_vec(),
+            };
+        }
+        Ok(())
+    }
+
+    fn check_call(
+        &self,
+        func: &Function,
+        ip: usize,
+        result: Option<&str>,
+        result_type: Option<DataType>,
+        args: &[Operand],
+        arg_types: &[Option<DataType>],
+    ) -> Result<(), AsmError> {
+        let error = |message: String| self.call_error(ip, message);
+
+        if let (DataType::Void, Some(dest)) = (func.return_type, result) {
+            return Err(error(format!(
+                "Function '{}' returns void; call it without a destination instead of '{}'",
+                func.name, dest
+            )));
+        }
+        if !func.typed {
+            return Ok(());
+        }
+
+        if args.len() != func.parameters.len() {
+            return Err(error(format!(
+                "Function '{}' expects {} argument(s), got {}",
+                func.name,
+                func.parameters.len(),
+                args.len()
+            )));
+        }
+
+        for ((arg, arg_type), param) in args.iter().zip(arg_types).zip(&func.parameters) {
+            let mismatch = match (arg, arg_type) {
+                // Immediates convert only when nothing is lost, as for array initializers
+                (Operand::Immediate(value), _) if value.cast_exact(param.dtype).is_none() => Some(value.dtype()),
+                (_, Some(dtype)) if *dtype != param.dtype => Some(*dtype),
+                _ => None,
+            };
+            if let Some(got) = mismatch {
+                return Err(error(format!(
+                    "Argument '{}' of '{}' expects {:?}, got {:?}",
+                    param.name, func.name, param.dtype, got
+                )));
+            }
+        }
+
+        if let Some(result_type) = result_type.filter(|dtype| *dtype != func.return_type) {
+            return Err(error(format!(
+                "Function '{}' returns {:?}, but the result is stored in a {:?} variable",
+                func.name, func.return_type, result_type
+            )));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -720,4 +2182,95 @@ main:
         assert!(program.functions.contains_key("main"));
         assert_eq!(program.globals.len(), 1);
     }
+
+    #[test]
+    fn test_assemble_data_arrays() {
+        let source = r#"
+section .data
+    table: i32[4] = {10, 20, 30}
+    buf: u8[256]
+    count: i32
+    bytes: i32
+    third: i32
+
+section .text
+main:
+    func_begin i32
+    local p: ptr
+    set count, table.len
+    set bytes, buf.size
+    add p, table, 8
+    load third, p, i32
+    ret 0
+    func_end
+"#;
+
+        let program = assemble(source, "test.vasm".to_string()).unwrap();
+        assert_eq!(program.data_blocks.len(), 2);
+        assert_eq!(program.data_blocks[0].bytes.len(), 16);
+        assert_eq!(program.data_blocks[1].bytes.len(), 256);
+
+        let mut vm = crate::vm::VM::new(program);
+        vm.run().unwrap();
+        assert_eq!(vm.get_globals().get("count"), Some(&Value::I32(4)));
+        assert_eq!(vm.get_globals().get("bytes"), Some(&Value::I32(256)));
+        assert_eq!(vm.get_globals().get("third"), Some(&Value::I32(30)));
+        assert!(matches!(vm.get_globals().get("table"), Some(Value::Ptr(_))));
+
+        // Initializers are range-checked against the element type instead of wrapping
+        for init in ["{300, 1}", "{1, -1}", "{300.0}", "{1.5}", "{'\u{263A}'}"] {
+            let bad = source.replace("buf: u8[256]", &format!("buf: u8[2] = {}", init));
+            let err = assemble(&bad, "test.vasm".to_string()).unwrap_err().to_string();
+            assert!(err.contains("initializer out of range for u8"), "{}", err);
+        }
+
+        let bad = source.replace("buf: u8[256]", "buf: ref[2]");
+        let err = assemble(&bad, "test.vasm".to_string()).unwrap_err().to_string();
+        assert!(err.contains("cannot have element type ref"), "{}", err);
+    }
+
+    #[test]
+    fn test_assemble_struct_layout() {
+        let source = r#"
+struct Rec {
+    tag: u8
+    id: i32
+    name: u8[5]
+    value: i64
+}
+
+section .data
+    value_offset: i32
+    rec_size: i32
+
+section .text
+main:
+    func_begin i32
+    set value_offset, Rec.value
+    set rec_size, sizeof(Rec)
+    ret 0
+    func_end
+"#;
+
+        let program = assemble(source, "test.vasm".to_string()).unwrap();
+        let layout = &program.structs[0];
+        let offsets: Vec<usize> = layout.fields.iter().map(|f| f.offset).collect();
+        assert_eq!(offsets, vec![0, 4, 8, 16]);
+        assert_eq!(layout.size, 24);
+        assert_eq!(layout.align, 8);
+
+        let disasm = crate::asm::disassemble(&program);
+        assert!(disasm.contains("set value_offset, 16    ; Rec.value"));
+
+        let mut vm = crate::vm::VM::new(program);
+        vm.run().unwrap();
+        assert_eq!(vm.get_globals().get("value_offset"), Some(&Value::I32(16)));
+        assert_eq!(vm.get_globals().get("rec_size"), Some(&Value::I32(24)));
+
+        // A field named `size` would shadow the struct size constant
+        let bad = source.replace("value: i64", "size: i64");
+        let err = assemble(&bad, "test.vasm".to_string()).unwrap_err().to_string();
+        assert!(err.contains("Field 'Rec.size' collides"), "{}", err);
+    }
 }
+
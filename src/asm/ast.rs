@@ -18,6 +18,7 @@ pub enum Token {
     Local,
     Include,
     Define,
+    Struct,
 
     // Data types
     Type(DataType),
@@ -28,6 +29,7 @@ pub enum Token {
     Integer(i64),
     Float(f64),
     String(String),
+    Char(char),
 
     // Symbols
     Colon,
@@ -37,6 +39,10 @@ pub enum Token {
     RightBracket,  // ]
     LeftBrace,     // {
     RightBrace,    // }
+    LeftParen,     // (
+    RightParen,    // )
+    Plus,          // +
+    Star,          // *
 
     // Comments and whitespace
     Comment(String),
@@ -54,6 +60,7 @@ pub enum Section {
 pub struct DataDeclaration {
     pub name: String,
     pub dtype: DataType,
+    pub array_len: Option<usize>,
     pub value: Option<Immediate>,
 }
 
@@ -62,6 +69,8 @@ pub enum Immediate {
     Integer(i64),
     Float(f64),
     String(String),
+    Char(char),
+    Array(Vec<Immediate>),
 }
 
 #[derive(Debug, Clone)]
@@ -69,6 +78,28 @@ pub enum Operand {
     Variable(String),
     Immediate(Immediate),
     Label(String),
+    SizeOf(String),
+    Memory {
+        base: String,
+        terms: Vec<AddressTerm>,
+    },
+    /// Bracketed operand list, e.g. the jump table of `switch`
+    List(Vec<Operand>),
+    /// `(name: type, ...)` parameter list of `func_begin`
+    Parameters(Vec<(String, DataType)>),
+}
+
+/// One `*`-separated product inside a bracketed address, e.g. `i*4`
+/// or `Point.size*2`. Names are index variables or `.define` constants.
+#[derive(Debug, Clone)]
+pub struct AddressTerm {
+    pub factors: Vec<AddressFactor>,
+}
+
+#[derive(Debug, Clone)]
+pub enum AddressFactor {
+    Name(String),
+    Integer(i64),
 }
 
 #[derive(Debug, Clone)]
@@ -104,9 +135,23 @@ pub struct Define {
     pub value: DefineValue,
 }
 
+#[derive(Debug, Clone)]
+pub struct StructFieldDecl {
+    pub name: String,
+    pub dtype: DataType,
+    pub array_len: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructDecl {
+    pub name: String,
+    pub fields: Vec<StructFieldDecl>,
+}
+
 #[derive(Debug, Clone)]
 pub struct AsmProgram {
     pub defines: Vec<Define>,
+    pub structs: Vec<StructDecl>,
     pub data_section: Vec<DataDeclaration>,
     pub text_section: Vec<Statement>,
     pub includes: Vec<String>,
@@ -116,6 +161,7 @@ impl AsmProgram {
     pub fn new() -> Self {
         Self {
             defines: Vec::new(),
+            structs: Vec::new(),
             data_section: Vec::new(),
             text_section: Vec::new(),
             includes: Vec::new(),
@@ -124,6 +170,7 @@ impl AsmProgram {
 
     pub fn merge(&mut self, other: AsmProgram) {
         self.defines.extend(other.defines);
+        self.structs.extend(other.structs);
         self.data_section.extend(other.data_section);
         self.text_section.extend(other.text_section);
         // Don't merge includes to avoid re-including
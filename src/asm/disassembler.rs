@@ -1,11 +1,12 @@
 use crate::opcode::OpCode;
-use crate::program::Program;
-use crate::types::{DataType, Operand, Value};
+use crate::program::{DataBlock, Program, Variable};
+use crate::types::{Address, DataType, Endian, Operand, Value};
 
 pub fn disassemble(program: &Program) -> String {
     let mut output = String::new();
     let mut disasm = Disassembler::new(program);
 
+    output.push_str(&disasm.disassemble_structs());
     output.push_str(&disasm.disassemble_data_section());
     output.push_str("\n");
     output.push_str(&disasm.disassemble_text_section());
@@ -26,6 +27,28 @@ impl<'a> Disassembler<'a> {
         }
     }
 
+    fn disassemble_structs(&self) -> String {
+        let mut output = String::new();
+
+        for layout in &self.program.structs {
+            output.push_str(&format!(
+                "struct {} {{    ; size {}, align {}\n",
+                layout.name, layout.size, layout.align
+            ));
+            for field in &layout.fields {
+                let decl = if field.count > 1 {
+                    format!("{}: {}[{}]", field.name, self.format_datatype(field.dtype), field.count)
+                } else {
+                    format!("{}: {}", field.name, self.format_datatype(field.dtype))
+                };
+                output.push_str(&format!("    {:<20} ; offset {}\n", decl, field.offset));
+            }
+            output.push_str("}\n\n");
+        }
+
+        output
+    }
+
     fn disassemble_data_section(&self) -> String {
         if self.program.globals.is_empty() {
             return String::new();
@@ -34,6 +57,16 @@ impl<'a> Disassembler<'a> {
         let mut output = String::from("section .data\n");
 
         for global in &self.program.globals {
+            if let Some(block) = self
+                .program
+                .data_blocks
+                .iter()
+                .find(|b| b.global_name == global.name)
+            {
+                output.push_str(&format!("    {}\n", self.format_data_block(block)));
+                continue;
+            }
+
             output.push_str(&format!(
                 "    {}: {}\n",
                 global.name,
@@ -44,6 +77,40 @@ impl<'a> Disassembler<'a> {
         output
     }
 
+    fn format_data_block(&self, block: &DataBlock) -> String {
+        let elem_size = Variable::size_of(block.dtype);
+        let values: Vec<Value> = block
+            .bytes
+            .chunks(elem_size)
+            .filter_map(|chunk| Value::from_le_bytes(chunk, block.dtype).ok())
+            .collect();
+
+        // Trailing zero elements are implicit, so only print up to the last non-zero one
+        let used = values
+            .iter()
+            .rposition(|v| !v.is_zero())
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        let decl = format!(
+            "{}: {}[{}]",
+            block.global_name,
+            self.format_datatype(block.dtype),
+            block.count
+        );
+
+        if used == 0 {
+            decl
+        } else {
+            let init = values[..used]
+                .iter()
+                .map(|v| self.format_value(v))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{} = {{{}}}", decl, init)
+        }
+    }
+
     fn disassemble_text_section(&mut self) -> String {
         let mut output = String::from("section .text\n");
 
@@ -51,6 +118,9 @@ impl<'a> Disassembler<'a> {
             let line = self.disassemble_instruction(instr, idx);
             if !line.is_empty() {
                 output.push_str(&line);
+                if let Some(refs) = self.field_refs(idx) {
+                    output.push_str(&format!("    ; {}", refs.join(", ")));
+                }
                 output.push('\n');
             }
         }
@@ -58,6 +128,13 @@ impl<'a> Disassembler<'a> {
         output
     }
 
+    fn field_refs(&self, idx: usize) -> Option<&Vec<String>> {
+        self.program
+            .source_map
+            .as_ref()
+            .and_then(|map| map.field_refs.get(&idx))
+    }
+
     fn disassemble_instruction(&mut self, instr: &OpCode, _idx: usize) -> String {
         match instr {
             OpCode::Label { name } => {
@@ -69,7 +146,18 @@ impl<'a> Disassembler<'a> {
             },
             OpCode::FuncBegin { name, return_type } => {
                 self.current_function = Some(name.clone());
-                format!("    func_begin {}", self.format_datatype(*return_type))
+                let signature = match self.program.functions.get(name) {
+                    Some(func) if func.typed => {
+                        let params: Vec<String> = func
+                            .parameters
+                            .iter()
+                            .map(|param| format!("{}: {}", param.name, self.format_datatype(param.dtype)))
+                            .collect();
+                        format!(" ({})", params.join(", "))
+                    }
+                    _ => String::new(),
+                };
+                format!("    func_begin {}{}", self.format_datatype(*return_type), signature)
             },
             OpCode::FuncEnd => {
                 self.current_function = None;
@@ -126,6 +214,30 @@ impl<'a> Disassembler<'a> {
             OpCode::Shr { dest, left, right } => {
                 format!("    shr {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
             },
+            OpCode::Popcnt { dest, source } => {
+                format!("    popcnt {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Clz { dest, source } => {
+                format!("    clz {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Ctz { dest, source } => {
+                format!("    ctz {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Bswap { dest, source } => {
+                format!("    bswap {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Rotl { dest, left, right } => {
+                format!("    rotl {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
+            },
+            OpCode::Rotr { dest, left, right } => {
+                format!("    rotr {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
+            },
+            OpCode::BitExtract { dest, source, start, len } => {
+                format!("    bfext {}, {}, {}, {}", dest, self.format_operand(source), self.format_operand(start), self.format_operand(len))
+            },
+            OpCode::BitInsert { dest, source, field, start, len } => {
+                format!("    bfins {}, {}, {}, {}, {}", dest, self.format_operand(source), self.format_operand(field), self.format_operand(start), self.format_operand(len))
+            },
             OpCode::Eq { dest, left, right } => {
                 format!("    eq {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
             },
@@ -144,26 +256,52 @@ impl<'a> Disassembler<'a> {
             OpCode::Ge { dest, left, right } => {
                 format!("    ge {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
             },
+            OpCode::Select { dest, cond, a, b } => {
+                format!("    select {}, {}, {}, {}", dest, self.format_operand(cond), self.format_operand(a), self.format_operand(b))
+            },
             OpCode::Jmp { label } => {
                 format!("    jmp {}", label)
             },
+            OpCode::Switch { value, default, targets } => {
+                format!("    switch {}, {}, [{}]", self.format_operand(value), default, targets.join(", "))
+            },
+            OpCode::Assert { cond, message } => match message {
+                Some(message) => format!("    assert {}, {:?}", self.format_operand(cond), message),
+                None => format!("    assert {}", self.format_operand(cond)),
+            },
+            OpCode::Trap { code } => {
+                format!("    trap {}", self.format_operand(code))
+            },
+            OpCode::Unreachable => "    unreachable".to_string(),
             OpCode::Jz { var, label } => {
                 format!("    jz {}, {}", var, label)
             },
             OpCode::Jnz { var, label } => {
                 format!("    jnz {}, {}", var, label)
             },
+            OpCode::Jeq { left, right, label } => {
+                format!("    jeq {}, {}, {}", self.format_operand(left), self.format_operand(right), label)
+            },
+            OpCode::Jne { left, right, label } => {
+                format!("    jne {}, {}, {}", self.format_operand(left), self.format_operand(right), label)
+            },
+            OpCode::Jlt { left, right, label } => {
+                format!("    jlt {}, {}, {}", self.format_operand(left), self.format_operand(right), label)
+            },
+            OpCode::Jle { left, right, label } => {
+                format!("    jle {}, {}, {}", self.format_operand(left), self.format_operand(right), label)
+            },
+            OpCode::Jgt { left, right, label } => {
+                format!("    jgt {}, {}, {}", self.format_operand(left), self.format_operand(right), label)
+            },
+            OpCode::Jge { left, right, label } => {
+                format!("    jge {}, {}, {}", self.format_operand(left), self.format_operand(right), label)
+            },
             OpCode::Call { result, func, args } => {
-                let result_str = result.as_ref().map(|s| s.as_str()).unwrap_or("_");
-                let args_str = args.iter()
-                    .map(|arg| self.format_operand(arg))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                if args.is_empty() {
-                    format!("    call {}, {}", result_str, func)
-                } else {
-                    format!("    call {}, {}, {}", result_str, func, args_str)
-                }
+                let mut operands: Vec<String> = result.iter().cloned().collect();
+                operands.push(func.clone());
+                operands.extend(args.iter().map(|arg| self.format_operand(arg)));
+                format!("    call {}", operands.join(", "))
             },
             OpCode::Return { value } => {
                 if let Some(v) = value {
@@ -184,15 +322,39 @@ impl<'a> Disassembler<'a> {
             OpCode::Free { ptr } => {
                 format!("    free {}", ptr)
             },
-            OpCode::Load { dest, ptr, dtype } => {
-                format!("    load {}, {}, {}", dest, ptr, self.format_datatype(*dtype))
+            OpCode::Load { dest, addr, dtype, endian } => {
+                format!("    load{} {}, {}, {}", self.format_endian(*endian), dest, self.format_address(addr), self.format_datatype(*dtype))
+            },
+            OpCode::Store { addr, source, dtype, endian } => {
+                format!("    store{} {}, {}, {}", self.format_endian(*endian), self.format_address(addr), self.format_operand(source), self.format_datatype(*dtype))
             },
-            OpCode::Store { ptr, source, dtype } => {
-                format!("    store {}, {}, {}", ptr, source, self.format_datatype(*dtype))
+            OpCode::LoadVec { buf, addr, count, dtype, endian } => {
+                format!("    loadv{} {}, {}, {}, {}", self.format_endian(*endian), buf, self.format_address(addr), self.format_operand(count), self.format_datatype(*dtype))
+            },
+            OpCode::StoreVec { addr, buf, count, dtype, endian } => {
+                format!("    storev{} {}, {}, {}, {}", self.format_endian(*endian), self.format_address(addr), buf, self.format_operand(count), self.format_datatype(*dtype))
             },
             OpCode::GetAddr { dest, var } => {
                 format!("    get_addr {}, {}", dest, var)
             },
+            OpCode::MemCopy { dest, src, len } => {
+                format!("    memcpy {}, {}, {}", dest, src, self.format_operand(len))
+            },
+            OpCode::MemMove { dest, src, len } => {
+                format!("    memmove {}, {}, {}", dest, src, self.format_operand(len))
+            },
+            OpCode::MemSet { dest, value, len } => {
+                format!("    memset {}, {}, {}", dest, self.format_operand(value), self.format_operand(len))
+            },
+            OpCode::MemCmp { result, a, b, len } => {
+                format!("    memcmp {}, {}, {}, {}", result, a, b, self.format_operand(len))
+            },
+            OpCode::SizeOf { dest, target } => {
+                format!("    sizeof {}, {}", dest, self.format_operand(target))
+            },
+            OpCode::AllocArray { dest, elem, count } => {
+                format!("    alloc_array {}, {}, {}", dest, self.format_operand(elem), self.format_operand(count))
+            },
             OpCode::Cast { dest, source, target_type } => {
                 format!("    cast {}, {}, {}", dest, source, self.format_datatype(*target_type))
             },
@@ -220,11 +382,259 @@ impl<'a> Disassembler<'a> {
             OpCode::Tan { dest, source } => {
                 format!("    tan {}, {}", dest, self.format_operand(source))
             },
+            OpCode::Floor { dest, source } => {
+                format!("    floor {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Ceil { dest, source } => {
+                format!("    ceil {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Round { dest, source } => {
+                format!("    round {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Trunc { dest, source } => {
+                format!("    trunc {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Exp { dest, source } => {
+                format!("    exp {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Ln { dest, source } => {
+                format!("    ln {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Log2 { dest, source } => {
+                format!("    log2 {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Log10 { dest, source } => {
+                format!("    log10 {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Asin { dest, source } => {
+                format!("    asin {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Acos { dest, source } => {
+                format!("    acos {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Atan { dest, source } => {
+                format!("    atan {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::Atan2 { dest, y, x } => {
+                format!("    atan2 {}, {}, {}", dest, self.format_operand(y), self.format_operand(x))
+            },
+            OpCode::Hypot { dest, x, y } => {
+                format!("    hypot {}, {}, {}", dest, self.format_operand(x), self.format_operand(y))
+            },
+            OpCode::CopySign { dest, magnitude, sign } => {
+                format!("    copysign {}, {}, {}", dest, self.format_operand(magnitude), self.format_operand(sign))
+            },
+            OpCode::Fma { dest, a, b, c } => {
+                format!("    fma {}, {}, {}, {}", dest, self.format_operand(a), self.format_operand(b), self.format_operand(c))
+            },
+            OpCode::IsNan { dest, source } => {
+                format!("    isnan {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::IsInf { dest, source } => {
+                format!("    isinf {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::IsFinite { dest, source } => {
+                format!("    isfinite {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::VecSplat { dest, value, dtype } => {
+                format!("    vsplat {}, {}, {}", dest, self.format_operand(value), self.format_datatype(*dtype))
+            },
+            OpCode::VecExtract { dest, vector, lane } => {
+                format!("    vextract {}, {}, {}", dest, self.format_operand(vector), self.format_operand(lane))
+            },
+            OpCode::VecInsert { dest, vector, lane, value } => {
+                format!(
+                    "    vinsert {}, {}, {}, {}",
+                    dest,
+                    self.format_operand(vector),
+                    self.format_operand(lane),
+                    self.format_operand(value)
+                )
+            },
+            OpCode::VecShuffle { dest, a, b, lanes } => {
+                let lanes: Vec<String> = lanes.iter().map(|lane| lane.to_string()).collect();
+                format!("    vshuffle {}, {}, {}, [{}]", dest, self.format_operand(a), self.format_operand(b), lanes.join(", "))
+            },
+            OpCode::VecEq { dest, left, right } => {
+                format!("    veq {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
+            },
+            OpCode::VecNe { dest, left, right } => {
+                format!("    vne {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
+            },
+            OpCode::VecLt { dest, left, right } => {
+                format!("    vlt {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
+            },
+            OpCode::VecLe { dest, left, right } => {
+                format!("    vle {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
+            },
+            OpCode::VecGt { dest, left, right } => {
+                format!("    vgt {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
+            },
+            OpCode::VecGe { dest, left, right } => {
+                format!("    vge {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
+            },
+            OpCode::VecSelect { dest, mask, a, b } => {
+                format!("    vselect {}, {}, {}, {}", dest, self.format_operand(mask), self.format_operand(a), self.format_operand(b))
+            },
+            OpCode::StrLen { dest, source } => {
+                format!("    str_len {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::StrConcat { dest, left, right } => {
+                format!("    str_concat {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
+            },
+            OpCode::StrSub { dest, source, start, len } => {
+                format!("    str_sub {}, {}, {}, {}", dest, self.format_operand(source), self.format_operand(start), self.format_operand(len))
+            },
+            OpCode::StrFind { dest, haystack, needle } => {
+                format!("    str_find {}, {}, {}", dest, self.format_operand(haystack), self.format_operand(needle))
+            },
+            OpCode::StrCmp { dest, left, right } => {
+                format!("    str_cmp {}, {}, {}", dest, self.format_operand(left), self.format_operand(right))
+            },
+            OpCode::StrToInt { dest, source } => {
+                format!("    str_to_int {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::IntToStr { dest, source } => {
+                format!("    int_to_str {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::FloatToStr { dest, source } => {
+                format!("    float_to_str {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::PtrToStr { dest, ptr } => {
+                format!("    ptr_to_str {}, {}", dest, self.format_operand(ptr))
+            },
+            OpCode::StrToPtr { dest, source } => {
+                format!("    str_to_ptr {}, {}", dest, self.format_operand(source))
+            },
+            OpCode::ArrayNew { dest } => {
+                format!("    array_new {}", dest)
+            },
+            OpCode::ArrayPush { array, value } => {
+                format!("    array_push {}, {}", self.format_operand(array), self.format_operand(value))
+            },
+            OpCode::ArrayPop { dest, array } => {
+                format!("    array_pop {}, {}", dest, self.format_operand(array))
+            },
+            OpCode::MapNew { dest } => {
+                format!("    map_new {}", dest)
+            },
+            OpCode::MapHas { dest, map, key } => {
+                format!("    map_has {}, {}, {}", dest, self.format_operand(map), self.format_operand(key))
+            },
+            OpCode::MapKey { dest, map, index } => {
+                format!("    map_key {}, {}, {}", dest, self.format_operand(map), self.format_operand(index))
+            },
+            OpCode::ObjLen { dest, object } => {
+                format!("    obj_len {}, {}", dest, self.format_operand(object))
+            },
+            OpCode::ObjGet { dest, object, key } => {
+                format!("    obj_get {}, {}, {}", dest, self.format_operand(object), self.format_operand(key))
+            },
+            OpCode::ObjSet { object, key, value } => {
+                format!("    obj_set {}, {}, {}", self.format_operand(object), self.format_operand(key), self.format_operand(value))
+            },
             OpCode::Print { var } => {
                 format!("    print {}", var)
             },
-            OpCode::Input { dest } => {
-                format!("    input {}", dest)
+            OpCode::Input { dest, dtype, status, prompt } => {
+                let mnemonic = if *prompt { "input" } else { "read" };
+                let mut line = format!("    {} {}, {}", mnemonic, dest, self.format_datatype(*dtype));
+                if let Some(status) = status {
+                    line.push_str(&format!(", {}", status));
+                }
+                line
+            },
+            OpCode::PrintStr { ptr } => {
+                format!("    print_str {}", ptr)
+            },
+            OpCode::PrintChar { value } => {
+                format!("    print_char {}", self.format_operand(value))
+            },
+            OpCode::EPrintStr { ptr } => {
+                format!("    eprint_str {}", ptr)
+            },
+            OpCode::EPrintChar { value } => {
+                format!("    eprint_char {}", self.format_operand(value))
+            },
+            OpCode::ReadLine { dest, buf, cap } => {
+                format!("    read_line {}, {}, {}", dest, buf, self.format_operand(cap))
+            },
+            OpCode::Printf { fmt, args } => {
+                let mut line = format!("    printf {}", fmt);
+                for arg in args {
+                    line.push_str(&format!(", {}", self.format_operand(arg)));
+                }
+                line
+            },
+            OpCode::FileOpen { dest, path, mode } => {
+                format!("    fopen {}, {}, {}", dest, path, self.format_operand(mode))
+            },
+            OpCode::FileRead { dest, handle, buf, len } => {
+                format!("    fread {}, {}, {}, {}", dest, self.format_operand(handle), buf, self.format_operand(len))
+            },
+            OpCode::FileWrite { dest, handle, buf, len } => {
+                format!("    fwrite {}, {}, {}, {}", dest, self.format_operand(handle), buf, self.format_operand(len))
+            },
+            OpCode::FileSeek { dest, handle, offset, whence } => {
+                format!(
+                    "    fseek {}, {}, {}, {}",
+                    dest,
+                    self.format_operand(handle),
+                    self.format_operand(offset),
+                    self.format_operand(whence)
+                )
+            },
+            OpCode::FileClose { dest, handle } => {
+                format!("    fclose {}, {}", dest, self.format_operand(handle))
+            },
+            OpCode::Clock { dest } => {
+                format!("    clock {}", dest)
+            },
+            OpCode::Sleep { millis } => {
+                format!("    sleep {}", self.format_operand(millis))
+            },
+            OpCode::Srand { seed } => {
+                format!("    srand {}", self.format_operand(seed))
+            },
+            OpCode::Rand { dest, bound } => {
+                match bound {
+                    Some(b) => format!("    rand {}, {}", dest, self.format_operand(b)),
+                    None => format!("    rand {}", dest),
+                }
+            },
+            OpCode::GetEnv { dest, name } => {
+                format!("    getenv {}, {}", dest, name)
+            },
+            OpCode::TcpConnect { dest, addr } => {
+                format!("    tcp_connect {}, {}", dest, addr)
+            },
+            OpCode::TcpListen { dest, addr } => {
+                format!("    tcp_listen {}, {}", dest, addr)
+            },
+            OpCode::TcpAccept { dest, handle } => {
+                format!("    tcp_accept {}, {}", dest, self.format_operand(handle))
+            },
+            OpCode::TcpSend { dest, handle, buf, len } => {
+                format!("    tcp_send {}, {}, {}, {}", dest, self.format_operand(handle), buf, self.format_operand(len))
+            },
+            OpCode::TcpRecv { dest, handle, buf, len } => {
+                format!("    tcp_recv {}, {}, {}, {}", dest, self.format_operand(handle), buf, self.format_operand(len))
+            },
+            OpCode::TcpClose { dest, handle } => {
+                format!("    tcp_close {}, {}", dest, self.format_operand(handle))
+            },
+            OpCode::TcpNonblock { dest, handle, enabled } => {
+                format!("    tcp_nonblock {}, {}, {}", dest, self.format_operand(handle), self.format_operand(enabled))
+            },
+            OpCode::ReadKey { dest, wait } => {
+                format!("    {} {}", if *wait { "getkey" } else { "pollkey" }, dest)
+            },
+            OpCode::SetCursor { row, col } => {
+                format!("    cursor {}, {}", self.format_operand(row), self.format_operand(col))
+            },
+            OpCode::ClearScreen => "    clear".to_string(),
+            OpCode::SetColor { fg, bg } => {
+                format!("    color {}, {}", self.format_operand(fg), self.format_operand(bg))
             },
             OpCode::Exit { code } => {
                 format!("    exit {}", self.format_operand(code))
@@ -232,6 +642,26 @@ impl<'a> Disassembler<'a> {
         }
     }
 
+    fn format_address(&self, addr: &Address) -> String {
+        if addr.is_plain() {
+            return addr.base.clone();
+        }
+        let mut expr = String::new();
+        if let Some(index) = &addr.index {
+            expr.push_str(index);
+            if addr.scale != 1 {
+                expr.push_str(&format!("*{}", addr.scale));
+            }
+        }
+        if addr.offset != 0 || expr.is_empty() {
+            if !expr.is_empty() && addr.offset >= 0 {
+                expr.push('+');
+            }
+            expr.push_str(&addr.offset.to_string());
+        }
+        format!("{}[{}]", addr.base, expr)
+    }
+
     fn format_operand(&self, operand: &Operand) -> String {
         match operand {
             Operand::Variable(name) => name.clone(),
@@ -247,10 +677,12 @@ impl<'a> Disassembler<'a> {
             Value::I16(v) => v.to_string(),
             Value::I32(v) => v.to_string(),
             Value::I64(v) => v.to_string(),
+            Value::I128(v) => v.to_string(),
             Value::U8(v) => v.to_string(),
             Value::U16(v) => v.to_string(),
             Value::U32(v) => v.to_string(),
             Value::U64(v) => v.to_string(),
+            Value::U128(v) => v.to_string(),
             Value::F32(v) => {
                 if v.fract() == 0.0 {
                     format!("{}.0", v)
@@ -266,6 +698,24 @@ impl<'a> Disassembler<'a> {
                 }
             },
             Value::Ptr(v) => format!("0x{:x}", v),
+            Value::Bool(v) => (*v as u8).to_string(),
+            Value::Char(v) => format!("{:?}", v),
+            Value::Str(v) => format!("{:?}", v),
+            Value::Ref(_) => "null".to_string(),
+            Value::Vector(v) => {
+                let lanes: Vec<String> = (0..v.shape().lane_count())
+                    .map(|i| self.format_value(&v.lane(i).unwrap()))
+                    .collect();
+                format!("[{}]", lanes.join(", "))
+            }
+        }
+    }
+
+    // Mnemonic suffix; little-endian is the default and is left implicit
+    fn format_endian(&self, endian: Endian) -> &'static str {
+        match endian {
+            Endian::Little => "",
+            Endian::Big => ".be",
         }
     }
 
@@ -275,14 +725,21 @@ impl<'a> Disassembler<'a> {
             DataType::I16 => "i16".to_string(),
             DataType::I32 => "i32".to_string(),
             DataType::I64 => "i64".to_string(),
+            DataType::I128 => "i128".to_string(),
             DataType::U8 => "u8".to_string(),
             DataType::U16 => "u16".to_string(),
             DataType::U32 => "u32".to_string(),
             DataType::U64 => "u64".to_string(),
+            DataType::U128 => "u128".to_string(),
             DataType::F32 => "f32".to_string(),
             DataType::F64 => "f64".to_string(),
             DataType::Ptr => "ptr".to_string(),
             DataType::Void => "void".to_string(),
+            DataType::Bool => "bool".to_string(),
+            DataType::Char => "char".to_string(),
+            DataType::Str => "string".to_string(),
+            DataType::Ref => "ref".to_string(),
+            DataType::Vector(shape) => shape.name().to_string(),
         }
     }
 }
@@ -1,5 +1,6 @@
 use crate::asm::ast::Token;
 use crate::asm::error::{AsmError, SourceLocation};
+use crate::simd::VectorType;
 use crate::types::DataType;
 
 pub struct Lexer {
@@ -8,6 +9,8 @@ pub struct Lexer {
     line: usize,
     column: usize,
     file: String,
+    // (line, column) where each token returned by `tokenize` starts
+    positions: Vec<(usize, usize)>,
 }
 
 impl Lexer {
@@ -18,6 +21,7 @@ impl Lexer {
             line: 1,
             column: 1,
             file,
+            positions: Vec::new(),
         }
     }
 
@@ -31,6 +35,7 @@ impl Lexer {
                 break;
             }
 
+            let start = (self.line, self.column);
             let token = self.next_token()?;
 
             match token {
@@ -39,17 +44,27 @@ impl Lexer {
                     if let Some(last) = tokens.last() {
                         if !matches!(last, Token::Newline) {
                             tokens.push(token);
+                            self.positions.push(start);
                         }
                     }
                 },
-                _ => tokens.push(token),
+                _ => {
+                    tokens.push(token);
+                    self.positions.push(start);
+                },
             }
         }
 
         tokens.push(Token::Eof);
+        self.positions.push((self.line, self.column));
         Ok(tokens)
     }
 
+    /// Source (line, column) of each token from the last `tokenize`.
+    pub fn positions(&self) -> &[(usize, usize)] {
+        &self.positions
+    }
+
     fn next_token(&mut self) -> Result<Token, AsmError> {
         if self.is_at_end() {
             return Ok(Token::Eof);
@@ -93,7 +108,24 @@ impl Lexer {
                 self.advance();
                 Ok(Token::RightBrace)
             },
+            '(' => {
+                self.advance();
+                Ok(Token::LeftParen)
+            },
+            ')' => {
+                self.advance();
+                Ok(Token::RightParen)
+            },
+            '+' => {
+                self.advance();
+                Ok(Token::Plus)
+            },
+            '*' => {
+                self.advance();
+                Ok(Token::Star)
+            },
             '"' => self.read_string(),
+            '\'' => self.read_char(),
             '.' => self.read_local_label(),
             '-' | '0'..='9' => self.read_number(),
             'a'..='z' | 'A'..='Z' | '_' => self.read_identifier_or_keyword(),
@@ -162,6 +194,65 @@ impl Lexer {
         Ok(Token::String(string_val))
     }
 
+    // 'a', '\n', '\'' or '\u{1F600}'
+    fn read_char(&mut self) -> Result<Token, AsmError> {
+        let location = self.location();
+        let invalid = |message: &str| AsmError::LexError {
+            message: message.to_string(),
+            location: Some(location.clone()),
+        };
+        self.advance(); // Skip opening quote
+
+        if self.is_at_end() || matches!(self.current_char(), '\'' | '\n') {
+            return Err(invalid("Empty character literal"));
+        }
+        let ch = if self.current_char() == '\\' {
+            self.advance();
+            if self.is_at_end() {
+                return Err(invalid("Unterminated character literal"));
+            }
+            let escaped = self.current_char();
+            self.advance();
+            match escaped {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '0' => '\0',
+                '\\' | '\'' | '"' => escaped,
+                'u' => self.read_unicode_escape().ok_or_else(|| invalid("Invalid unicode escape in character literal"))?,
+                _ => return Err(invalid(&format!("Unknown escape in character literal: \\{}", escaped))),
+            }
+        } else {
+            let ch = self.current_char();
+            self.advance();
+            ch
+        };
+
+        if self.is_at_end() || self.current_char() != '\'' {
+            return Err(invalid("Unterminated character literal"));
+        }
+        self.advance(); // Skip closing quote
+        Ok(Token::Char(ch))
+    }
+
+    // The `{XXXX}` part of a `\u{XXXX}` escape
+    fn read_unicode_escape(&mut self) -> Option<char> {
+        if self.is_at_end() || self.current_char() != '{' {
+            return None;
+        }
+        self.advance();
+        let mut digits = String::new();
+        while !self.is_at_end() && self.current_char().is_ascii_hexdigit() {
+            digits.push(self.current_char());
+            self.advance();
+        }
+        if self.is_at_end() || self.current_char() != '}' {
+            return None;
+        }
+        self.advance();
+        u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32)
+    }
+
     fn read_local_label(&mut self) -> Result<Token, AsmError> {
         let start = self.position;
         self.advance();
@@ -265,6 +356,9 @@ impl Lexer {
             let ch = self.current_char();
             if ch.is_alphanumeric() || ch == '_' {
                 self.advance();
+            } else if ch == '.' && self.next_starts_identifier() {
+                // Dotted names such as `table.len` refer to assembler constants
+                self.advance();
             } else {
                 break;
             }
@@ -281,20 +375,33 @@ impl Lexer {
             "local" => Token::Local,
             "include" => Token::Include,
             "define" => Token::Define,
+            "struct" => Token::Struct,
 
             "str" => Token::Identifier("str".to_string()), // str is special, not a DataType
             "i8" => Token::Type(DataType::I8),
             "i16" => Token::Type(DataType::I16),
             "i32" => Token::Type(DataType::I32),
             "i64" => Token::Type(DataType::I64),
+            "i128" => Token::Type(DataType::I128),
             "u8" => Token::Type(DataType::U8),
             "u16" => Token::Type(DataType::U16),
             "u32" => Token::Type(DataType::U32),
             "u64" => Token::Type(DataType::U64),
+            "u128" => Token::Type(DataType::U128),
             "f32" => Token::Type(DataType::F32),
             "f64" => Token::Type(DataType::F64),
             "ptr" => Token::Type(DataType::Ptr),
             "void" => Token::Type(DataType::Void),
+            "bool" => Token::Type(DataType::Bool),
+            "char" => Token::Type(DataType::Char),
+            "string" => Token::Type(DataType::Str),
+            "ref" => Token::Type(DataType::Ref),
+            "v16u8" => Token::Type(DataType::Vector(VectorType::V16U8)),
+            "v8i16" => Token::Type(DataType::Vector(VectorType::V8I16)),
+            "v4i32" => Token::Type(DataType::Vector(VectorType::V4I32)),
+            "v2i64" => Token::Type(DataType::Vector(VectorType::V2I64)),
+            "v4f32" => Token::Type(DataType::Vector(VectorType::V4F32)),
+            "v2f64" => Token::Type(DataType::Vector(VectorType::V2F64)),
 
             _ => Token::Identifier(text),
         };
@@ -313,6 +420,13 @@ impl Lexer {
         }
     }
 
+    fn next_starts_identifier(&self) -> bool {
+        match self.input.get(self.position + 1) {
+            Some(ch) => ch.is_alphabetic() || *ch == '_',
+            None => false,
+        }
+    }
+
     fn current_char(&self) -> char {
         self.input[self.position]
     }
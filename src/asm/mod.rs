@@ -5,6 +5,6 @@ pub mod assembler;
 pub mod disassembler;
 pub mod error;
 
-pub use assembler::assemble;
+pub use assembler::{assemble, assemble_with_options, AssembleOptions};
 pub use disassembler::disassemble;
 pub use error::AsmError;
@@ -7,6 +7,7 @@ pub struct Parser {
     position: usize,
     current_line: usize,
     current_column: usize,
+    positions: Vec<(usize, usize)>,
 }
 
 impl Parser {
@@ -16,9 +17,15 @@ impl Parser {
             position: 0,
             current_line: 1,
             current_column: 1,
+            positions: Vec::new(),
         }
     }
 
+    /// Parser that reports exact instruction locations, from `Lexer::positions`.
+    pub fn with_positions(tokens: Vec<Token>, positions: Vec<(usize, usize)>) -> Self {
+        Self { positions, ..Self::new(tokens) }
+    }
+
     pub fn parse(&mut self) -> Result<AsmProgram, AsmError> {
         let mut program = AsmProgram::new();
 
@@ -31,9 +38,11 @@ impl Parser {
                 self.parse_include(&mut program)?;
             } else if self.check_keyword("section") {
                 self.parse_section(&mut program)?;
+            } else if self.check_keyword("struct") {
+                self.parse_struct(&mut program)?;
             } else {
                 return Err(AsmError::ParseError {
-                    message: format!("Expected 'define', 'include', or 'section', got {:?}", self.current()),
+                    message: format!("Expected 'define', 'include', 'struct', or 'section', got {:?}", self.current()),
                     location: None,
                 });
             }
@@ -105,6 +114,70 @@ impl Parser {
         Ok(())
     }
 
+    fn parse_struct(&mut self, program: &mut AsmProgram) -> Result<(), AsmError> {
+        self.expect_keyword("struct")?;
+
+        let name = self.expect_identifier()?;
+        self.expect(Token::LeftBrace)?;
+        self.skip_newlines();
+
+        // Fields are separated by commas and/or newlines
+        let mut fields = Vec::new();
+        while !self.check(Token::RightBrace) {
+            let field_name = self.expect_identifier()?;
+            self.expect(Token::Colon)?;
+            let dtype = self.expect_type()?;
+
+            let array_len = if self.check(Token::LeftBracket) {
+                self.advance();
+                let len = match self.current().clone() {
+                    Token::Integer(n) if n > 0 => n as usize,
+                    _ => {
+                        return Err(AsmError::ParseError {
+                            message: format!("Expected positive array length, got {:?}", self.current()),
+                            location: None,
+                        });
+                    }
+                };
+                self.advance();
+                self.expect(Token::RightBracket)?;
+                Some(len)
+            } else {
+                None
+            };
+
+            fields.push(StructFieldDecl {
+                name: field_name,
+                dtype,
+                array_len,
+            });
+
+            if self.check(Token::Comma) {
+                self.advance();
+            } else if !matches!(self.current(), Token::Newline | Token::RightBrace) {
+                return Err(AsmError::ParseError {
+                    message: format!("Expected ',' or '}}' in struct '{}', got {:?}", name, self.current()),
+                    location: None,
+                });
+            }
+            self.skip_newlines();
+        }
+
+        self.expect(Token::RightBrace)?;
+        self.expect_newline()?;
+
+        if fields.is_empty() {
+            return Err(AsmError::ParseError {
+                message: format!("Struct '{}' has no fields", name),
+                location: None,
+            });
+        }
+
+        program.structs.push(StructDecl { name, fields });
+
+        Ok(())
+    }
+
     fn parse_section(&mut self, program: &mut AsmProgram) -> Result<(), AsmError> {
         self.expect_keyword("section")?;
 
@@ -146,7 +219,7 @@ impl Parser {
         use crate::asm::ast::Immediate;
         use crate::types::DataType;
 
-        while !self.is_at_end() && !self.check_keyword("section") {
+        while !self.is_at_end() && !self.check_keyword("section") && !self.check_keyword("struct") {
             if matches!(self.current(), Token::Newline) {
                 self.advance();
                 continue;
@@ -180,22 +253,71 @@ impl Parser {
                 program.data_section.push(DataDeclaration {
                     name,
                     dtype: DataType::Ptr,  // Strings are stored as pointers
+                    array_len: None,
                     value: Some(Immediate::String(string_value)),
                 });
             } else {
                 // Regular type declaration
                 let dtype = self.expect_type()?;
 
+                // Array declaration: `name: type[len]` or `name: type[]` with an initializer
+                let mut array_len = None;
+                let mut len_omitted = false;
+                if self.check(Token::LeftBracket) {
+                    self.advance();
+                    match self.current().clone() {
+                        Token::Integer(n) if n > 0 => {
+                            self.advance();
+                            array_len = Some(n as usize);
+                        },
+                        Token::RightBracket => {
+                            len_omitted = true;
+                        },
+                        _ => {
+                            return Err(AsmError::ParseError {
+                                message: format!("Expected positive array length, got {:?}", self.current()),
+                                location: None,
+                            });
+                        }
+                    }
+                    self.expect(Token::RightBracket)?;
+                }
+
                 let value = if self.check(Token::Equals) {
                     self.advance();
-                    Some(self.parse_immediate()?)
+                    if self.check(Token::LeftBrace) {
+                        if array_len.is_none() && !len_omitted {
+                            return Err(AsmError::ParseError {
+                                message: format!("Initializer list requires an array type for '{}'", name),
+                                location: None,
+                            });
+                        }
+                        Some(self.parse_initializer_list()?)
+                    } else {
+                        Some(self.parse_immediate()?)
+                    }
                 } else {
                     None
                 };
 
+                if len_omitted {
+                    match &value {
+                        Some(Immediate::Array(items)) if !items.is_empty() => {
+                            array_len = Some(items.len());
+                        },
+                        _ => {
+                            return Err(AsmError::ParseError {
+                                message: format!("Array '{}' without a length needs a non-empty initializer list", name),
+                                location: None,
+                            });
+                        }
+                    }
+                }
+
                 program.data_section.push(DataDeclaration {
                     name,
                     dtype,
+                    array_len,
                     value,
                 });
             }
@@ -207,7 +329,7 @@ impl Parser {
     }
 
     fn parse_text_section(&mut self, program: &mut AsmProgram) -> Result<(), AsmError> {
-        while !self.is_at_end() && !self.check_keyword("section") {
+        while !self.is_at_end() && !self.check_keyword("section") && !self.check_keyword("struct") {
             if matches!(self.current(), Token::Newline) {
                 self.advance();
                 continue;
@@ -280,10 +402,12 @@ impl Parser {
     }
 
     fn parse_instruction(&mut self) -> Result<Instruction, AsmError> {
-        let location = SourceLoc {
-            line: self.current_line,
-            column: self.current_column,
-        };
+        let (line, column) = self
+            .positions
+            .get(self.position)
+            .copied()
+            .unwrap_or((self.current_line, self.current_column));
+        let location = SourceLoc { line, column };
 
         let opcode = match self.current().clone() {
             Token::Identifier(name) => {
@@ -321,11 +445,61 @@ impl Parser {
             }
         }
 
+        // `func_begin i32 (a: i32, b: ptr)` declares typed parameters
+        if opcode == "func_begin" && self.check(Token::LeftParen) {
+            operands.push(self.parse_parameter_list()?);
+        }
+
         Ok(Instruction { opcode, operands, location })
     }
 
+    fn parse_parameter_list(&mut self) -> Result<Operand, AsmError> {
+        self.expect(Token::LeftParen)?;
+        let mut params = Vec::new();
+
+        self.skip_newlines();
+        while !self.check(Token::RightParen) {
+            let name = self.expect_identifier()?;
+            self.expect(Token::Colon)?;
+            let dtype = self.expect_type()?;
+            params.push((name, dtype));
+            self.skip_newlines();
+            if self.check(Token::Comma) {
+                self.advance();
+                self.skip_newlines();
+            } else {
+                break;
+            }
+        }
+
+        self.expect(Token::RightParen)?;
+        Ok(Operand::Parameters(params))
+    }
+
     fn parse_operand(&mut self) -> Result<Operand, AsmError> {
         match self.current().clone() {
+            Token::Identifier(name) if name == "sizeof" && matches!(self.peek(1), Some(Token::LeftParen)) => {
+                self.advance();
+                self.expect(Token::LeftParen)?;
+                let target = match self.parse_operand()? {
+                    Operand::Variable(target) => target,
+                    other => {
+                        return Err(AsmError::ParseError {
+                            message: format!("Expected type, struct or array name in sizeof, got {:?}", other),
+                            location: None,
+                        });
+                    }
+                };
+                self.expect(Token::RightParen)?;
+                Ok(Operand::SizeOf(target))
+            },
+            Token::Identifier(name) if matches!(self.peek(1), Some(Token::LeftBracket)) => {
+                self.advance();
+                self.expect(Token::LeftBracket)?;
+                let terms = self.parse_address_terms()?;
+                self.expect(Token::RightBracket)?;
+                Ok(Operand::Memory { base: name, terms })
+            },
             Token::Identifier(name) => {
                 self.advance();
                 Ok(Operand::Variable(name))
@@ -334,10 +508,15 @@ impl Parser {
                 self.advance();
                 Ok(Operand::Label(name))
             },
-            Token::Integer(_) | Token::Float(_) => {
+            Token::LeftBracket => self.parse_operand_list(),
+            Token::Integer(_) | Token::Float(_) | Token::Char(_) => {
                 let imm = self.parse_immediate()?;
                 Ok(Operand::Immediate(imm))
             },
+            Token::String(s) => {
+                self.advance();
+                Ok(Operand::Immediate(Immediate::String(s)))
+            },
             Token::Type(dtype) => {
                 self.advance();
                 let type_name = match dtype {
@@ -345,14 +524,21 @@ impl Parser {
                     crate::types::DataType::I16 => "i16",
                     crate::types::DataType::I32 => "i32",
                     crate::types::DataType::I64 => "i64",
+                    crate::types::DataType::I128 => "i128",
                     crate::types::DataType::U8 => "u8",
                     crate::types::DataType::U16 => "u16",
                     crate::types::DataType::U32 => "u32",
                     crate::types::DataType::U64 => "u64",
+                    crate::types::DataType::U128 => "u128",
                     crate::types::DataType::F32 => "f32",
                     crate::types::DataType::F64 => "f64",
                     crate::types::DataType::Ptr => "ptr",
                     crate::types::DataType::Void => "void",
+                    crate::types::DataType::Bool => "bool",
+                    crate::types::DataType::Char => "char",
+                    crate::types::DataType::Str => "string",
+                    crate::types::DataType::Ref => "ref",
+                    crate::types::DataType::Vector(shape) => shape.name(),
                 };
                 Ok(Operand::Variable(type_name.to_string()))
             },
@@ -363,6 +549,50 @@ impl Parser {
         }
     }
 
+    // Parses `i*4+8`, `Point.y`, `i-4` and similar sums inside brackets.
+    // A negative literal directly after a term is read as subtraction.
+    fn parse_address_terms(&mut self) -> Result<Vec<AddressTerm>, AsmError> {
+        let mut terms = vec![self.parse_address_term()?];
+        loop {
+            match self.current() {
+                Token::Plus => {
+                    self.advance();
+                    terms.push(self.parse_address_term()?);
+                },
+                Token::Integer(val) if *val < 0 => {
+                    terms.push(self.parse_address_term()?);
+                },
+                _ => return Ok(terms),
+            }
+        }
+    }
+
+    fn parse_address_term(&mut self) -> Result<AddressTerm, AsmError> {
+        let mut factors = vec![self.parse_address_factor()?];
+        while matches!(self.current(), Token::Star) {
+            self.advance();
+            factors.push(self.parse_address_factor()?);
+        }
+        Ok(AddressTerm { factors })
+    }
+
+    fn parse_address_factor(&mut self) -> Result<AddressFactor, AsmError> {
+        match self.current().clone() {
+            Token::Identifier(name) => {
+                self.advance();
+                Ok(AddressFactor::Name(name))
+            },
+            Token::Integer(val) => {
+                self.advance();
+                Ok(AddressFactor::Integer(val))
+            },
+            other => Err(AsmError::ParseError {
+                message: format!("Expected index or offset in address, got {:?}", other),
+                location: None,
+            }),
+        }
+    }
+
     fn parse_immediate(&mut self) -> Result<Immediate, AsmError> {
         match self.current().clone() {
             Token::Integer(val) => {
@@ -373,6 +603,10 @@ impl Parser {
                 self.advance();
                 Ok(Immediate::Float(val))
             },
+            Token::Char(val) => {
+                self.advance();
+                Ok(Immediate::Char(val))
+            },
             _ => Err(AsmError::ParseError {
                 message: format!("Expected immediate value, got {:?}", self.current()),
                 location: None,
@@ -380,6 +614,44 @@ impl Parser {
         }
     }
 
+    fn parse_initializer_list(&mut self) -> Result<Immediate, AsmError> {
+        self.expect(Token::LeftBrace)?;
+        let mut items = Vec::new();
+
+        while !self.check(Token::RightBrace) {
+            items.push(self.parse_immediate()?);
+            if self.check(Token::Comma) {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        self.expect(Token::RightBrace)?;
+        Ok(Immediate::Array(items))
+    }
+
+    // `[a, b, ...]`; the list may span several lines
+    fn parse_operand_list(&mut self) -> Result<Operand, AsmError> {
+        self.expect(Token::LeftBracket)?;
+        let mut items = Vec::new();
+
+        self.skip_newlines();
+        while !self.check(Token::RightBracket) {
+            items.push(self.parse_operand()?);
+            self.skip_newlines();
+            if self.check(Token::Comma) {
+                self.advance();
+                self.skip_newlines();
+            } else {
+                break;
+            }
+        }
+
+        self.expect(Token::RightBracket)?;
+        Ok(Operand::List(items))
+    }
+
     fn expect_keyword(&mut self, keyword: &str) -> Result<(), AsmError> {
         if !self.check_keyword(keyword) {
             return Err(AsmError::ParseError {
@@ -449,6 +721,7 @@ impl Parser {
             Token::Local if keyword == "local" => true,
             Token::Include if keyword == "include" => true,
             Token::Define if keyword == "define" => true,
+            Token::Struct if keyword == "struct" => true,
             Token::FuncBegin if keyword == "func_begin" => true,
             Token::FuncEnd if keyword == "func_end" => true,
             _ => false,
@@ -514,4 +787,20 @@ mod tests {
         assert_eq!(program.data_section.len(), 1);
         assert_eq!(program.data_section[0].name, "result");
     }
+
+    #[test]
+    fn test_parse_array_declarations() {
+        let input = "section .data\n    table: i32[16] = {1, 2, 3}\n    buf: u8[256]\n    primes: u16[] = {2, 3, 5, 7}\n";
+        let mut lexer = Lexer::new(input, "test.vasm".to_string());
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+        let program = parser.parse().unwrap();
+
+        assert_eq!(program.data_section.len(), 3);
+        assert_eq!(program.data_section[0].array_len, Some(16));
+        assert!(matches!(&program.data_section[0].value, Some(Immediate::Array(items)) if items.len() == 3));
+        assert_eq!(program.data_section[1].array_len, Some(256));
+        assert!(program.data_section[1].value.is_none());
+        assert_eq!(program.data_section[2].array_len, Some(4));
+    }
 }
@@ -1,8 +1,10 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use std::fs;
-use std::path::PathBuf;
-use varvm::asm::{assemble, disassemble};
+use std::path::{Path, PathBuf};
+use varvm::asm::{assemble, assemble_with_options, disassemble, AssembleOptions};
 use varvm::bytecode::{encode, decode};
+use varvm::capabilities::Capabilities;
+use varvm::system::SystemServices;
 use varvm::vm::VM;
 
 #[derive(Parser)]
@@ -13,6 +15,65 @@ struct Cli {
     command: Commands,
 }
 
+#[derive(Args)]
+struct RunOptions {
+    #[arg(long = "allow-read", value_name = "DIR", help = "Allow the program to read files under DIR")]
+    allow_read: Vec<PathBuf>,
+
+    #[arg(long = "allow-write", value_name = "DIR", help = "Allow the program to create and write files under DIR")]
+    allow_write: Vec<PathBuf>,
+
+    #[arg(long = "allow-env", help = "Allow the program to read environment variables")]
+    allow_env: bool,
+
+    #[arg(long = "allow-net", help = "Allow the program to open TCP connections and listen")]
+    allow_net: bool,
+
+    #[arg(long, help = "Run with a virtual clock starting at 0 and a fixed RNG seed")]
+    deterministic: bool,
+
+    #[arg(long, value_name = "N", help = "RNG seed (implies --deterministic)")]
+    seed: Option<u64>,
+
+    #[arg(last = true, help = "Arguments passed to main as argc/argv")]
+    args: Vec<String>,
+}
+
+impl RunOptions {
+    fn capabilities(&self) -> Result<Capabilities, Box<dyn std::error::Error>> {
+        let mut capabilities = Capabilities::new();
+        for dir in &self.allow_read {
+            capabilities
+                .allow_read(dir)
+                .map_err(|e| format!("--allow-read {}: {}", dir.display(), e))?;
+        }
+        for dir in &self.allow_write {
+            capabilities
+                .allow_write(dir)
+                .map_err(|e| format!("--allow-write {}: {}", dir.display(), e))?;
+        }
+        if self.allow_env {
+            capabilities.allow_env();
+        }
+        if self.allow_net {
+            capabilities.allow_net();
+        }
+        Ok(capabilities)
+    }
+
+    // Applies the sandbox, system services and program arguments to `vm`
+    fn configure(&self, vm: &mut VM, program_name: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        vm.set_capabilities(self.capabilities()?);
+        if self.deterministic || self.seed.is_some() {
+            vm.set_system(SystemServices::deterministic(0, self.seed.unwrap_or(0)));
+        }
+        let mut argv = vec![program_name.to_string_lossy().to_string()];
+        argv.extend(self.args.iter().cloned());
+        vm.set_args(&argv)?;
+        Ok(())
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     #[command(about = "Assemble .vasm file to bytecode")]
@@ -22,18 +83,27 @@ enum Commands {
 
         #[arg(short, long, help = "Output .vbc bytecode file")]
         output: PathBuf,
+
+        #[arg(long = "strip-asserts", help = "Leave assert instructions out of the bytecode (release build)")]
+        strip_asserts: bool,
     },
 
     #[command(about = "Run a .vbc bytecode file")]
     Run {
         #[arg(help = "Bytecode file to run")]
         input: PathBuf,
+
+        #[command(flatten)]
+        options: RunOptions,
     },
 
     #[command(about = "Assemble and run a .vasm file")]
     AsmRun {
         #[arg(help = "Input .vasm file")]
         input: PathBuf,
+
+        #[command(flatten)]
+        options: RunOptions,
     },
 
     #[command(about = "Disassemble bytecode or program back to .vasm")]
@@ -52,6 +122,9 @@ enum Commands {
 
         #[arg(long, default_value_t = 10, help = "Number of top items to show")]
         top: usize,
+
+        #[command(flatten)]
+        options: RunOptions,
     },
 
     #[command(about = "List available standard library files")]
@@ -59,7 +132,7 @@ enum Commands {
 
     #[command(about = "Extract a standard library file")]
     ExtractStdlib {
-        #[arg(help = "Library name (prelude, math)")]
+        #[arg(help = "Library name (prelude, math, strings)")]
         name: String,
 
         #[arg(short, long, help = "Output file (default: <name>.vasm)")]
@@ -71,20 +144,21 @@ fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Assemble { input, output } => {
-            if let Err(e) = assemble_command(input, output) {
+        Commands::Assemble { input, output, strip_asserts } => {
+            let options = AssembleOptions { strip_asserts };
+            if let Err(e) = assemble_command(input, output, &options) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         },
-        Commands::Run { input } => {
-            if let Err(e) = run_command(input) {
+        Commands::Run { input, options } => {
+            if let Err(e) = run_command(input, &options) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         },
-        Commands::AsmRun { input } => {
-            if let Err(e) = asm_run_command(input) {
+        Commands::AsmRun { input, options } => {
+            if let Err(e) = asm_run_command(input, &options) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
@@ -95,8 +169,8 @@ fn main() {
                 std::process::exit(1);
             }
         },
-        Commands::Profile { input, top } => {
-            if let Err(e) = profile_command(input, top) {
+        Commands::Profile { input, top, options } => {
+            if let Err(e) = profile_command(input, top, &options) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
@@ -113,12 +187,16 @@ fn main() {
     }
 }
 
-fn assemble_command(input: PathBuf, output: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+fn assemble_command(
+    input: PathBuf,
+    output: PathBuf,
+    options: &AssembleOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
     let source = fs::read_to_string(&input)?;
     let filename = input.to_string_lossy().to_string();
 
     println!("Assembling {}...", filename);
-    let program = assemble(&source, filename)?;
+    let program = assemble_with_options(&source, filename, options)?;
 
     println!("Encoding to bytecode...");
     let bytecode = encode(&program)?;
@@ -134,7 +212,7 @@ fn assemble_command(input: PathBuf, output: PathBuf) -> Result<(), Box<dyn std::
     Ok(())
 }
 
-fn run_command(input: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+fn run_command(input: PathBuf, options: &RunOptions) -> Result<(), Box<dyn std::error::Error>> {
     println!("Loading bytecode from {}...", input.display());
     let bytecode = fs::read(&input)?;
 
@@ -143,6 +221,7 @@ fn run_command(input: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
 
     println!("Running program...\n");
     let mut vm = VM::new(program);
+    options.configure(&mut vm, &input)?;
     match vm.run() {
         Ok(exit_code) => {
             println!("\nProgram exited with code: {}", exit_code);
@@ -154,7 +233,7 @@ fn run_command(input: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
-fn asm_run_command(input: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+fn asm_run_command(input: PathBuf, options: &RunOptions) -> Result<(), Box<dyn std::error::Error>> {
     let source = fs::read_to_string(&input)?;
     let filename = input.to_string_lossy().to_string();
 
@@ -163,6 +242,7 @@ fn asm_run_command(input: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
 
     println!("Running program...\n");
     let mut vm = VM::new(program);
+    options.configure(&mut vm, &input)?;
     match vm.run() {
         Ok(exit_code) => {
             println!("\nProgram exited with code: {}", exit_code);
@@ -194,7 +274,7 @@ fn disasm_command(input: PathBuf, output: Option<PathBuf>) -> Result<(), Box<dyn
     Ok(())
 }
 
-fn profile_command(input: PathBuf, top: usize) -> Result<(), Box<dyn std::error::Error>> {
+fn profile_command(input: PathBuf, top: usize, options: &RunOptions) -> Result<(), Box<dyn std::error::Error>> {
     use varvm::tools::profiler::Profiler;
 
     let source = fs::read_to_string(&input)?;
@@ -205,6 +285,7 @@ fn profile_command(input: PathBuf, top: usize) -> Result<(), Box<dyn std::error:
 
     println!("Running with profiling enabled...\n");
     let mut vm = VM::new(program);
+    options.configure(&mut vm, &input)?;
     vm.enable_profiling();
 
     match vm.run() {
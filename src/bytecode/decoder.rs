@@ -1,7 +1,8 @@
-use crate::bytecode::{MAGIC, VERSION};
+use crate::bytecode::{MAGIC, MIN_VERSION, VERSION};
 use crate::opcode::OpCode;
 use crate::program::{Function, Program, Variable};
-use crate::types::{DataType, Operand, Value};
+use crate::simd::VectorType;
+use crate::types::{Address, DataType, Endian, Operand, Value};
 use std::collections::HashMap;
 use std::io::{self, Read};
 
@@ -17,7 +18,7 @@ pub fn decode(data: &[u8]) -> io::Result<Program> {
     }
 
     let version = read_u32(data, &mut cursor)?;
-    if version != VERSION {
+    if !(MIN_VERSION..=VERSION).contains(&version) {
         return Err(io::Error::new(
             io::ErrorKind::InvalidData,
             format!("Unsupported version: {}", version),
@@ -27,9 +28,13 @@ pub fn decode(data: &[u8]) -> io::Result<Program> {
     let mut program = Program::new();
 
     program.globals = decode_globals(data, &mut cursor)?;
-    program.functions = decode_functions(data, &mut cursor)?;
+    if version >= 2 {
+        decode_strings(data, &mut cursor, &mut program)?;
+        decode_data_blocks(data, &mut cursor, &mut program)?;
+    }
+    program.functions = decode_functions(data, &mut cursor, version)?;
     program.labels = decode_labels(data, &mut cursor)?;
-    program.instructions = decode_instructions(data, &mut cursor)?;
+    program.instructions = decode_instructions(data, &mut cursor, version)?;
 
     Ok(program)
 }
@@ -47,9 +52,44 @@ fn decode_globals(data: &[u8], cursor: &mut usize) -> io::Result<Vec<Variable>>
     Ok(globals)
 }
 
+fn decode_strings(data: &[u8], cursor: &mut usize, program: &mut Program) -> io::Result<()> {
+    let count = read_u32(data, cursor)? as usize;
+
+    for _ in 0..count {
+        let global_name = read_string(data, cursor)?;
+        let content = read_string(data, cursor)?;
+        program.add_string(content, global_name);
+    }
+
+    Ok(())
+}
+
+fn decode_data_blocks(data: &[u8], cursor: &mut usize, program: &mut Program) -> io::Result<()> {
+    let count = read_u32(data, cursor)? as usize;
+
+    for _ in 0..count {
+        let global_name = read_string(data, cursor)?;
+        let dtype = read_datatype(data, cursor)?;
+        let elements = read_u32(data, cursor)? as usize;
+        let len = read_u32(data, cursor)? as usize;
+        if *cursor + len > data.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Unexpected end of bytecode",
+            ));
+        }
+        let bytes = data[*cursor..*cursor + len].to_vec();
+        *cursor += len;
+        program.add_data_block(global_name, dtype, elements, bytes);
+    }
+
+    Ok(())
+}
+
 fn decode_functions(
     data: &[u8],
     cursor: &mut usize,
+    version: u32,
 ) -> io::Result<HashMap<String, Function>> {
     let count = read_u32(data, cursor)? as usize;
     let mut functions = HashMap::new();
@@ -60,13 +100,27 @@ fn decode_functions(
         let start_ip = read_u32(data, cursor)? as usize;
         let end_ip = read_u32(data, cursor)? as usize;
 
+        // Signatures were added in version 6
+        let mut typed = false;
+        let mut parameters = Vec::new();
+        if version >= 6 {
+            typed = read_u8(data, cursor)? != 0;
+            let param_count = read_u32(data, cursor)? as usize;
+            for _ in 0..param_count {
+                let param_name = read_string(data, cursor)?;
+                let dtype = read_datatype(data, cursor)?;
+                parameters.push(Variable::new(param_name, dtype, false));
+            }
+        }
+
         let func = Function {
             name: name.clone(),
             return_type,
-            parameters: Vec::new(),
+            parameters,
             locals: Vec::new(),
             start_ip,
             end_ip,
+            typed,
         };
 
         functions.insert(name, func);
@@ -88,18 +142,18 @@ fn decode_labels(data: &[u8], cursor: &mut usize) -> io::Result<HashMap<String,
     Ok(labels)
 }
 
-fn decode_instructions(data: &[u8], cursor: &mut usize) -> io::Result<Vec<OpCode>> {
+fn decode_instructions(data: &[u8], cursor: &mut usize, version: u32) -> io::Result<Vec<OpCode>> {
     let count = read_u32(data, cursor)? as usize;
     let mut instructions = Vec::with_capacity(count);
 
     for _ in 0..count {
-        instructions.push(decode_opcode(data, cursor)?);
+        instructions.push(decode_opcode(data, cursor, version)?);
     }
 
     Ok(instructions)
 }
 
-fn decode_opcode(data: &[u8], cursor: &mut usize) -> io::Result<OpCode> {
+fn decode_opcode(data: &[u8], cursor: &mut usize, version: u32) -> io::Result<OpCode> {
     let opcode_id = read_u8(data, cursor)?;
 
     match opcode_id {
@@ -173,21 +227,80 @@ fn decode_opcode(data: &[u8], cursor: &mut usize) -> io::Result<OpCode> {
         },
         7 => {
             let dest = read_string(data, cursor)?;
-            let ptr = read_string(data, cursor)?;
+            let addr = read_address(data, cursor, version)?;
             let dtype = read_datatype(data, cursor)?;
-            Ok(OpCode::Load { dest, ptr, dtype })
+            let endian = read_endian(data, cursor, version)?;
+            Ok(OpCode::Load { dest, addr, dtype, endian })
         },
         8 => {
-            let ptr = read_string(data, cursor)?;
-            let source = read_string(data, cursor)?;
+            let addr = read_address(data, cursor, version)?;
+            // Before version 3 the store source was always a variable name
+            let source = if version >= 3 {
+                read_operand(data, cursor)?
+            } else {
+                Operand::Variable(read_string(data, cursor)?)
+            };
             let dtype = read_datatype(data, cursor)?;
-            Ok(OpCode::Store { ptr, source, dtype })
+            let endian = read_endian(data, cursor, version)?;
+            Ok(OpCode::Store { addr, source, dtype, endian })
         },
         9 => {
             let dest = read_string(data, cursor)?;
             let var = read_string(data, cursor)?;
             Ok(OpCode::GetAddr { dest, var })
         },
+        100 => {
+            let dest = read_string(data, cursor)?;
+            let src = read_string(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::MemCopy { dest, src, len })
+        },
+        101 => {
+            let dest = read_string(data, cursor)?;
+            let src = read_string(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::MemMove { dest, src, len })
+        },
+        102 => {
+            let dest = read_string(data, cursor)?;
+            let value = read_operand(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::MemSet { dest, value, len })
+        },
+        103 => {
+            let result = read_string(data, cursor)?;
+            let a = read_string(data, cursor)?;
+            let b = read_string(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::MemCmp { result, a, b, len })
+        },
+        104 => {
+            let dest = read_string(data, cursor)?;
+            let target = read_operand(data, cursor)?;
+            Ok(OpCode::SizeOf { dest, target })
+        },
+        105 => {
+            let dest = read_string(data, cursor)?;
+            let elem = read_operand(data, cursor)?;
+            let count = read_operand(data, cursor)?;
+            Ok(OpCode::AllocArray { dest, elem, count })
+        },
+        106 => {
+            let buf = read_string(data, cursor)?;
+            let addr = read_address(data, cursor, version)?;
+            let count = read_operand(data, cursor)?;
+            let dtype = read_datatype(data, cursor)?;
+            let endian = read_endian(data, cursor, version)?;
+            Ok(OpCode::LoadVec { buf, addr, count, dtype, endian })
+        },
+        107 => {
+            let addr = read_address(data, cursor, version)?;
+            let buf = read_string(data, cursor)?;
+            let count = read_operand(data, cursor)?;
+            let dtype = read_datatype(data, cursor)?;
+            let endian = read_endian(data, cursor, version)?;
+            Ok(OpCode::StoreVec { addr, buf, count, dtype, endian })
+        },
         16 => {
             let dest = read_string(data, cursor)?;
             let left = read_operand(data, cursor)?;
@@ -223,6 +336,53 @@ fn decode_opcode(data: &[u8], cursor: &mut usize) -> io::Result<OpCode> {
             let right = read_operand(data, cursor)?;
             Ok(OpCode::Shr { dest, left, right })
         },
+        22 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Popcnt { dest, source })
+        },
+        23 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Clz { dest, source })
+        },
+        24 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Ctz { dest, source })
+        },
+        25 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Bswap { dest, source })
+        },
+        26 => {
+            let dest = read_string(data, cursor)?;
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            Ok(OpCode::Rotl { dest, left, right })
+        },
+        27 => {
+            let dest = read_string(data, cursor)?;
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            Ok(OpCode::Rotr { dest, left, right })
+        },
+        28 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            let start = read_operand(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::BitExtract { dest, source, start, len })
+        },
+        29 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            let field = read_operand(data, cursor)?;
+            let start = read_operand(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::BitInsert { dest, source, field, start, len })
+        },
         30 => {
             let dest = read_string(data, cursor)?;
             let left = read_operand(data, cursor)?;
@@ -259,6 +419,13 @@ fn decode_opcode(data: &[u8], cursor: &mut usize) -> io::Result<OpCode> {
             let right = read_operand(data, cursor)?;
             Ok(OpCode::Ge { dest, left, right })
         },
+        36 => {
+            let dest = read_string(data, cursor)?;
+            let cond = read_operand(data, cursor)?;
+            let a = read_operand(data, cursor)?;
+            let b = read_operand(data, cursor)?;
+            Ok(OpCode::Select { dest, cond, a, b })
+        },
         50 => {
             let name = read_string(data, cursor)?;
             Ok(OpCode::Label { name })
@@ -277,6 +444,42 @@ fn decode_opcode(data: &[u8], cursor: &mut usize) -> io::Result<OpCode> {
             let label = read_string(data, cursor)?;
             Ok(OpCode::Jnz { var, label })
         },
+        54 => {
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            let label = read_string(data, cursor)?;
+            Ok(OpCode::Jeq { left, right, label })
+        },
+        55 => {
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            let label = read_string(data, cursor)?;
+            Ok(OpCode::Jne { left, right, label })
+        },
+        56 => {
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            let label = read_string(data, cursor)?;
+            Ok(OpCode::Jlt { left, right, label })
+        },
+        57 => {
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            let label = read_string(data, cursor)?;
+            Ok(OpCode::Jle { left, right, label })
+        },
+        58 => {
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            let label = read_string(data, cursor)?;
+            Ok(OpCode::Jgt { left, right, label })
+        },
+        59 => {
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            let label = read_string(data, cursor)?;
+            Ok(OpCode::Jge { left, right, label })
+        },
         60 => {
             let name = read_string(data, cursor)?;
             let return_type = read_datatype(data, cursor)?;
@@ -364,14 +567,457 @@ fn decode_opcode(data: &[u8], cursor: &mut usize) -> io::Result<OpCode> {
             let source = read_operand(data, cursor)?;
             Ok(OpCode::Tan { dest, source })
         },
+        140 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Floor { dest, source })
+        },
+        141 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Ceil { dest, source })
+        },
+        142 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Round { dest, source })
+        },
+        143 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Trunc { dest, source })
+        },
+        144 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Exp { dest, source })
+        },
+        145 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Ln { dest, source })
+        },
+        146 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Log2 { dest, source })
+        },
+        147 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Log10 { dest, source })
+        },
+        148 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Asin { dest, source })
+        },
+        149 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Acos { dest, source })
+        },
+        150 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::Atan { dest, source })
+        },
+        151 => {
+            let dest = read_string(data, cursor)?;
+            let y = read_operand(data, cursor)?;
+            let x = read_operand(data, cursor)?;
+            Ok(OpCode::Atan2 { dest, y, x })
+        },
+        152 => {
+            let dest = read_string(data, cursor)?;
+            let x = read_operand(data, cursor)?;
+            let y = read_operand(data, cursor)?;
+            Ok(OpCode::Hypot { dest, x, y })
+        },
+        153 => {
+            let dest = read_string(data, cursor)?;
+            let magnitude = read_operand(data, cursor)?;
+            let sign = read_operand(data, cursor)?;
+            Ok(OpCode::CopySign { dest, magnitude, sign })
+        },
+        154 => {
+            let dest = read_string(data, cursor)?;
+            let a = read_operand(data, cursor)?;
+            let b = read_operand(data, cursor)?;
+            let c = read_operand(data, cursor)?;
+            Ok(OpCode::Fma { dest, a, b, c })
+        },
+        155 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::IsNan { dest, source })
+        },
+        156 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::IsInf { dest, source })
+        },
+        157 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::IsFinite { dest, source })
+        },
+        160 => {
+            let dest = read_string(data, cursor)?;
+            let value = read_operand(data, cursor)?;
+            let dtype = read_datatype(data, cursor)?;
+            Ok(OpCode::VecSplat { dest, value, dtype })
+        },
+        161 => {
+            let dest = read_string(data, cursor)?;
+            let vector = read_operand(data, cursor)?;
+            let lane = read_operand(data, cursor)?;
+            Ok(OpCode::VecExtract { dest, vector, lane })
+        },
+        162 => {
+            let dest = read_string(data, cursor)?;
+            let vector = read_operand(data, cursor)?;
+            let lane = read_operand(data, cursor)?;
+            let value = read_operand(data, cursor)?;
+            Ok(OpCode::VecInsert { dest, vector, lane, value })
+        },
+        163 => {
+            let dest = read_string(data, cursor)?;
+            let a = read_operand(data, cursor)?;
+            let b = read_operand(data, cursor)?;
+            let count = read_u8(data, cursor)?;
+            let lanes = (0..count).map(|_| read_u8(data, cursor)).collect::<io::Result<Vec<_>>>()?;
+            Ok(OpCode::VecShuffle { dest, a, b, lanes })
+        },
+        164 => {
+            let dest = read_string(data, cursor)?;
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            Ok(OpCode::VecEq { dest, left, right })
+        },
+        165 => {
+            let dest = read_string(data, cursor)?;
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            Ok(OpCode::VecNe { dest, left, right })
+        },
+        166 => {
+            let dest = read_string(data, cursor)?;
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            Ok(OpCode::VecLt { dest, left, right })
+        },
+        167 => {
+            let dest = read_string(data, cursor)?;
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            Ok(OpCode::VecLe { dest, left, right })
+        },
+        168 => {
+            let dest = read_string(data, cursor)?;
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            Ok(OpCode::VecGt { dest, left, right })
+        },
+        169 => {
+            let dest = read_string(data, cursor)?;
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            Ok(OpCode::VecGe { dest, left, right })
+        },
+        170 => {
+            let dest = read_string(data, cursor)?;
+            let mask = read_operand(data, cursor)?;
+            let a = read_operand(data, cursor)?;
+            let b = read_operand(data, cursor)?;
+            Ok(OpCode::VecSelect { dest, mask, a, b })
+        },
+        171 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::StrLen { dest, source })
+        },
+        172 => {
+            let dest = read_string(data, cursor)?;
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            Ok(OpCode::StrConcat { dest, left, right })
+        },
+        173 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            let start = read_operand(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::StrSub { dest, source, start, len })
+        },
+        174 => {
+            let dest = read_string(data, cursor)?;
+            let haystack = read_operand(data, cursor)?;
+            let needle = read_operand(data, cursor)?;
+            Ok(OpCode::StrFind { dest, haystack, needle })
+        },
+        175 => {
+            let dest = read_string(data, cursor)?;
+            let left = read_operand(data, cursor)?;
+            let right = read_operand(data, cursor)?;
+            Ok(OpCode::StrCmp { dest, left, right })
+        },
+        176 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::StrToInt { dest, source })
+        },
+        177 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::IntToStr { dest, source })
+        },
+        178 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::FloatToStr { dest, source })
+        },
+        179 => {
+            let dest = read_string(data, cursor)?;
+            let ptr = read_operand(data, cursor)?;
+            Ok(OpCode::PtrToStr { dest, ptr })
+        },
+        180 => {
+            let dest = read_string(data, cursor)?;
+            let source = read_operand(data, cursor)?;
+            Ok(OpCode::StrToPtr { dest, source })
+        },
+        181 => {
+            let dest = read_string(data, cursor)?;
+            Ok(OpCode::ArrayNew { dest })
+        },
+        182 => {
+            let array = read_operand(data, cursor)?;
+            let value = read_operand(data, cursor)?;
+            Ok(OpCode::ArrayPush { array, value })
+        },
+        183 => {
+            let dest = read_string(data, cursor)?;
+            let array = read_operand(data, cursor)?;
+            Ok(OpCode::ArrayPop { dest, array })
+        },
+        184 => {
+            let dest = read_string(data, cursor)?;
+            Ok(OpCode::MapNew { dest })
+        },
+        185 => {
+            let dest = read_string(data, cursor)?;
+            let map = read_operand(data, cursor)?;
+            let key = read_operand(data, cursor)?;
+            Ok(OpCode::MapHas { dest, map, key })
+        },
+        186 => {
+            let dest = read_string(data, cursor)?;
+            let map = read_operand(data, cursor)?;
+            let index = read_operand(data, cursor)?;
+            Ok(OpCode::MapKey { dest, map, index })
+        },
+        187 => {
+            let dest = read_string(data, cursor)?;
+            let object = read_operand(data, cursor)?;
+            Ok(OpCode::ObjLen { dest, object })
+        },
+        188 => {
+            let dest = read_string(data, cursor)?;
+            let object = read_operand(data, cursor)?;
+            let key = read_operand(data, cursor)?;
+            Ok(OpCode::ObjGet { dest, object, key })
+        },
+        189 => {
+            let object = read_operand(data, cursor)?;
+            let key = read_operand(data, cursor)?;
+            let value = read_operand(data, cursor)?;
+            Ok(OpCode::ObjSet { object, key, value })
+        },
         70 => {
             let var = read_string(data, cursor)?;
             Ok(OpCode::Print { var })
         },
         72 => {
             let dest = read_string(data, cursor)?;
-            Ok(OpCode::Input { dest })
+            // Before version 4 input always prompted for an i32
+            if version < 4 {
+                return Ok(OpCode::Input { dest, dtype: DataType::I32, status: None, prompt: true });
+            }
+            let dtype = read_datatype(data, cursor)?;
+            let status = if read_u8(data, cursor)? == 1 {
+                Some(read_string(data, cursor)?)
+            } else {
+                None
+            };
+            let prompt = read_u8(data, cursor)? != 0;
+            Ok(OpCode::Input { dest, dtype, status, prompt })
+        },
+        73 => {
+            let ptr = read_string(data, cursor)?;
+            Ok(OpCode::PrintStr { ptr })
+        },
+        74 => {
+            let value = read_operand(data, cursor)?;
+            Ok(OpCode::PrintChar { value })
+        },
+        75 => {
+            let ptr = read_string(data, cursor)?;
+            Ok(OpCode::EPrintStr { ptr })
+        },
+        76 => {
+            let value = read_operand(data, cursor)?;
+            Ok(OpCode::EPrintChar { value })
+        },
+        77 => {
+            let dest = read_string(data, cursor)?;
+            let buf = read_string(data, cursor)?;
+            let cap = read_operand(data, cursor)?;
+            Ok(OpCode::ReadLine { dest, buf, cap })
+        },
+        78 => {
+            let fmt = read_string(data, cursor)?;
+            let arg_count = read_u32(data, cursor)? as usize;
+            let mut args = Vec::with_capacity(arg_count);
+            for _ in 0..arg_count {
+                args.push(read_operand(data, cursor)?);
+            }
+            Ok(OpCode::Printf { fmt, args })
+        },
+        110 => {
+            let dest = read_string(data, cursor)?;
+            let path = read_string(data, cursor)?;
+            let mode = read_operand(data, cursor)?;
+            Ok(OpCode::FileOpen { dest, path, mode })
+        },
+        111 => {
+            let dest = read_string(data, cursor)?;
+            let handle = read_operand(data, cursor)?;
+            let buf = read_string(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::FileRead { dest, handle, buf, len })
+        },
+        112 => {
+            let dest = read_string(data, cursor)?;
+            let handle = read_operand(data, cursor)?;
+            let buf = read_string(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::FileWrite { dest, handle, buf, len })
+        },
+        113 => {
+            let dest = read_string(data, cursor)?;
+            let handle = read_operand(data, cursor)?;
+            let offset = read_operand(data, cursor)?;
+            let whence = read_operand(data, cursor)?;
+            Ok(OpCode::FileSeek { dest, handle, offset, whence })
+        },
+        114 => {
+            let dest = read_string(data, cursor)?;
+            let handle = read_operand(data, cursor)?;
+            Ok(OpCode::FileClose { dest, handle })
+        },
+        115 => {
+            let dest = read_string(data, cursor)?;
+            Ok(OpCode::Clock { dest })
+        },
+        116 => {
+            let millis = read_operand(data, cursor)?;
+            Ok(OpCode::Sleep { millis })
+        },
+        117 => {
+            let seed = read_operand(data, cursor)?;
+            Ok(OpCode::Srand { seed })
         },
+        118 => {
+            let dest = read_string(data, cursor)?;
+            let bound = if read_u8(data, cursor)? == 1 {
+                Some(read_operand(data, cursor)?)
+            } else {
+                None
+            };
+            Ok(OpCode::Rand { dest, bound })
+        },
+        119 => {
+            let dest = read_string(data, cursor)?;
+            let name = read_string(data, cursor)?;
+            Ok(OpCode::GetEnv { dest, name })
+        },
+        120 => {
+            let dest = read_string(data, cursor)?;
+            let addr = read_string(data, cursor)?;
+            Ok(OpCode::TcpConnect { dest, addr })
+        },
+        121 => {
+            let dest = read_string(data, cursor)?;
+            let addr = read_string(data, cursor)?;
+            Ok(OpCode::TcpListen { dest, addr })
+        },
+        122 => {
+            let dest = read_string(data, cursor)?;
+            let handle = read_operand(data, cursor)?;
+            Ok(OpCode::TcpAccept { dest, handle })
+        },
+        123 => {
+            let dest = read_string(data, cursor)?;
+            let handle = read_operand(data, cursor)?;
+            let buf = read_string(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::TcpSend { dest, handle, buf, len })
+        },
+        124 => {
+            let dest = read_string(data, cursor)?;
+            let handle = read_operand(data, cursor)?;
+            let buf = read_string(data, cursor)?;
+            let len = read_operand(data, cursor)?;
+            Ok(OpCode::TcpRecv { dest, handle, buf, len })
+        },
+        125 => {
+            let dest = read_string(data, cursor)?;
+            let handle = read_operand(data, cursor)?;
+            Ok(OpCode::TcpClose { dest, handle })
+        },
+        126 => {
+            let dest = read_string(data, cursor)?;
+            let handle = read_operand(data, cursor)?;
+            let enabled = read_operand(data, cursor)?;
+            Ok(OpCode::TcpNonblock { dest, handle, enabled })
+        },
+        130 => {
+            let dest = read_string(data, cursor)?;
+            let wait = read_u8(data, cursor)? != 0;
+            Ok(OpCode::ReadKey { dest, wait })
+        },
+        131 => {
+            let row = read_operand(data, cursor)?;
+            let col = read_operand(data, cursor)?;
+            Ok(OpCode::SetCursor { row, col })
+        },
+        132 => Ok(OpCode::ClearScreen),
+        133 => {
+            let fg = read_operand(data, cursor)?;
+            let bg = read_operand(data, cursor)?;
+            Ok(OpCode::SetColor { fg, bg })
+        },
+        66 => {
+            let value = read_operand(data, cursor)?;
+            let (default, targets) = read_jump_table(data, cursor)?;
+            Ok(OpCode::Switch { value, default, targets })
+        },
+        67 => {
+            let cond = read_operand(data, cursor)?;
+            let has_message = read_u8(data, cursor)?;
+            let message = if has_message == 1 {
+                Some(read_string(data, cursor)?)
+            } else {
+                None
+            };
+            Ok(OpCode::Assert { cond, message })
+        },
+        68 => {
+            let code = read_operand(data, cursor)?;
+            Ok(OpCode::Trap { code })
+        },
+        69 => Ok(OpCode::Unreachable),
         71 => {
             let code = read_operand(data, cursor)?;
             Ok(OpCode::Exit { code })
@@ -383,6 +1029,51 @@ fn decode_opcode(data: &[u8], cursor: &mut usize) -> io::Result<OpCode> {
     }
 }
 
+fn read_jump_table(data: &[u8], cursor: &mut usize) -> io::Result<(String, Vec<String>)> {
+    let label_count = read_u16(data, cursor)? as usize;
+    let mut labels = Vec::with_capacity(label_count);
+    for _ in 0..label_count {
+        labels.push(read_string(data, cursor)?);
+    }
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid switch table");
+    let default = labels.first().cloned().ok_or_else(invalid)?;
+
+    let entry_count = read_u32(data, cursor)? as usize;
+    let mut targets = Vec::with_capacity(entry_count.min(data.len()));
+    for _ in 0..entry_count {
+        let index = read_u16(data, cursor)? as usize;
+        targets.push(labels.get(index).cloned().ok_or_else(invalid)?);
+    }
+    Ok((default, targets))
+}
+
+fn read_address(data: &[u8], cursor: &mut usize, version: u32) -> io::Result<Address> {
+    let mut addr = Address::new(read_string(data, cursor)?);
+    if version >= 3 {
+        if read_u8(data, cursor)? != 0 {
+            addr.index = Some(read_string(data, cursor)?);
+        }
+        addr.scale = read_i64(data, cursor)?;
+        addr.offset = read_i64(data, cursor)?;
+    }
+    Ok(addr)
+}
+
+// Byte order of a memory access; everything before version 5 is little-endian
+fn read_endian(data: &[u8], cursor: &mut usize, version: u32) -> io::Result<Endian> {
+    if version < 5 {
+        return Ok(Endian::Little);
+    }
+    match read_u8(data, cursor)? {
+        0 => Ok(Endian::Little),
+        1 => Ok(Endian::Big),
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid byte order: {}", other),
+        )),
+    }
+}
+
 fn read_operand(data: &[u8], cursor: &mut usize) -> io::Result<Operand> {
     let operand_type = read_u8(data, cursor)?;
 
@@ -410,6 +1101,21 @@ fn read_operand(data: &[u8], cursor: &mut usize) -> io::Result<Operand> {
                     let v = read_f64(data, cursor)?;
                     Value::F64(v)
                 },
+                4 => {
+                    let dtype = read_datatype(data, cursor)?;
+                    let len = Variable::size_of(dtype);
+                    if *cursor + len > data.len() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "Unexpected end of bytecode",
+                        ));
+                    }
+                    let value = Value::from_le_bytes(&data[*cursor..*cursor + len], dtype)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    *cursor += len;
+                    value
+                },
+                5 => Value::Str(read_string(data, cursor)?.into()),
                 _ => {
                     return Err(io::Error::new(
                         io::ErrorKind::InvalidData,
@@ -446,6 +1152,18 @@ fn read_u8(data: &[u8], cursor: &mut usize) -> io::Result<u8> {
     Ok(value)
 }
 
+fn read_u16(data: &[u8], cursor: &mut usize) -> io::Result<u16> {
+    if *cursor + 2 > data.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Unexpected end of bytecode",
+        ));
+    }
+    let bytes = [data[*cursor], data[*cursor + 1]];
+    *cursor += 2;
+    Ok(u16::from_le_bytes(bytes))
+}
+
 fn read_u32(data: &[u8], cursor: &mut usize) -> io::Result<u32> {
     if *cursor + 4 > data.len() {
         return Err(io::Error::new(
@@ -554,6 +1272,18 @@ fn read_datatype(data: &[u8], cursor: &mut usize) -> io::Result<DataType> {
         9 => DataType::F64,
         10 => DataType::Ptr,
         11 => DataType::Void,
+        12 => DataType::I128,
+        13 => DataType::U128,
+        14 => DataType::Bool,
+        15 => DataType::Char,
+        16 => DataType::Vector(VectorType::V16U8),
+        17 => DataType::Vector(VectorType::V8I16),
+        18 => DataType::Vector(VectorType::V4I32),
+        19 => DataType::Vector(VectorType::V2I64),
+        20 => DataType::Vector(VectorType::V4F32),
+        21 => DataType::Vector(VectorType::V2F64),
+        22 => DataType::Str,
+        23 => DataType::Ref,
         _ => {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
@@ -1,7 +1,8 @@
 use crate::bytecode::{MAGIC, VERSION};
 use crate::opcode::OpCode;
-use crate::program::{Function, Program, Variable};
-use crate::types::DataType;
+use crate::program::{DataBlock, Function, Program, StringLiteral, Variable};
+use crate::simd::VectorType;
+use crate::types::{Address, DataType};
 use std::io::{self, Write};
 
 pub fn encode(program: &Program) -> io::Result<Vec<u8>> {
@@ -11,6 +12,8 @@ pub fn encode(program: &Program) -> io::Result<Vec<u8>> {
     buffer.write_all(&VERSION.to_le_bytes())?;
 
     encode_globals(&mut buffer, &program.globals)?;
+    encode_strings(&mut buffer, &program.strings)?;
+    encode_data_blocks(&mut buffer, &program.data_blocks)?;
     encode_functions(&mut buffer, &program.functions)?;
     encode_labels(&mut buffer, &program.labels)?;
     encode_instructions(&mut buffer, &program.instructions)?;
@@ -23,7 +26,32 @@ fn encode_globals(buffer: &mut Vec<u8>, globals: &[Variable]) -> io::Result<()>
 
     for global in globals {
         encode_string(buffer, &global.name)?;
-        buffer.write_all(&(global.dtype as u8).to_le_bytes())?;
+        buffer.write_all(&[encode_datatype(global.dtype)])?;
+    }
+
+    Ok(())
+}
+
+fn encode_strings(buffer: &mut Vec<u8>, strings: &[StringLiteral]) -> io::Result<()> {
+    buffer.write_all(&(strings.len() as u32).to_le_bytes())?;
+
+    for string in strings {
+        encode_string(buffer, &string.global_name)?;
+        encode_string(buffer, &string.content)?;
+    }
+
+    Ok(())
+}
+
+fn encode_data_blocks(buffer: &mut Vec<u8>, blocks: &[DataBlock]) -> io::Result<()> {
+    buffer.write_all(&(blocks.len() as u32).to_le_bytes())?;
+
+    for block in blocks {
+        encode_string(buffer, &block.global_name)?;
+        buffer.write_all(&[encode_datatype(block.dtype)])?;
+        buffer.write_all(&(block.count as u32).to_le_bytes())?;
+        buffer.write_all(&(block.bytes.len() as u32).to_le_bytes())?;
+        buffer.write_all(&block.bytes)?;
     }
 
     Ok(())
@@ -37,9 +65,15 @@ fn encode_functions(
 
     for (name, func) in functions {
         encode_string(buffer, name)?;
-        buffer.write_all(&(func.return_type as u8).to_le_bytes())?;
+        buffer.write_all(&[encode_datatype(func.return_type)])?;
         buffer.write_all(&(func.start_ip as u32).to_le_bytes())?;
         buffer.write_all(&(func.end_ip as u32).to_le_bytes())?;
+        buffer.write_all(&[func.typed as u8])?;
+        buffer.write_all(&(func.parameters.len() as u32).to_le_bytes())?;
+        for param in &func.parameters {
+            encode_string(buffer, &param.name)?;
+            buffer.write_all(&[encode_datatype(param.dtype)])?;
+        }
     }
 
     Ok(())
@@ -73,12 +107,12 @@ fn encode_opcode(buffer: &mut Vec<u8>, opcode: &OpCode) -> io::Result<()> {
     match opcode {
         OpCode::CreateLocal { dtype, name } => {
             buffer.write_all(&[0])?;
-            buffer.write_all(&(*dtype as u8).to_le_bytes())?;
+            buffer.write_all(&[encode_datatype(*dtype)])?;
             encode_string(buffer, name)?;
         },
         OpCode::CreateGlobal { dtype, name } => {
             buffer.write_all(&[1])?;
-            buffer.write_all(&(*dtype as u8).to_le_bytes())?;
+            buffer.write_all(&[encode_datatype(*dtype)])?;
             encode_string(buffer, name)?;
         },
         OpCode::DeleteLocal { name } => {
@@ -139,23 +173,77 @@ fn encode_opcode(buffer: &mut Vec<u8>, opcode: &OpCode) -> io::Result<()> {
             buffer.write_all(&[6])?;
             encode_string(buffer, ptr)?;
         },
-        OpCode::Load { dest, ptr, dtype } => {
+        OpCode::Load { dest, addr, dtype, endian } => {
             buffer.write_all(&[7])?;
             encode_string(buffer, dest)?;
-            encode_string(buffer, ptr)?;
-            buffer.write_all(&(*dtype as u8).to_le_bytes())?;
+            encode_address(buffer, addr)?;
+            buffer.write_all(&[encode_datatype(*dtype)])?;
+            buffer.write_all(&[*endian as u8])?;
         },
-        OpCode::Store { ptr, source, dtype } => {
+        OpCode::Store { addr, source, dtype, endian } => {
             buffer.write_all(&[8])?;
-            encode_string(buffer, ptr)?;
-            encode_string(buffer, source)?;
-            buffer.write_all(&(*dtype as u8).to_le_bytes())?;
+            encode_address(buffer, addr)?;
+            encode_operand(buffer, source)?;
+            buffer.write_all(&[encode_datatype(*dtype)])?;
+            buffer.write_all(&[*endian as u8])?;
         },
         OpCode::GetAddr { dest, var } => {
             buffer.write_all(&[9])?;
             encode_string(buffer, dest)?;
             encode_string(buffer, var)?;
         },
+        OpCode::MemCopy { dest, src, len } => {
+            buffer.write_all(&[100])?;
+            encode_string(buffer, dest)?;
+            encode_string(buffer, src)?;
+            encode_operand(buffer, len)?;
+        },
+        OpCode::MemMove { dest, src, len } => {
+            buffer.write_all(&[101])?;
+            encode_string(buffer, dest)?;
+            encode_string(buffer, src)?;
+            encode_operand(buffer, len)?;
+        },
+        OpCode::MemSet { dest, value, len } => {
+            buffer.write_all(&[102])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, value)?;
+            encode_operand(buffer, len)?;
+        },
+        OpCode::MemCmp { result, a, b, len } => {
+            buffer.write_all(&[103])?;
+            encode_string(buffer, result)?;
+            encode_string(buffer, a)?;
+            encode_string(buffer, b)?;
+            encode_operand(buffer, len)?;
+        },
+        OpCode::SizeOf { dest, target } => {
+            buffer.write_all(&[104])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, target)?;
+        },
+        OpCode::AllocArray { dest, elem, count } => {
+            buffer.write_all(&[105])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, elem)?;
+            encode_operand(buffer, count)?;
+        },
+        OpCode::LoadVec { buf, addr, count, dtype, endian } => {
+            buffer.write_all(&[106])?;
+            encode_string(buffer, buf)?;
+            encode_address(buffer, addr)?;
+            encode_operand(buffer, count)?;
+            buffer.write_all(&[encode_datatype(*dtype)])?;
+            buffer.write_all(&[*endian as u8])?;
+        },
+        OpCode::StoreVec { addr, buf, count, dtype, endian } => {
+            buffer.write_all(&[107])?;
+            encode_address(buffer, addr)?;
+            encode_string(buffer, buf)?;
+            encode_operand(buffer, count)?;
+            buffer.write_all(&[encode_datatype(*dtype)])?;
+            buffer.write_all(&[*endian as u8])?;
+        },
         OpCode::And { dest, left, right } => {
             buffer.write_all(&[16])?;
             encode_string(buffer, dest)?;
@@ -191,6 +279,53 @@ fn encode_opcode(buffer: &mut Vec<u8>, opcode: &OpCode) -> io::Result<()> {
             encode_operand(buffer, left)?;
             encode_operand(buffer, right)?;
         },
+        OpCode::Popcnt { dest, source } => {
+            buffer.write_all(&[22])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Clz { dest, source } => {
+            buffer.write_all(&[23])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Ctz { dest, source } => {
+            buffer.write_all(&[24])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Bswap { dest, source } => {
+            buffer.write_all(&[25])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Rotl { dest, left, right } => {
+            buffer.write_all(&[26])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+        },
+        OpCode::Rotr { dest, left, right } => {
+            buffer.write_all(&[27])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+        },
+        OpCode::BitExtract { dest, source, start, len } => {
+            buffer.write_all(&[28])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+            encode_operand(buffer, start)?;
+            encode_operand(buffer, len)?;
+        },
+        OpCode::BitInsert { dest, source, field, start, len } => {
+            buffer.write_all(&[29])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+            encode_operand(buffer, field)?;
+            encode_operand(buffer, start)?;
+            encode_operand(buffer, len)?;
+        },
         OpCode::Eq { dest, left, right } => {
             buffer.write_all(&[30])?;
             encode_string(buffer, dest)?;
@@ -227,6 +362,13 @@ fn encode_opcode(buffer: &mut Vec<u8>, opcode: &OpCode) -> io::Result<()> {
             encode_operand(buffer, left)?;
             encode_operand(buffer, right)?;
         },
+        OpCode::Select { dest, cond, a, b } => {
+            buffer.write_all(&[36])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, cond)?;
+            encode_operand(buffer, a)?;
+            encode_operand(buffer, b)?;
+        },
         OpCode::Label { name } => {
             buffer.write_all(&[50])?;
             encode_string(buffer, name)?;
@@ -235,6 +377,31 @@ fn encode_opcode(buffer: &mut Vec<u8>, opcode: &OpCode) -> io::Result<()> {
             buffer.write_all(&[51])?;
             encode_string(buffer, label)?;
         },
+        OpCode::Switch { value, default, targets } => {
+            buffer.write_all(&[66])?;
+            encode_operand(buffer, value)?;
+            encode_jump_table(buffer, default, targets)?;
+        },
+        OpCode::Assert { cond, message } => {
+            buffer.write_all(&[67])?;
+            encode_operand(buffer, cond)?;
+            match message {
+                Some(m) => {
+                    buffer.write_all(&[1])?;
+                    encode_string(buffer, m)?;
+                },
+                None => {
+                    buffer.write_all(&[0])?;
+                }
+            }
+        },
+        OpCode::Trap { code } => {
+            buffer.write_all(&[68])?;
+            encode_operand(buffer, code)?;
+        },
+        OpCode::Unreachable => {
+            buffer.write_all(&[69])?;
+        },
         OpCode::Jz { var, label } => {
             buffer.write_all(&[52])?;
             encode_string(buffer, var)?;
@@ -245,10 +412,46 @@ fn encode_opcode(buffer: &mut Vec<u8>, opcode: &OpCode) -> io::Result<()> {
             encode_string(buffer, var)?;
             encode_string(buffer, label)?;
         },
+        OpCode::Jeq { left, right, label } => {
+            buffer.write_all(&[54])?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+            encode_string(buffer, label)?;
+        },
+        OpCode::Jne { left, right, label } => {
+            buffer.write_all(&[55])?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+            encode_string(buffer, label)?;
+        },
+        OpCode::Jlt { left, right, label } => {
+            buffer.write_all(&[56])?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+            encode_string(buffer, label)?;
+        },
+        OpCode::Jle { left, right, label } => {
+            buffer.write_all(&[57])?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+            encode_string(buffer, label)?;
+        },
+        OpCode::Jgt { left, right, label } => {
+            buffer.write_all(&[58])?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+            encode_string(buffer, label)?;
+        },
+        OpCode::Jge { left, right, label } => {
+            buffer.write_all(&[59])?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+            encode_string(buffer, label)?;
+        },
         OpCode::FuncBegin { name, return_type } => {
             buffer.write_all(&[60])?;
             encode_string(buffer, name)?;
-            buffer.write_all(&(*return_type as u8).to_le_bytes())?;
+            buffer.write_all(&[encode_datatype(*return_type)])?;
         },
         OpCode::FuncEnd => {
             buffer.write_all(&[61])?;
@@ -294,7 +497,7 @@ fn encode_opcode(buffer: &mut Vec<u8>, opcode: &OpCode) -> io::Result<()> {
             buffer.write_all(&[80])?;
             encode_string(buffer, dest)?;
             encode_string(buffer, source)?;
-            buffer.write_all(&(*target_type as u8).to_le_bytes())?;
+            buffer.write_all(&[encode_datatype(*target_type)])?;
         },
         OpCode::Sqrt { dest, source } => {
             buffer.write_all(&[90])?;
@@ -339,13 +542,441 @@ fn encode_opcode(buffer: &mut Vec<u8>, opcode: &OpCode) -> io::Result<()> {
             encode_string(buffer, dest)?;
             encode_operand(buffer, source)?;
         },
+        OpCode::Floor { dest, source } => {
+            buffer.write_all(&[140])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Ceil { dest, source } => {
+            buffer.write_all(&[141])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Round { dest, source } => {
+            buffer.write_all(&[142])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Trunc { dest, source } => {
+            buffer.write_all(&[143])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Exp { dest, source } => {
+            buffer.write_all(&[144])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Ln { dest, source } => {
+            buffer.write_all(&[145])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Log2 { dest, source } => {
+            buffer.write_all(&[146])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Log10 { dest, source } => {
+            buffer.write_all(&[147])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Asin { dest, source } => {
+            buffer.write_all(&[148])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Acos { dest, source } => {
+            buffer.write_all(&[149])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Atan { dest, source } => {
+            buffer.write_all(&[150])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::Atan2 { dest, y, x } => {
+            buffer.write_all(&[151])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, y)?;
+            encode_operand(buffer, x)?;
+        },
+        OpCode::Hypot { dest, x, y } => {
+            buffer.write_all(&[152])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, x)?;
+            encode_operand(buffer, y)?;
+        },
+        OpCode::CopySign { dest, magnitude, sign } => {
+            buffer.write_all(&[153])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, magnitude)?;
+            encode_operand(buffer, sign)?;
+        },
+        OpCode::Fma { dest, a, b, c } => {
+            buffer.write_all(&[154])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, a)?;
+            encode_operand(buffer, b)?;
+            encode_operand(buffer, c)?;
+        },
+        OpCode::IsNan { dest, source } => {
+            buffer.write_all(&[155])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::IsInf { dest, source } => {
+            buffer.write_all(&[156])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::IsFinite { dest, source } => {
+            buffer.write_all(&[157])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::VecSplat { dest, value, dtype } => {
+            buffer.write_all(&[160])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, value)?;
+            buffer.write_all(&[encode_datatype(*dtype)])?;
+        },
+        OpCode::VecExtract { dest, vector, lane } => {
+            buffer.write_all(&[161])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, vector)?;
+            encode_operand(buffer, lane)?;
+        },
+        OpCode::VecInsert { dest, vector, lane, value } => {
+            buffer.write_all(&[162])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, vector)?;
+            encode_operand(buffer, lane)?;
+            encode_operand(buffer, value)?;
+        },
+        OpCode::VecShuffle { dest, a, b, lanes } => {
+            buffer.write_all(&[163])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, a)?;
+            encode_operand(buffer, b)?;
+            buffer.write_all(&[lanes.len() as u8])?;
+            buffer.write_all(lanes)?;
+        },
+        OpCode::VecEq { dest, left, right } => {
+            buffer.write_all(&[164])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+        },
+        OpCode::VecNe { dest, left, right } => {
+            buffer.write_all(&[165])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+        },
+        OpCode::VecLt { dest, left, right } => {
+            buffer.write_all(&[166])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+        },
+        OpCode::VecLe { dest, left, right } => {
+            buffer.write_all(&[167])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+        },
+        OpCode::VecGt { dest, left, right } => {
+            buffer.write_all(&[168])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+        },
+        OpCode::VecGe { dest, left, right } => {
+            buffer.write_all(&[169])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+        },
+        OpCode::VecSelect { dest, mask, a, b } => {
+            buffer.write_all(&[170])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, mask)?;
+            encode_operand(buffer, a)?;
+            encode_operand(buffer, b)?;
+        },
+        OpCode::StrLen { dest, source } => {
+            buffer.write_all(&[171])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::StrConcat { dest, left, right } => {
+            buffer.write_all(&[172])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+        },
+        OpCode::StrSub { dest, source, start, len } => {
+            buffer.write_all(&[173])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+            encode_operand(buffer, start)?;
+            encode_operand(buffer, len)?;
+        },
+        OpCode::StrFind { dest, haystack, needle } => {
+            buffer.write_all(&[174])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, haystack)?;
+            encode_operand(buffer, needle)?;
+        },
+        OpCode::StrCmp { dest, left, right } => {
+            buffer.write_all(&[175])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, left)?;
+            encode_operand(buffer, right)?;
+        },
+        OpCode::StrToInt { dest, source } => {
+            buffer.write_all(&[176])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::IntToStr { dest, source } => {
+            buffer.write_all(&[177])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::FloatToStr { dest, source } => {
+            buffer.write_all(&[178])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::PtrToStr { dest, ptr } => {
+            buffer.write_all(&[179])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, ptr)?;
+        },
+        OpCode::StrToPtr { dest, source } => {
+            buffer.write_all(&[180])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, source)?;
+        },
+        OpCode::ArrayNew { dest } => {
+            buffer.write_all(&[181])?;
+            encode_string(buffer, dest)?;
+        },
+        OpCode::ArrayPush { array, value } => {
+            buffer.write_all(&[182])?;
+            encode_operand(buffer, array)?;
+            encode_operand(buffer, value)?;
+        },
+        OpCode::ArrayPop { dest, array } => {
+            buffer.write_all(&[183])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, array)?;
+        },
+        OpCode::MapNew { dest } => {
+            buffer.write_all(&[184])?;
+            encode_string(buffer, dest)?;
+        },
+        OpCode::MapHas { dest, map, key } => {
+            buffer.write_all(&[185])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, map)?;
+            encode_operand(buffer, key)?;
+        },
+        OpCode::MapKey { dest, map, index } => {
+            buffer.write_all(&[186])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, map)?;
+            encode_operand(buffer, index)?;
+        },
+        OpCode::ObjLen { dest, object } => {
+            buffer.write_all(&[187])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, object)?;
+        },
+        OpCode::ObjGet { dest, object, key } => {
+            buffer.write_all(&[188])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, object)?;
+            encode_operand(buffer, key)?;
+        },
+        OpCode::ObjSet { object, key, value } => {
+            buffer.write_all(&[189])?;
+            encode_operand(buffer, object)?;
+            encode_operand(buffer, key)?;
+            encode_operand(buffer, value)?;
+        },
         OpCode::Print { var } => {
             buffer.write_all(&[70])?;
             encode_string(buffer, var)?;
         },
-        OpCode::Input { dest } => {
+        OpCode::Input { dest, dtype, status, prompt } => {
             buffer.write_all(&[72])?;
             encode_string(buffer, dest)?;
+            buffer.write_all(&[encode_datatype(*dtype)])?;
+            match status {
+                Some(s) => {
+                    buffer.write_all(&[1])?;
+                    encode_string(buffer, s)?;
+                },
+                None => {
+                    buffer.write_all(&[0])?;
+                }
+            }
+            buffer.write_all(&[*prompt as u8])?;
+        },
+        OpCode::PrintStr { ptr } => {
+            buffer.write_all(&[73])?;
+            encode_string(buffer, ptr)?;
+        },
+        OpCode::PrintChar { value } => {
+            buffer.write_all(&[74])?;
+            encode_operand(buffer, value)?;
+        },
+        OpCode::EPrintStr { ptr } => {
+            buffer.write_all(&[75])?;
+            encode_string(buffer, ptr)?;
+        },
+        OpCode::EPrintChar { value } => {
+            buffer.write_all(&[76])?;
+            encode_operand(buffer, value)?;
+        },
+        OpCode::ReadLine { dest, buf, cap } => {
+            buffer.write_all(&[77])?;
+            encode_string(buffer, dest)?;
+            encode_string(buffer, buf)?;
+            encode_operand(buffer, cap)?;
+        },
+        OpCode::Printf { fmt, args } => {
+            buffer.write_all(&[78])?;
+            encode_string(buffer, fmt)?;
+            buffer.write_all(&(args.len() as u32).to_le_bytes())?;
+            for arg in args {
+                encode_operand(buffer, arg)?;
+            }
+        },
+        OpCode::FileOpen { dest, path, mode } => {
+            buffer.write_all(&[110])?;
+            encode_string(buffer, dest)?;
+            encode_string(buffer, path)?;
+            encode_operand(buffer, mode)?;
+        },
+        OpCode::FileRead { dest, handle, buf, len } => {
+            buffer.write_all(&[111])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, handle)?;
+            encode_string(buffer, buf)?;
+            encode_operand(buffer, len)?;
+        },
+        OpCode::FileWrite { dest, handle, buf, len } => {
+            buffer.write_all(&[112])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, handle)?;
+            encode_string(buffer, buf)?;
+            encode_operand(buffer, len)?;
+        },
+        OpCode::FileSeek { dest, handle, offset, whence } => {
+            buffer.write_all(&[113])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, handle)?;
+            encode_operand(buffer, offset)?;
+            encode_operand(buffer, whence)?;
+        },
+        OpCode::FileClose { dest, handle } => {
+            buffer.write_all(&[114])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, handle)?;
+        },
+        OpCode::Clock { dest } => {
+            buffer.write_all(&[115])?;
+            encode_string(buffer, dest)?;
+        },
+        OpCode::Sleep { millis } => {
+            buffer.write_all(&[116])?;
+            encode_operand(buffer, millis)?;
+        },
+        OpCode::Srand { seed } => {
+            buffer.write_all(&[117])?;
+            encode_operand(buffer, seed)?;
+        },
+        OpCode::Rand { dest, bound } => {
+            buffer.write_all(&[118])?;
+            encode_string(buffer, dest)?;
+            match bound {
+                Some(b) => {
+                    buffer.write_all(&[1])?;
+                    encode_operand(buffer, b)?;
+                },
+                None => {
+                    buffer.write_all(&[0])?;
+                }
+            }
+        },
+        OpCode::GetEnv { dest, name } => {
+            buffer.write_all(&[119])?;
+            encode_string(buffer, dest)?;
+            encode_string(buffer, name)?;
+        },
+        OpCode::TcpConnect { dest, addr } => {
+            buffer.write_all(&[120])?;
+            encode_string(buffer, dest)?;
+            encode_string(buffer, addr)?;
+        },
+        OpCode::TcpListen { dest, addr } => {
+            buffer.write_all(&[121])?;
+            encode_string(buffer, dest)?;
+            encode_string(buffer, addr)?;
+        },
+        OpCode::TcpAccept { dest, handle } => {
+            buffer.write_all(&[122])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, handle)?;
+        },
+        OpCode::TcpSend { dest, handle, buf, len } => {
+            buffer.write_all(&[123])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, handle)?;
+            encode_string(buffer, buf)?;
+            encode_operand(buffer, len)?;
+        },
+        OpCode::TcpRecv { dest, handle, buf, len } => {
+            buffer.write_all(&[124])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, handle)?;
+            encode_string(buffer, buf)?;
+            encode_operand(buffer, len)?;
+        },
+        OpCode::TcpClose { dest, handle } => {
+            buffer.write_all(&[125])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, handle)?;
+        },
+        OpCode::TcpNonblock { dest, handle, enabled } => {
+            buffer.write_all(&[126])?;
+            encode_string(buffer, dest)?;
+            encode_operand(buffer, handle)?;
+            encode_operand(buffer, enabled)?;
+        },
+        OpCode::ReadKey { dest, wait } => {
+            buffer.write_all(&[130])?;
+            encode_string(buffer, dest)?;
+            buffer.write_all(&[*wait as u8])?;
+        },
+        OpCode::SetCursor { row, col } => {
+            buffer.write_all(&[131])?;
+            encode_operand(buffer, row)?;
+            encode_operand(buffer, col)?;
+        },
+        OpCode::ClearScreen => {
+            buffer.write_all(&[132])?;
+        },
+        OpCode::SetColor { fg, bg } => {
+            buffer.write_all(&[133])?;
+            encode_operand(buffer, fg)?;
+            encode_operand(buffer, bg)?;
         },
         OpCode::Exit { code } => {
             buffer.write_all(&[71])?;
@@ -383,11 +1014,15 @@ fn encode_operand(buffer: &mut Vec<u8>, operand: &crate::types::Operand) -> io::
                     buffer.write_all(&[3])?;
                     buffer.write_all(&v.to_le_bytes())?;
                 },
-                _ => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "Unsupported value type in bytecode",
-                    ));
+                Value::Str(v) => {
+                    buffer.write_all(&[5])?;
+                    encode_string(buffer, v)?;
+                },
+                other => {
+                    // Any other width: type tag followed by its memory image
+                    buffer.write_all(&[4])?;
+                    buffer.write_all(&[encode_datatype(other.dtype())])?;
+                    buffer.write_all(&other.to_le_bytes())?;
                 }
             }
         },
@@ -397,13 +1032,57 @@ fn encode_operand(buffer: &mut Vec<u8>, operand: &crate::types::Operand) -> io::
         },
         Operand::Type(dtype) => {
             buffer.write_all(&[3])?;
-            buffer.write_all(&(*dtype as u8).to_le_bytes())?;
+            buffer.write_all(&[encode_datatype(*dtype)])?;
         },
     }
 
     Ok(())
 }
 
+fn encode_address(buffer: &mut Vec<u8>, addr: &Address) -> io::Result<()> {
+    encode_string(buffer, &addr.base)?;
+    match &addr.index {
+        Some(index) => {
+            buffer.write_all(&[1])?;
+            encode_string(buffer, index)?;
+        }
+        None => buffer.write_all(&[0])?,
+    }
+    buffer.write_all(&addr.scale.to_le_bytes())?;
+    buffer.write_all(&addr.offset.to_le_bytes())?;
+    Ok(())
+}
+
+// Each distinct label is written once (the default first), then every
+// table entry as a u16 index into that list
+fn encode_jump_table(buffer: &mut Vec<u8>, default: &str, targets: &[String]) -> io::Result<()> {
+    let mut labels = vec![default];
+    let mut indices = Vec::with_capacity(targets.len());
+    for target in targets {
+        let index = match labels.iter().position(|label| label == target) {
+            Some(index) => index,
+            None => {
+                labels.push(target);
+                labels.len() - 1
+            }
+        };
+        indices.push(index as u16);
+    }
+    let label_count = u16::try_from(labels.len()).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidInput, "switch table has too many distinct labels")
+    })?;
+
+    buffer.write_all(&label_count.to_le_bytes())?;
+    for label in labels {
+        encode_string(buffer, label)?;
+    }
+    buffer.write_all(&(indices.len() as u32).to_le_bytes())?;
+    for index in indices {
+        buffer.write_all(&index.to_le_bytes())?;
+    }
+    Ok(())
+}
+
 fn encode_string(buffer: &mut Vec<u8>, s: &str) -> io::Result<()> {
     let bytes = s.as_bytes();
     buffer.write_all(&(bytes.len() as u32).to_le_bytes())?;
@@ -411,6 +1090,32 @@ fn encode_string(buffer: &mut Vec<u8>, s: &str) -> io::Result<()> {
     Ok(())
 }
 
-fn encode_datatype(_dtype: DataType) -> u8 {
-    _dtype as u8
+// Stable on-disk tags: existing values must never change, new types take the next free tag
+fn encode_datatype(dtype: DataType) -> u8 {
+    match dtype {
+        DataType::I8 => 0,
+        DataType::I16 => 1,
+        DataType::I32 => 2,
+        DataType::I64 => 3,
+        DataType::U8 => 4,
+        DataType::U16 => 5,
+        DataType::U32 => 6,
+        DataType::U64 => 7,
+        DataType::F32 => 8,
+        DataType::F64 => 9,
+        DataType::Ptr => 10,
+        DataType::Void => 11,
+        DataType::I128 => 12,
+        DataType::U128 => 13,
+        DataType::Bool => 14,
+        DataType::Char => 15,
+        DataType::Vector(VectorType::V16U8) => 16,
+        DataType::Vector(VectorType::V8I16) => 17,
+        DataType::Vector(VectorType::V4I32) => 18,
+        DataType::Vector(VectorType::V2I64) => 19,
+        DataType::Vector(VectorType::V4F32) => 20,
+        DataType::Vector(VectorType::V2F64) => 21,
+        DataType::Str => 22,
+        DataType::Ref => 23,
+    }
 }
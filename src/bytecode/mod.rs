@@ -5,4 +5,7 @@ pub use encoder::encode;
 pub use decoder::decode;
 
 pub const MAGIC: u32 = 0x56424300;
-pub const VERSION: u32 = 1;
+pub const VERSION: u32 = 6;
+
+// Oldest bytecode version the decoder still accepts
+pub const MIN_VERSION: u32 = 1;
@@ -0,0 +1,102 @@
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Host resources a program is allowed to touch. Everything is denied by
+/// default; the embedder (or the CLI flags) grants access explicitly.
+#[derive(Debug, Clone, Default)]
+pub struct Capabilities {
+    read_dirs: Vec<PathBuf>,
+    write_dirs: Vec<PathBuf>,
+    env: bool,
+    net: bool,
+}
+
+impl Capabilities {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow reading files anywhere under `dir`.
+    pub fn allow_read(&mut self, dir: impl AsRef<Path>) -> io::Result<()> {
+        self.read_dirs.push(dir.as_ref().canonicalize()?);
+        Ok(())
+    }
+
+    /// Allow creating and writing files anywhere under `dir`.
+    pub fn allow_write(&mut self, dir: impl AsRef<Path>) -> io::Result<()> {
+        self.write_dirs.push(dir.as_ref().canonicalize()?);
+        Ok(())
+    }
+
+    /// Allow reading host environment variables.
+    pub fn allow_env(&mut self) {
+        self.env = true;
+    }
+
+    pub fn check_env(&self, name: &str) -> Result<(), String> {
+        if self.env {
+            Ok(())
+        } else {
+            Err(format!(
+                "Permission denied: environment access to '{}' is not granted (use --allow-env)",
+                name
+            ))
+        }
+    }
+
+    /// Allow opening TCP connections and listening sockets.
+    pub fn allow_net(&mut self) {
+        self.net = true;
+    }
+
+    pub fn check_net(&self, addr: &str) -> Result<(), String> {
+        if self.net {
+            Ok(())
+        } else {
+            Err(format!(
+                "Permission denied: network access to '{}' is not granted (use --allow-net)",
+                addr
+            ))
+        }
+    }
+
+    pub fn check_read(&self, path: &Path) -> Result<PathBuf, String> {
+        Self::check(path, &self.read_dirs, "read", "--allow-read")
+    }
+
+    pub fn check_write(&self, path: &Path) -> Result<PathBuf, String> {
+        Self::check(path, &self.write_dirs, "write", "--allow-write")
+    }
+
+    // Resolves `path` (following symlinks and `..`) and checks it lies under a granted directory
+    fn check(path: &Path, dirs: &[PathBuf], access: &str, flag: &str) -> Result<PathBuf, String> {
+        let denied = || {
+            format!(
+                "Permission denied: {} access to '{}' is not granted (use {} DIR)",
+                access,
+                path.display(),
+                flag
+            )
+        };
+
+        let resolved = match path.canonicalize() {
+            Ok(resolved) => resolved,
+            // A dangling symlink would be followed by the later create or open
+            Err(_) if path.symlink_metadata().is_ok() => return Err(denied()),
+            // A file that does not exist yet is judged by its parent directory
+            Err(_) => match (path.parent(), path.components().next_back()) {
+                (Some(parent), Some(Component::Normal(name))) => {
+                    let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+                    parent.canonicalize().map_err(|_| denied())?.join(name)
+                }
+                _ => return Err(denied()),
+            },
+        };
+
+        if dirs.iter().any(|dir| resolved.starts_with(dir)) {
+            Ok(resolved)
+        } else {
+            Err(denied())
+        }
+    }
+}
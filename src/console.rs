@@ -0,0 +1,185 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::rc::Rc;
+
+/// Terminal device behind the `getkey`, `pollkey`, `cursor`, `clear` and
+/// `color` instructions. Colors are the 16 ANSI palette indices (0-7 normal,
+/// 8-15 bright); `None` selects the terminal's default.
+///
+/// Keys are read from `input`, the VM's input reader, so key reads and line
+/// reads share one buffer and see the same bytes in order.
+pub trait Console {
+    /// Waits for a key press and returns its byte, or `None` at end of input.
+    fn read_key(&mut self, input: &mut dyn BufRead) -> io::Result<Option<u8>>;
+
+    /// Returns a key that is already pending without waiting.
+    fn poll_key(&mut self, input: &mut dyn BufRead) -> io::Result<Option<u8>>;
+
+    /// Moves the cursor to a zero-based row and column.
+    fn move_cursor(&mut self, row: u16, col: u16) -> io::Result<()>;
+
+    /// Clears the screen and homes the cursor.
+    fn clear(&mut self) -> io::Result<()>;
+
+    fn set_color(&mut self, fg: Option<u8>, bg: Option<u8>) -> io::Result<()>;
+}
+
+/// Console on the process terminal, driven by ANSI escape sequences.
+///
+/// Keys are read without echo or line buffering; the terminal mode is switched
+/// (via `stty`) only for the duration of each read. When stdin is not a
+/// terminal, keys are plain bytes of input and `poll_key` never reports one.
+#[derive(Debug, Default)]
+pub struct AnsiConsole;
+
+impl AnsiConsole {
+    pub fn new() -> Self {
+        AnsiConsole
+    }
+
+    fn write(&mut self, sequence: &str) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        stdout.write_all(sequence.as_bytes())?;
+        stdout.flush()
+    }
+
+    // `min_bytes` 0 makes the read return immediately when nothing is pending
+    fn read_raw(&mut self, input: &mut dyn BufRead, min_bytes: u8) -> io::Result<Option<u8>> {
+        if !io::stdin().is_terminal() {
+            if min_bytes == 0 {
+                return Ok(None);
+            }
+            return read_byte(input);
+        }
+
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "min", &min_bytes.to_string(), "time", "0"])?;
+        let key = read_byte(input);
+        stty(&[saved.trim()])?;
+        key
+    }
+}
+
+impl Console for AnsiConsole {
+    fn read_key(&mut self, input: &mut dyn BufRead) -> io::Result<Option<u8>> {
+        self.read_raw(input, 1)
+    }
+
+    fn poll_key(&mut self, input: &mut dyn BufRead) -> io::Result<Option<u8>> {
+        self.read_raw(input, 0)
+    }
+
+    fn move_cursor(&mut self, row: u16, col: u16) -> io::Result<()> {
+        self.write(&format!("\x1b[{};{}H", row as u32 + 1, col as u32 + 1))
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.write("\x1b[2J\x1b[H")
+    }
+
+    fn set_color(&mut self, fg: Option<u8>, bg: Option<u8>) -> io::Result<()> {
+        let fg = match fg {
+            Some(c) if c < 8 => 30 + c as u32,
+            Some(c) => 90 + (c as u32 - 8),
+            None => 39,
+        };
+        let bg = match bg {
+            Some(c) if c < 8 => 40 + c as u32,
+            Some(c) => 100 + (c as u32 - 8),
+            None => 49,
+        };
+        self.write(&format!("\x1b[{};{}m", fg, bg))
+    }
+}
+
+// Bytes already buffered are returned before the terminal is read again
+fn read_byte(reader: &mut dyn BufRead) -> io::Result<Option<u8>> {
+    let byte = reader.fill_buf()?.first().copied();
+    if byte.is_some() {
+        reader.consume(1);
+    }
+    Ok(byte)
+}
+
+#[cfg(unix)]
+fn stty(args: &[&str]) -> io::Result<String> {
+    use std::process::{Command, Stdio};
+
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(String::from_utf8_lossy(&output.stderr).into_owned()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// No terminal mode switching elsewhere: keys arrive line-buffered
+#[cfg(not(unix))]
+fn stty(_args: &[&str]) -> io::Result<String> {
+    Ok(String::new())
+}
+
+/// Screen operation recorded by `MockConsole`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleEvent {
+    MoveCursor(u16, u16),
+    Clear,
+    SetColor(Option<u8>, Option<u8>),
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    keys: VecDeque<u8>,
+    events: Vec<ConsoleEvent>,
+}
+
+/// Headless console for tests: keys come from a queue and screen operations
+/// are recorded. Clones share state, so a test can keep a handle after
+/// giving one to the VM.
+#[derive(Debug, Clone, Default)]
+pub struct MockConsole {
+    state: Rc<RefCell<MockState>>,
+}
+
+impl MockConsole {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_keys(&self, keys: &[u8]) {
+        self.state.borrow_mut().keys.extend(keys);
+    }
+
+    pub fn events(&self) -> Vec<ConsoleEvent> {
+        self.state.borrow().events.clone()
+    }
+}
+
+impl Console for MockConsole {
+    // Scripted keys come first; once the queue is empty, keys are bytes of input
+    fn read_key(&mut self, input: &mut dyn BufRead) -> io::Result<Option<u8>> {
+        match self.state.borrow_mut().keys.pop_front() {
+            Some(key) => Ok(Some(key)),
+            None => read_byte(input),
+        }
+    }
+
+    fn poll_key(&mut self, input: &mut dyn BufRead) -> io::Result<Option<u8>> {
+        self.read_key(input)
+    }
+
+    fn move_cursor(&mut self, row: u16, col: u16) -> io::Result<()> {
+        self.state.borrow_mut().events.push(ConsoleEvent::MoveCursor(row, col));
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.state.borrow_mut().events.push(ConsoleEvent::Clear);
+        Ok(())
+    }
+
+    fn set_color(&mut self, fg: Option<u8>, bg: Option<u8>) -> io::Result<()> {
+        self.state.borrow_mut().events.push(ConsoleEvent::SetColor(fg, bg));
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use crate::opcode::OpCode;
 use crate::program::{Function, Program, Variable};
-use crate::types::DataType;
+use crate::types::{Address, DataType, Endian};
 
 pub fn bitwise_operations_test() -> Program {
     let mut prog = Program::new();
@@ -160,16 +160,18 @@ pub fn memory_operations_test() -> Program {
     // Store value
     prog.set_var("value", 123);
     prog.emit(OpCode::Store {
-        ptr: "ptr".to_string(),
-        source: "value".to_string(),
+        addr: Address::new("ptr"),
+        source: "value".into(),
         dtype: DataType::I32,
+        endian: Endian::Little,
     });
 
     // Load value
     prog.emit(OpCode::Load {
         dest: "loaded".to_string(),
-        ptr: "ptr".to_string(),
+        addr: Address::new("ptr"),
         dtype: DataType::I32,
+        endian: Endian::Little,
     });
     prog.emit(OpCode::Print {
         var: "loaded".to_string(),
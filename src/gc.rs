@@ -0,0 +1,214 @@
+use crate::types::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Live objects at which the first automatic collection runs
+const INITIAL_THRESHOLD: usize = 1024;
+
+/// Handle to an object in the managed heap. The zero handle is null.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjRef(usize);
+
+impl ObjRef {
+    pub const NULL: ObjRef = ObjRef(0);
+
+    pub fn is_null(self) -> bool {
+        self == ObjRef::NULL
+    }
+}
+
+/// A garbage-collected object.
+#[derive(Debug, Clone)]
+pub enum Object {
+    Array(Vec<Value>),
+    Map(Map),
+}
+
+impl Object {
+    pub fn len(&self) -> usize {
+        match self {
+            Object::Array(items) => items.len(),
+            Object::Map(map) => map.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Object::Array(_) => "array",
+            Object::Map(_) => "map",
+        }
+    }
+
+    fn values(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+        match self {
+            Object::Array(items) => Box::new(items.iter()),
+            Object::Map(map) => Box::new(map.entries.iter().map(|(_, value)| value)),
+        }
+    }
+}
+
+/// String-keyed hash map that iterates in insertion order, so programs
+/// walking it with `map_key` see the same order on every run.
+#[derive(Debug, Clone, Default)]
+pub struct Map {
+    index: HashMap<Arc<str>, usize>,
+    entries: Vec<(Arc<str>, Value)>,
+}
+
+impl Map {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.index.get(key).map(|i| &self.entries[*i].1)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn insert(&mut self, key: Arc<str>, value: Value) {
+        match self.index.get(&key) {
+            Some(i) => self.entries[*i].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn key_at(&self, index: usize) -> Option<&Arc<str>> {
+        self.entries.get(index).map(|(key, _)| key)
+    }
+}
+
+/// Collector counters reported by `VM::gc_stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcStats {
+    pub collections: u64,
+    pub live_objects: usize,
+    pub allocated: u64,
+    pub freed: u64,
+}
+
+/// Mark-and-sweep heap for arrays and maps, separate from the raw byte heap.
+#[derive(Debug)]
+pub struct ManagedHeap {
+    // Slot i holds handle i + 1; freed slots are reused
+    slots: Vec<Option<Object>>,
+    free: Vec<usize>,
+    threshold: usize,
+    stats: GcStats,
+}
+
+impl Default for ManagedHeap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManagedHeap {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            threshold: INITIAL_THRESHOLD,
+            stats: GcStats::default(),
+        }
+    }
+
+    pub fn alloc(&mut self, object: Object) -> ObjRef {
+        self.stats.allocated += 1;
+        self.stats.live_objects += 1;
+        match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot] = Some(object);
+                ObjRef(slot + 1)
+            }
+            None => {
+                self.slots.push(Some(object));
+                ObjRef(self.slots.len())
+            }
+        }
+    }
+
+    pub fn get(&self, obj: ObjRef) -> Result<&Object, String> {
+        obj.0
+            .checked_sub(1)
+            .and_then(|slot| self.slots.get(slot))
+            .and_then(|object| object.as_ref())
+            .ok_or_else(|| Self::invalid(obj))
+    }
+
+    pub fn get_mut(&mut self, obj: ObjRef) -> Result<&mut Object, String> {
+        obj.0
+            .checked_sub(1)
+            .and_then(|slot| self.slots.get_mut(slot))
+            .and_then(|object| object.as_mut())
+            .ok_or_else(|| Self::invalid(obj))
+    }
+
+    fn invalid(obj: ObjRef) -> String {
+        if obj.is_null() {
+            "Null reference".to_string()
+        } else {
+            format!("Invalid reference: {}", obj.0)
+        }
+    }
+
+    pub fn stats(&self) -> GcStats {
+        self.stats
+    }
+
+    /// Whether enough objects have accumulated to make a collection worthwhile.
+    pub fn should_collect(&self) -> bool {
+        self.stats.live_objects >= self.threshold
+    }
+
+    /// Frees every object not reachable from `roots` and returns how many were freed.
+    pub fn collect<'a>(&mut self, roots: impl Iterator<Item = &'a Value>) -> usize {
+        let mut marked = vec![false; self.slots.len()];
+        let mut pending: Vec<ObjRef> = roots.filter_map(Self::reference).collect();
+        while let Some(obj) = pending.pop() {
+            let slot = obj.0 - 1;
+            if marked[slot] {
+                continue;
+            }
+            marked[slot] = true;
+            if let Some(object) = &self.slots[slot] {
+                pending.extend(object.values().filter_map(Self::reference));
+            }
+        }
+
+        let mut freed = 0;
+        for (slot, object) in self.slots.iter_mut().enumerate() {
+            if object.is_some() && !marked[slot] {
+                *object = None;
+                self.free.push(slot);
+                freed += 1;
+            }
+        }
+
+        self.stats.collections += 1;
+        self.stats.freed += freed as u64;
+        self.stats.live_objects -= freed;
+        self.threshold = INITIAL_THRESHOLD.max(self.stats.live_objects * 2);
+        freed
+    }
+
+    fn reference(value: &Value) -> Option<ObjRef> {
+        match value {
+            Value::Ref(obj) if !obj.is_null() => Some(*obj),
+            _ => None,
+        }
+    }
+}
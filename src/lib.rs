@@ -1,7 +1,12 @@
 pub mod opcode;
 pub mod program;
 pub mod types;
+pub mod simd;
+pub mod gc;
 pub mod vm;
+pub mod capabilities;
+pub mod system;
+pub mod console;
 pub mod examples;
 pub mod asm;
 pub mod bytecode;
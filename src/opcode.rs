@@ -1,4 +1,4 @@
-use crate::types::{DataType, Operand};
+use crate::types::{Address, DataType, Endian, Operand};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum OpCode {
@@ -33,18 +33,68 @@ pub enum OpCode {
     },
     Load {
         dest: String,
-        ptr: String,
+        addr: Address,
         dtype: DataType,
+        endian: Endian,
     },
     Store {
-        ptr: String,
-        source: String,
+        addr: Address,
+        source: Operand,
         dtype: DataType,
+        endian: Endian,
     },
     GetAddr {
         dest: String,
         var: String,
     },
+    SizeOf {
+        dest: String,
+        target: Operand,
+    },
+    AllocArray {
+        dest: String,
+        elem: Operand,
+        count: Operand,
+    },
+    // `count` consecutive elements between memory at `addr` (in `endian`
+    // order) and the native-order buffer `buf`
+    LoadVec {
+        buf: String,
+        addr: Address,
+        count: Operand,
+        dtype: DataType,
+        endian: Endian,
+    },
+    StoreVec {
+        addr: Address,
+        buf: String,
+        count: Operand,
+        dtype: DataType,
+        endian: Endian,
+    },
+
+    // bulk memory operations
+    MemCopy {
+        dest: String,
+        src: String,
+        len: Operand,
+    },
+    MemMove {
+        dest: String,
+        src: String,
+        len: Operand,
+    },
+    MemSet {
+        dest: String,
+        value: Operand,
+        len: Operand,
+    },
+    MemCmp {
+        result: String,
+        a: String,
+        b: String,
+        len: Operand,
+    },
 
     // arithmetic operations
     Add {
@@ -107,6 +157,45 @@ pub enum OpCode {
         left: Operand,
         right: Operand,
     },
+    Popcnt {
+        dest: String,
+        source: Operand,
+    },
+    Clz {
+        dest: String,
+        source: Operand,
+    },
+    Ctz {
+        dest: String,
+        source: Operand,
+    },
+    Bswap {
+        dest: String,
+        source: Operand,
+    },
+    Rotl {
+        dest: String,
+        left: Operand,
+        right: Operand,
+    },
+    Rotr {
+        dest: String,
+        left: Operand,
+        right: Operand,
+    },
+    BitExtract {
+        dest: String,
+        source: Operand,
+        start: Operand,
+        len: Operand,
+    },
+    BitInsert {
+        dest: String,
+        source: Operand,
+        field: Operand,
+        start: Operand,
+        len: Operand,
+    },
 
     // comparison operations
     Eq {
@@ -139,6 +228,12 @@ pub enum OpCode {
         left: Operand,
         right: Operand,
     },
+    Select {
+        dest: String,
+        cond: Operand,
+        a: Operand,
+        b: Operand,
+    },
 
     // control flow
     Label {
@@ -155,6 +250,53 @@ pub enum OpCode {
         var: String,
         label: String,
     },
+    // jumps to `targets[value]`, or `default` when out of range
+    Switch {
+        value: Operand,
+        default: String,
+        targets: Vec<String>,
+    },
+    // fused compare-and-branch
+    Jeq {
+        left: Operand,
+        right: Operand,
+        label: String,
+    },
+    Jne {
+        left: Operand,
+        right: Operand,
+        label: String,
+    },
+    Jlt {
+        left: Operand,
+        right: Operand,
+        label: String,
+    },
+    Jle {
+        left: Operand,
+        right: Operand,
+        label: String,
+    },
+    Jgt {
+        left: Operand,
+        right: Operand,
+        label: String,
+    },
+    Jge {
+        left: Operand,
+        right: Operand,
+        label: String,
+    },
+
+    // traps
+    Assert {
+        cond: Operand,
+        message: Option<String>,
+    },
+    Trap {
+        code: Operand,
+    },
+    Unreachable,
 
     // function operations
     FuncBegin {
@@ -220,6 +362,235 @@ pub enum OpCode {
         dest: String,
         source: Operand,
     },
+    Floor {
+        dest: String,
+        source: Operand,
+    },
+    Ceil {
+        dest: String,
+        source: Operand,
+    },
+    Round {
+        dest: String,
+        source: Operand,
+    },
+    Trunc {
+        dest: String,
+        source: Operand,
+    },
+    Exp {
+        dest: String,
+        source: Operand,
+    },
+    Ln {
+        dest: String,
+        source: Operand,
+    },
+    Log2 {
+        dest: String,
+        source: Operand,
+    },
+    Log10 {
+        dest: String,
+        source: Operand,
+    },
+    Asin {
+        dest: String,
+        source: Operand,
+    },
+    Acos {
+        dest: String,
+        source: Operand,
+    },
+    Atan {
+        dest: String,
+        source: Operand,
+    },
+    Atan2 {
+        dest: String,
+        y: Operand,
+        x: Operand,
+    },
+    Hypot {
+        dest: String,
+        x: Operand,
+        y: Operand,
+    },
+    CopySign {
+        dest: String,
+        magnitude: Operand,
+        sign: Operand,
+    },
+    Fma {
+        dest: String,
+        a: Operand,
+        b: Operand,
+        c: Operand,
+    },
+    IsNan {
+        dest: String,
+        source: Operand,
+    },
+    IsInf {
+        dest: String,
+        source: Operand,
+    },
+    IsFinite {
+        dest: String,
+        source: Operand,
+    },
+
+    // simd vectors; arithmetic, min/max and bitwise ops also work lane-wise
+    VecSplat {
+        dest: String,
+        value: Operand,
+        dtype: DataType,
+    },
+    VecExtract {
+        dest: String,
+        vector: Operand,
+        lane: Operand,
+    },
+    VecInsert {
+        dest: String,
+        vector: Operand,
+        lane: Operand,
+        value: Operand,
+    },
+    // picks lanes from `a` followed by `b`, one index per result lane
+    VecShuffle {
+        dest: String,
+        a: Operand,
+        b: Operand,
+        lanes: Vec<u8>,
+    },
+    VecEq {
+        dest: String,
+        left: Operand,
+        right: Operand,
+    },
+    VecNe {
+        dest: String,
+        left: Operand,
+        right: Operand,
+    },
+    VecLt {
+        dest: String,
+        left: Operand,
+        right: Operand,
+    },
+    VecLe {
+        dest: String,
+        left: Operand,
+        right: Operand,
+    },
+    VecGt {
+        dest: String,
+        left: Operand,
+        right: Operand,
+    },
+    VecGe {
+        dest: String,
+        left: Operand,
+        right: Operand,
+    },
+    VecSelect {
+        dest: String,
+        mask: Operand,
+        a: Operand,
+        b: Operand,
+    },
+
+    // strings; lengths and indices count characters
+    StrLen {
+        dest: String,
+        source: Operand,
+    },
+    StrConcat {
+        dest: String,
+        left: Operand,
+        right: Operand,
+    },
+    // `len` characters starting at character `start`
+    StrSub {
+        dest: String,
+        source: Operand,
+        start: Operand,
+        len: Operand,
+    },
+    // character index of the first match, or -1
+    StrFind {
+        dest: String,
+        haystack: Operand,
+        needle: Operand,
+    },
+    StrCmp {
+        dest: String,
+        left: Operand,
+        right: Operand,
+    },
+    StrToInt {
+        dest: String,
+        source: Operand,
+    },
+    IntToStr {
+        dest: String,
+        source: Operand,
+    },
+    FloatToStr {
+        dest: String,
+        source: Operand,
+    },
+    PtrToStr {
+        dest: String,
+        ptr: Operand,
+    },
+    StrToPtr {
+        dest: String,
+        source: Operand,
+    },
+
+    // garbage-collected arrays and maps
+    ArrayNew {
+        dest: String,
+    },
+    ArrayPush {
+        array: Operand,
+        value: Operand,
+    },
+    ArrayPop {
+        dest: String,
+        array: Operand,
+    },
+    MapNew {
+        dest: String,
+    },
+    MapHas {
+        dest: String,
+        map: Operand,
+        key: Operand,
+    },
+    // key at `index` in insertion order, for iterating a map
+    MapKey {
+        dest: String,
+        map: Operand,
+        index: Operand,
+    },
+    ObjLen {
+        dest: String,
+        object: Operand,
+    },
+    // arrays are indexed by integer, maps by string key
+    ObjGet {
+        dest: String,
+        object: Operand,
+        key: Operand,
+    },
+    ObjSet {
+        object: Operand,
+        key: Operand,
+        value: Operand,
+    },
 
     // system operations
     Print {
@@ -227,6 +598,129 @@ pub enum OpCode {
     },
     Input {
         dest: String,
+        dtype: DataType,
+        // Receives 0 on success, 1 at end of input, 2 on a parse failure
+        status: Option<String>,
+        prompt: bool,
+    },
+    PrintStr {
+        ptr: String,
+    },
+    PrintChar {
+        value: Operand,
+    },
+    EPrintStr {
+        ptr: String,
+    },
+    EPrintChar {
+        value: Operand,
+    },
+    ReadLine {
+        dest: String,
+        buf: String,
+        cap: Operand,
+    },
+    Printf {
+        fmt: String,
+        args: Vec<Operand>,
+    },
+
+    // sandboxed file i/o
+    FileOpen {
+        dest: String,
+        path: String,
+        mode: Operand,
+    },
+    FileRead {
+        dest: String,
+        handle: Operand,
+        buf: String,
+        len: Operand,
+    },
+    FileWrite {
+        dest: String,
+        handle: Operand,
+        buf: String,
+        len: Operand,
+    },
+    FileSeek {
+        dest: String,
+        handle: Operand,
+        offset: Operand,
+        whence: Operand,
+    },
+    FileClose {
+        dest: String,
+        handle: Operand,
+    },
+
+    // system services
+    Clock {
+        dest: String,
+    },
+    Sleep {
+        millis: Operand,
+    },
+    Srand {
+        seed: Operand,
+    },
+    Rand {
+        dest: String,
+        bound: Option<Operand>,
+    },
+    GetEnv {
+        dest: String,
+        name: String,
+    },
+
+    // tcp sockets
+    TcpConnect {
+        dest: String,
+        addr: String,
+    },
+    TcpListen {
+        dest: String,
+        addr: String,
+    },
+    TcpAccept {
+        dest: String,
+        handle: Operand,
+    },
+    TcpSend {
+        dest: String,
+        handle: Operand,
+        buf: String,
+        len: Operand,
+    },
+    TcpRecv {
+        dest: String,
+        handle: Operand,
+        buf: String,
+        len: Operand,
+    },
+    TcpClose {
+        dest: String,
+        handle: Operand,
+    },
+    TcpNonblock {
+        dest: String,
+        handle: Operand,
+        enabled: Operand,
+    },
+
+    // console
+    ReadKey {
+        dest: String,
+        wait: bool,
+    },
+    SetCursor {
+        row: Operand,
+        col: Operand,
+    },
+    ClearScreen,
+    SetColor {
+        fg: Operand,
+        bg: Operand,
     },
     Exit {
         code: Operand,
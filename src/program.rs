@@ -24,15 +24,71 @@ impl Variable {
         }
     }
 
-    fn size_of(dtype: DataType) -> usize {
+    pub fn size_of(dtype: DataType) -> usize {
         match dtype {
-            DataType::I8 | DataType::U8 => 1,
+            DataType::I8 | DataType::U8 | DataType::Bool => 1,
             DataType::I16 | DataType::U16 => 2,
-            DataType::I32 | DataType::U32 | DataType::F32 => 4,
+            DataType::I32 | DataType::U32 | DataType::F32 | DataType::Char => 4,
             DataType::I64 | DataType::U64 | DataType::F64 | DataType::Ptr => 8,
-            DataType::Void => 0,
+            DataType::I128 | DataType::U128 | DataType::Vector(_) => 16,
+            // Strings and managed objects live outside the byte heap
+            DataType::Void | DataType::Str | DataType::Ref => 0,
         }
     }
+
+    // Natural alignment: every scalar is aligned to its own size
+    pub fn align_of(dtype: DataType) -> usize {
+        Self::size_of(dtype).max(1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructField {
+    pub name: String,
+    pub dtype: DataType,
+    pub count: usize,   // 1 for scalar fields, N for `name: type[N]`
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructLayout {
+    pub name: String,
+    pub fields: Vec<StructField>,
+    pub size: usize,
+    pub align: usize,
+}
+
+impl StructLayout {
+    // Lay out fields in declaration order, padding each to its natural alignment
+    pub fn new(name: String, fields: Vec<(String, DataType, usize)>) -> Self {
+        let mut offset: usize = 0;
+        let mut align: usize = 1;
+        let mut laid_out = Vec::with_capacity(fields.len());
+
+        for (field_name, dtype, count) in fields {
+            let field_align = Variable::align_of(dtype);
+            offset = offset.next_multiple_of(field_align);
+            align = align.max(field_align);
+            laid_out.push(StructField {
+                name: field_name,
+                dtype,
+                count,
+                offset,
+            });
+            offset += Variable::size_of(dtype) * count;
+        }
+
+        Self {
+            name,
+            fields: laid_out,
+            size: offset.next_multiple_of(align),
+            align,
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&StructField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +99,9 @@ pub struct Function {
     pub locals: Vec<Variable>,
     pub start_ip: usize,
     pub end_ip: usize,
+    // Declared with a parameter list: calls are checked against the signature
+    // and arguments bind to `parameters` instead of going through `pop_arg`
+    pub typed: bool,
 }
 
 impl Function {
@@ -54,6 +113,7 @@ impl Function {
             locals: Vec::new(),
             start_ip: 0,
             end_ip: 0,
+            typed: false,
         }
     }
 }
@@ -69,6 +129,7 @@ pub struct SourceLocation {
 pub struct SourceMap {
     pub file: PathBuf,
     pub instruction_locations: HashMap<usize, SourceLocation>,
+    pub field_refs: HashMap<usize, Vec<String>>,  // Struct constants (`Point.y`) used by each instruction
 }
 
 #[derive(Debug, Clone)]
@@ -78,6 +139,15 @@ pub struct StringLiteral {
     pub global_name: String,  // Name of the global variable that points to this string
 }
 
+#[derive(Debug, Clone)]
+pub struct DataBlock {
+    pub id: usize,
+    pub global_name: String,  // Name of the global variable that points to this block
+    pub dtype: DataType,
+    pub count: usize,
+    pub bytes: Vec<u8>,       // Initial memory image (count * element size bytes)
+}
+
 #[derive(Debug, Clone)]
 pub struct Program {
     pub instructions: Vec<OpCode>,
@@ -86,6 +156,8 @@ pub struct Program {
     pub labels: HashMap<String, usize>,
     pub source_map: Option<SourceMap>,
     pub strings: Vec<StringLiteral>,
+    pub data_blocks: Vec<DataBlock>,
+    pub structs: Vec<StructLayout>,
 }
 
 impl Program {
@@ -97,6 +169,8 @@ impl Program {
             labels: HashMap::new(),
             source_map: None,
             strings: Vec::new(),
+            data_blocks: Vec::new(),
+            structs: Vec::new(),
         }
     }
 
@@ -110,6 +184,18 @@ impl Program {
         id
     }
 
+    pub fn add_data_block(&mut self, global_name: String, dtype: DataType, count: usize, bytes: Vec<u8>) -> usize {
+        let id = self.data_blocks.len();
+        self.data_blocks.push(DataBlock {
+            id,
+            global_name,
+            dtype,
+            count,
+            bytes,
+        });
+        id
+    }
+
     pub fn emit(&mut self, opcode: OpCode) -> usize {
         let ip = self.instructions.len();
         if let OpCode::Label { ref name } = opcode {
@@ -0,0 +1,387 @@
+use crate::types::{DataType, Value};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Shape of a 128-bit vector: lane type and lane count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorType {
+    V16U8,
+    V8I16,
+    V4I32,
+    V2I64,
+    V4F32,
+    V2F64,
+}
+
+impl VectorType {
+    pub fn name(self) -> &'static str {
+        match self {
+            VectorType::V16U8 => "v16u8",
+            VectorType::V8I16 => "v8i16",
+            VectorType::V4I32 => "v4i32",
+            VectorType::V2I64 => "v2i64",
+            VectorType::V4F32 => "v4f32",
+            VectorType::V2F64 => "v2f64",
+        }
+    }
+
+    pub fn lane_type(self) -> DataType {
+        match self {
+            VectorType::V16U8 => DataType::U8,
+            VectorType::V8I16 => DataType::I16,
+            VectorType::V4I32 => DataType::I32,
+            VectorType::V2I64 => DataType::I64,
+            VectorType::V4F32 => DataType::F32,
+            VectorType::V2F64 => DataType::F64,
+        }
+    }
+
+    pub fn lane_count(self) -> usize {
+        16 / self.lane_size()
+    }
+
+    pub fn lane_size(self) -> usize {
+        crate::program::Variable::size_of(self.lane_type())
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, VectorType::V4F32 | VectorType::V2F64)
+    }
+
+    // Integer shape with the same lane width, used for comparison masks
+    fn mask_type(self) -> VectorType {
+        match self {
+            VectorType::V4F32 => VectorType::V4I32,
+            VectorType::V2F64 => VectorType::V2I64,
+            other => other,
+        }
+    }
+}
+
+/// Lane comparison used by `veq`..`vge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    // NaN lanes are unordered: only `Ne` holds for them
+    fn test<T: PartialOrd>(self, a: T, b: T) -> bool {
+        match (self, a.partial_cmp(&b)) {
+            (CompareOp::Ne, ordering) => ordering != Some(Ordering::Equal),
+            (_, None) => false,
+            (CompareOp::Eq, Some(o)) => o == Ordering::Equal,
+            (CompareOp::Lt, Some(o)) => o == Ordering::Less,
+            (CompareOp::Le, Some(o)) => o != Ordering::Greater,
+            (CompareOp::Gt, Some(o)) => o == Ordering::Greater,
+            (CompareOp::Ge, Some(o)) => o != Ordering::Less,
+        }
+    }
+}
+
+/// A 128-bit vector value. Operations run lane by lane in portable scalar
+/// code; integer lanes wrap on overflow, as in hardware SIMD.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Vector {
+    V16U8([u8; 16]),
+    V8I16([i16; 8]),
+    V4I32([i32; 4]),
+    V2I64([i64; 2]),
+    V4F32([f32; 4]),
+    V2F64([f64; 2]),
+}
+
+impl fmt::Display for Vector {
+    // e.g. `v4f32 [1.0, 2.0, 3.0, 4.0]`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.shape().name())?;
+        match self {
+            Vector::V16U8(l) => write!(f, "{:?}", l),
+            Vector::V8I16(l) => write!(f, "{:?}", l),
+            Vector::V4I32(l) => write!(f, "{:?}", l),
+            Vector::V2I64(l) => write!(f, "{:?}", l),
+            Vector::V4F32(l) => write!(f, "{:?}", l),
+            Vector::V2F64(l) => write!(f, "{:?}", l),
+        }
+    }
+}
+
+impl Vector {
+    pub fn zero(shape: VectorType) -> Vector {
+        match shape {
+            VectorType::V16U8 => Vector::V16U8([0; 16]),
+            VectorType::V8I16 => Vector::V8I16([0; 8]),
+            VectorType::V4I32 => Vector::V4I32([0; 4]),
+            VectorType::V2I64 => Vector::V2I64([0; 2]),
+            VectorType::V4F32 => Vector::V4F32([0.0; 4]),
+            VectorType::V2F64 => Vector::V2F64([0.0; 2]),
+        }
+    }
+
+    pub fn shape(&self) -> VectorType {
+        match self {
+            Vector::V16U8(_) => VectorType::V16U8,
+            Vector::V8I16(_) => VectorType::V8I16,
+            Vector::V4I32(_) => VectorType::V4I32,
+            Vector::V2I64(_) => VectorType::V2I64,
+            Vector::V4F32(_) => VectorType::V4F32,
+            Vector::V2F64(_) => VectorType::V2F64,
+        }
+    }
+
+    /// Every lane set to `value`, converted to the lane type.
+    pub fn splat(shape: VectorType, value: &Value) -> Result<Vector, String> {
+        let lane = value.cast(shape.lane_type())?;
+        let mut vector = Vector::zero(shape);
+        for index in 0..shape.lane_count() {
+            vector = vector.with_lane(index, &lane)?;
+        }
+        Ok(vector)
+    }
+
+    pub fn lane(&self, index: usize) -> Result<Value, String> {
+        self.check_lane(index)?;
+        Ok(match self {
+            Vector::V16U8(l) => Value::U8(l[index]),
+            Vector::V8I16(l) => Value::I16(l[index]),
+            Vector::V4I32(l) => Value::I32(l[index]),
+            Vector::V2I64(l) => Value::I64(l[index]),
+            Vector::V4F32(l) => Value::F32(l[index]),
+            Vector::V2F64(l) => Value::F64(l[index]),
+        })
+    }
+
+    /// A copy with lane `index` replaced by `value`, converted to the lane type.
+    pub fn with_lane(&self, index: usize, value: &Value) -> Result<Vector, String> {
+        self.check_lane(index)?;
+        let mut vector = *self;
+        match (&mut vector, value.cast(self.shape().lane_type())?) {
+            (Vector::V16U8(l), Value::U8(v)) => l[index] = v,
+            (Vector::V8I16(l), Value::I16(v)) => l[index] = v,
+            (Vector::V4I32(l), Value::I32(v)) => l[index] = v,
+            (Vector::V2I64(l), Value::I64(v)) => l[index] = v,
+            (Vector::V4F32(l), Value::F32(v)) => l[index] = v,
+            (Vector::V2F64(l), Value::F64(v)) => l[index] = v,
+            _ => unreachable!(),
+        }
+        Ok(vector)
+    }
+
+    fn check_lane(&self, index: usize) -> Result<(), String> {
+        let shape = self.shape();
+        if index >= shape.lane_count() {
+            return Err(format!("Lane {} out of range for {}", index, shape.name()));
+        }
+        Ok(())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.to_le_bytes().iter().all(|b| *b == 0)
+    }
+
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        match self {
+            Vector::V16U8(l) => l.to_vec(),
+            Vector::V8I16(l) => l.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            Vector::V4I32(l) => l.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            Vector::V2I64(l) => l.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            Vector::V4F32(l) => l.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            Vector::V2F64(l) => l.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        }
+    }
+
+    pub fn from_le_bytes(bytes: &[u8], shape: VectorType) -> Result<Vector, String> {
+        if bytes.len() < 16 {
+            return Err(format!("Insufficient bytes for {}", shape.name()));
+        }
+        let size = shape.lane_size();
+        let mut vector = Vector::zero(shape);
+        for (index, chunk) in bytes[..16].chunks_exact(size).enumerate() {
+            let lane = Value::from_le_bytes(chunk, shape.lane_type())?;
+            vector = vector.with_lane(index, &lane)?;
+        }
+        Ok(vector)
+    }
+
+    // Integer lanes widened without loss, so wrapping results are a plain truncation
+    fn int_lanes(&self) -> Option<Vec<i128>> {
+        match self {
+            Vector::V16U8(l) => Some(l.iter().map(|v| *v as i128).collect()),
+            Vector::V8I16(l) => Some(l.iter().map(|v| *v as i128).collect()),
+            Vector::V4I32(l) => Some(l.iter().map(|v| *v as i128).collect()),
+            Vector::V2I64(l) => Some(l.iter().map(|v| *v as i128).collect()),
+            Vector::V4F32(_) | Vector::V2F64(_) => None,
+        }
+    }
+
+    // f32 lanes are exact in f64, and + - * / rounded back to f32 match native f32 results
+    fn float_lanes(&self) -> Option<Vec<f64>> {
+        match self {
+            Vector::V4F32(l) => Some(l.iter().map(|v| *v as f64).collect()),
+            Vector::V2F64(l) => Some(l.to_vec()),
+            _ => None,
+        }
+    }
+
+    fn from_int_lanes(shape: VectorType, lanes: &[i128]) -> Vector {
+        let mut vector = Vector::zero(shape);
+        match &mut vector {
+            Vector::V16U8(l) => l.iter_mut().zip(lanes).for_each(|(d, s)| *d = *s as u8),
+            Vector::V8I16(l) => l.iter_mut().zip(lanes).for_each(|(d, s)| *d = *s as i16),
+            Vector::V4I32(l) => l.iter_mut().zip(lanes).for_each(|(d, s)| *d = *s as i32),
+            Vector::V2I64(l) => l.iter_mut().zip(lanes).for_each(|(d, s)| *d = *s as i64),
+            Vector::V4F32(_) | Vector::V2F64(_) => unreachable!(),
+        }
+        vector
+    }
+
+    fn from_float_lanes(shape: VectorType, lanes: &[f64]) -> Vector {
+        let mut vector = Vector::zero(shape);
+        match &mut vector {
+            Vector::V4F32(l) => l.iter_mut().zip(lanes).for_each(|(d, s)| *d = *s as f32),
+            Vector::V2F64(l) => l.copy_from_slice(lanes),
+            _ => unreachable!(),
+        }
+        vector
+    }
+
+    // Applies `int_op` or `float_op` to each pair of lanes of two vectors of the same shape
+    fn zip(
+        &self,
+        other: &Vector,
+        name: &str,
+        int_op: impl Fn(i128, i128) -> Result<i128, String>,
+        float_op: impl Fn(f64, f64) -> f64,
+    ) -> Result<Vector, String> {
+        let shape = self.check_shape(other, name)?;
+        if let (Some(a), Some(b)) = (self.int_lanes(), other.int_lanes()) {
+            let lanes = a.iter().zip(&b).map(|(x, y)| int_op(*x, *y)).collect::<Result<Vec<_>, _>>()?;
+            return Ok(Vector::from_int_lanes(shape, &lanes));
+        }
+        let (a, b) = (self.float_lanes().unwrap(), other.float_lanes().unwrap());
+        let lanes: Vec<f64> = a.iter().zip(&b).map(|(x, y)| float_op(*x, *y)).collect();
+        Ok(Vector::from_float_lanes(shape, &lanes))
+    }
+
+    fn check_shape(&self, other: &Vector, name: &str) -> Result<VectorType, String> {
+        if self.shape() != other.shape() {
+            return Err(format!(
+                "Vector {} requires matching shapes, got {} and {}",
+                name,
+                self.shape().name(),
+                other.shape().name()
+            ));
+        }
+        Ok(self.shape())
+    }
+
+    fn int_only(&self, name: &str) -> Result<(), String> {
+        if self.shape().is_float() {
+            return Err(format!("Vector {} requires integer lanes, got {}", name, self.shape().name()));
+        }
+        Ok(())
+    }
+
+    pub fn add(&self, other: &Vector) -> Result<Vector, String> {
+        self.zip(other, "add", |a, b| Ok(a + b), |a, b| a + b)
+    }
+
+    pub fn sub(&self, other: &Vector) -> Result<Vector, String> {
+        self.zip(other, "sub", |a, b| Ok(a - b), |a, b| a - b)
+    }
+
+    pub fn mul(&self, other: &Vector) -> Result<Vector, String> {
+        self.zip(other, "mul", |a, b| Ok(a.wrapping_mul(b)), |a, b| a * b)
+    }
+
+    pub fn div(&self, other: &Vector) -> Result<Vector, String> {
+        let int_div = |a: i128, b: i128| match b {
+            0 => Err("Division by zero in vector lane".to_string()),
+            _ => Ok(a / b),
+        };
+        self.zip(other, "div", int_div, |a, b| a / b)
+    }
+
+    pub fn min(&self, other: &Vector) -> Result<Vector, String> {
+        self.zip(other, "min", |a, b| Ok(a.min(b)), f64::min)
+    }
+
+    pub fn max(&self, other: &Vector) -> Result<Vector, String> {
+        self.zip(other, "max", |a, b| Ok(a.max(b)), f64::max)
+    }
+
+    pub fn bitwise_and(&self, other: &Vector) -> Result<Vector, String> {
+        self.int_only("and")?;
+        self.zip(other, "and", |a, b| Ok(a & b), |_, _| unreachable!())
+    }
+
+    pub fn bitwise_or(&self, other: &Vector) -> Result<Vector, String> {
+        self.int_only("or")?;
+        self.zip(other, "or", |a, b| Ok(a | b), |_, _| unreachable!())
+    }
+
+    pub fn bitwise_xor(&self, other: &Vector) -> Result<Vector, String> {
+        self.int_only("xor")?;
+        self.zip(other, "xor", |a, b| Ok(a ^ b), |_, _| unreachable!())
+    }
+
+    pub fn bitwise_not(&self) -> Result<Vector, String> {
+        self.int_only("not")?;
+        let lanes: Vec<i128> = self.int_lanes().unwrap().iter().map(|v| !v).collect();
+        Ok(Vector::from_int_lanes(self.shape(), &lanes))
+    }
+
+    /// Lane mask: all bits set where `op` holds, zero elsewhere. Float
+    /// vectors produce the integer shape with the same lane width.
+    pub fn compare(&self, other: &Vector, op: CompareOp) -> Result<Vector, String> {
+        let shape = self.check_shape(other, "comparison")?;
+        let mask: Vec<i128> = match (self.int_lanes(), other.int_lanes()) {
+            (Some(a), Some(b)) => a.iter().zip(&b).map(|(x, y)| op.test(x, y)).map(|t| -(t as i128)).collect(),
+            _ => {
+                let (a, b) = (self.float_lanes().unwrap(), other.float_lanes().unwrap());
+                a.iter().zip(&b).map(|(x, y)| op.test(x, y)).map(|t| -(t as i128)).collect()
+            }
+        };
+        Ok(Vector::from_int_lanes(shape.mask_type(), &mask))
+    }
+
+    /// Lanes of `a` where the mask lane is non-zero, otherwise lanes of `b`.
+    pub fn select(mask: &Vector, a: &Vector, b: &Vector) -> Result<Vector, String> {
+        let shape = a.check_shape(b, "select")?;
+        let mask = mask
+            .int_lanes()
+            .filter(|lanes| lanes.len() == shape.lane_count())
+            .ok_or_else(|| format!("Select mask must be an integer vector with {} lanes", shape.lane_count()))?;
+        let mut result = *b;
+        for (index, lane) in mask.iter().enumerate() {
+            if *lane != 0 {
+                result = result.with_lane(index, &a.lane(index)?)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Lanes picked by index from `a` followed by `b`; `indices` has one entry per result lane.
+    pub fn shuffle(a: &Vector, b: &Vector, indices: &[u8]) -> Result<Vector, String> {
+        let shape = a.check_shape(b, "shuffle")?;
+        let count = shape.lane_count();
+        if indices.len() != count {
+            return Err(format!("{} shuffle needs {} lane indices, got {}", shape.name(), count, indices.len()));
+        }
+        let mut result = Vector::zero(shape);
+        for (index, source) in indices.iter().map(|i| *i as usize).enumerate() {
+            let lane = match source {
+                s if s < count => a.lane(s)?,
+                s if s < 2 * count => b.lane(s - count)?,
+                s => return Err(format!("Shuffle index {} out of range for {}", s, shape.name())),
+            };
+            result = result.with_lane(index, &lane)?;
+        }
+        Ok(result)
+    }
+}
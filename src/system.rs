@@ -0,0 +1,85 @@
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+enum Clock {
+    Real(Instant),
+    // Virtual nanoseconds, advanced only by `sleep`
+    Virtual(u64),
+}
+
+/// Clock and random number source behind the `clock`, `sleep`, `srand`
+/// and `rand` instructions.
+#[derive(Debug, Clone)]
+pub struct SystemServices {
+    clock: Clock,
+    rng_state: u64,
+}
+
+impl SystemServices {
+    /// Real monotonic clock and a time-based RNG seed.
+    pub fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Self {
+            clock: Clock::Real(Instant::now()),
+            rng_state: seed,
+        }
+    }
+
+    /// Reproducible runs: the clock starts at `start_ns` and only moves when
+    /// the program sleeps (without actually blocking), and the RNG uses `seed`.
+    pub fn deterministic(start_ns: u64, seed: u64) -> Self {
+        Self {
+            clock: Clock::Virtual(start_ns),
+            rng_state: seed,
+        }
+    }
+
+    pub fn now_ns(&self) -> u64 {
+        match &self.clock {
+            Clock::Real(start) => start.elapsed().as_nanos() as u64,
+            Clock::Virtual(now) => *now,
+        }
+    }
+
+    pub fn sleep(&mut self, duration: Duration) {
+        match &mut self.clock {
+            Clock::Real(_) => thread::sleep(duration),
+            Clock::Virtual(now) => *now = now.saturating_add(duration.as_nanos() as u64),
+        }
+    }
+
+    pub fn seed(&mut self, seed: u64) {
+        self.rng_state = seed;
+    }
+
+    // splitmix64
+    pub fn next_u64(&mut self) -> u64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..bound` (`bound` must be non-zero).
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        // Reject the short final cycle so every value is equally likely
+        let zone = u64::MAX - (u64::MAX % bound);
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+impl Default for SystemServices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
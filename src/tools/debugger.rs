@@ -5,6 +5,9 @@ use crate::types::Value;
 use crate::vm::{CallFrame, VM};
 use std::collections::HashMap;
 
+// Elements shown when printing a pointer to a typed allocation
+const ARRAY_DISPLAY_LIMIT: usize = 16;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DebugCommand {
     Step,
@@ -190,17 +193,35 @@ impl Debugger {
     fn print_variable(&self, vm: &VM, var_name: &str) -> Result<String, String> {
         let frame = vm.get_current_frame();
 
-        // Try locals first
-        if let Some(value) = frame.locals.get(var_name) {
-            return Ok(format!("{}: {:?}", var_name, value));
-        }
-
-        // Try globals
-        if let Some(value) = vm.get_globals().get(var_name) {
-            return Ok(format!("{}: {:?}", var_name, value));
+        // Try locals first, then globals
+        let value = frame
+            .locals
+            .get(var_name)
+            .or_else(|| vm.get_globals().get(var_name))
+            .ok_or_else(|| format!("Variable '{}' not found", var_name))?;
+
+        let mut output = format!("{}: {:?}", var_name, value);
+
+        // Pointers to typed allocations are shown as arrays
+        if let Value::Ptr(addr) = value
+            && let Some((dtype, elements)) = vm.get_array_allocation(*addr)
+        {
+            let shown: Vec<String> = elements
+                .iter()
+                .take(ARRAY_DISPLAY_LIMIT)
+                .map(|v| format!("{:?}", v))
+                .collect();
+            let more = if elements.len() > ARRAY_DISPLAY_LIMIT { ", ..." } else { "" };
+            output.push_str(&format!(
+                " -> {:?}[{}] = [{}{}]",
+                dtype,
+                elements.len(),
+                shown.join(", "),
+                more
+            ));
         }
 
-        Err(format!("Variable '{}' not found", var_name))
+        Ok(output)
     }
 
     fn print_locals(&self, vm: &VM) -> Result<String, String> {
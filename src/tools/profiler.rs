@@ -66,7 +66,15 @@ impl ProfileData {
             OpCode::Free { .. } => "Free",
             OpCode::Load { .. } => "Load",
             OpCode::Store { .. } => "Store",
+            OpCode::LoadVec { .. } => "LoadVec",
+            OpCode::StoreVec { .. } => "StoreVec",
             OpCode::GetAddr { .. } => "GetAddr",
+            OpCode::MemCopy { .. } => "MemCopy",
+            OpCode::MemMove { .. } => "MemMove",
+            OpCode::MemSet { .. } => "MemSet",
+            OpCode::MemCmp { .. } => "MemCmp",
+            OpCode::SizeOf { .. } => "SizeOf",
+            OpCode::AllocArray { .. } => "AllocArray",
             OpCode::Add { .. } => "Add",
             OpCode::Sub { .. } => "Sub",
             OpCode::Mul { .. } => "Mul",
@@ -79,16 +87,35 @@ impl ProfileData {
             OpCode::Not { .. } => "Not",
             OpCode::Shl { .. } => "Shl",
             OpCode::Shr { .. } => "Shr",
+            OpCode::Popcnt { .. } => "Popcnt",
+            OpCode::Clz { .. } => "Clz",
+            OpCode::Ctz { .. } => "Ctz",
+            OpCode::Bswap { .. } => "Bswap",
+            OpCode::Rotl { .. } => "Rotl",
+            OpCode::Rotr { .. } => "Rotr",
+            OpCode::BitExtract { .. } => "BitExtract",
+            OpCode::BitInsert { .. } => "BitInsert",
             OpCode::Eq { .. } => "Eq",
             OpCode::Ne { .. } => "Ne",
             OpCode::Lt { .. } => "Lt",
             OpCode::Le { .. } => "Le",
             OpCode::Gt { .. } => "Gt",
             OpCode::Ge { .. } => "Ge",
+            OpCode::Select { .. } => "Select",
             OpCode::Label { .. } => "Label",
             OpCode::Jmp { .. } => "Jmp",
+            OpCode::Switch { .. } => "Switch",
             OpCode::Jz { .. } => "Jz",
+            OpCode::Assert { .. } => "Assert",
+            OpCode::Trap { .. } => "Trap",
+            OpCode::Unreachable => "Unreachable",
             OpCode::Jnz { .. } => "Jnz",
+            OpCode::Jeq { .. } => "Jeq",
+            OpCode::Jne { .. } => "Jne",
+            OpCode::Jlt { .. } => "Jlt",
+            OpCode::Jle { .. } => "Jle",
+            OpCode::Jgt { .. } => "Jgt",
+            OpCode::Jge { .. } => "Jge",
             OpCode::FuncBegin { .. } => "FuncBegin",
             OpCode::FuncEnd => "FuncEnd",
             OpCode::Call { .. } => "Call",
@@ -104,8 +131,83 @@ impl ProfileData {
             OpCode::Sin { .. } => "Sin",
             OpCode::Cos { .. } => "Cos",
             OpCode::Tan { .. } => "Tan",
+            OpCode::Floor { .. } => "Floor",
+            OpCode::Ceil { .. } => "Ceil",
+            OpCode::Round { .. } => "Round",
+            OpCode::Trunc { .. } => "Trunc",
+            OpCode::Exp { .. } => "Exp",
+            OpCode::Ln { .. } => "Ln",
+            OpCode::Log2 { .. } => "Log2",
+            OpCode::Log10 { .. } => "Log10",
+            OpCode::Asin { .. } => "Asin",
+            OpCode::Acos { .. } => "Acos",
+            OpCode::Atan { .. } => "Atan",
+            OpCode::Atan2 { .. } => "Atan2",
+            OpCode::Hypot { .. } => "Hypot",
+            OpCode::CopySign { .. } => "CopySign",
+            OpCode::Fma { .. } => "Fma",
+            OpCode::IsNan { .. } => "IsNan",
+            OpCode::IsInf { .. } => "IsInf",
+            OpCode::IsFinite { .. } => "IsFinite",
+            OpCode::VecSplat { .. } => "VecSplat",
+            OpCode::VecExtract { .. } => "VecExtract",
+            OpCode::VecInsert { .. } => "VecInsert",
+            OpCode::VecShuffle { .. } => "VecShuffle",
+            OpCode::VecEq { .. } => "VecEq",
+            OpCode::VecNe { .. } => "VecNe",
+            OpCode::VecLt { .. } => "VecLt",
+            OpCode::VecLe { .. } => "VecLe",
+            OpCode::VecGt { .. } => "VecGt",
+            OpCode::VecGe { .. } => "VecGe",
+            OpCode::VecSelect { .. } => "VecSelect",
+            OpCode::StrLen { .. } => "StrLen",
+            OpCode::StrConcat { .. } => "StrConcat",
+            OpCode::StrSub { .. } => "StrSub",
+            OpCode::StrFind { .. } => "StrFind",
+            OpCode::StrCmp { .. } => "StrCmp",
+            OpCode::StrToInt { .. } => "StrToInt",
+            OpCode::IntToStr { .. } => "IntToStr",
+            OpCode::FloatToStr { .. } => "FloatToStr",
+            OpCode::PtrToStr { .. } => "PtrToStr",
+            OpCode::StrToPtr { .. } => "StrToPtr",
+            OpCode::ArrayNew { .. } => "ArrayNew",
+            OpCode::ArrayPush { .. } => "ArrayPush",
+            OpCode::ArrayPop { .. } => "ArrayPop",
+            OpCode::MapNew { .. } => "MapNew",
+            OpCode::MapHas { .. } => "MapHas",
+            OpCode::MapKey { .. } => "MapKey",
+            OpCode::ObjLen { .. } => "ObjLen",
+            OpCode::ObjGet { .. } => "ObjGet",
+            OpCode::ObjSet { .. } => "ObjSet",
             OpCode::Print { .. } => "Print",
             OpCode::Input { .. } => "Input",
+            OpCode::PrintStr { .. } => "PrintStr",
+            OpCode::PrintChar { .. } => "PrintChar",
+            OpCode::EPrintStr { .. } => "EPrintStr",
+            OpCode::EPrintChar { .. } => "EPrintChar",
+            OpCode::ReadLine { .. } => "ReadLine",
+            OpCode::Printf { .. } => "Printf",
+            OpCode::FileOpen { .. } => "FileOpen",
+            OpCode::FileRead { .. } => "FileRead",
+            OpCode::FileWrite { .. } => "FileWrite",
+            OpCode::FileSeek { .. } => "FileSeek",
+            OpCode::FileClose { .. } => "FileClose",
+            OpCode::Clock { .. } => "Clock",
+            OpCode::Sleep { .. } => "Sleep",
+            OpCode::Srand { .. } => "Srand",
+            OpCode::Rand { .. } => "Rand",
+            OpCode::GetEnv { .. } => "GetEnv",
+            OpCode::TcpConnect { .. } => "TcpConnect",
+            OpCode::TcpListen { .. } => "TcpListen",
+            OpCode::TcpAccept { .. } => "TcpAccept",
+            OpCode::TcpSend { .. } => "TcpSend",
+            OpCode::TcpRecv { .. } => "TcpRecv",
+            OpCode::TcpClose { .. } => "TcpClose",
+            OpCode::TcpNonblock { .. } => "TcpNonblock",
+            OpCode::ReadKey { .. } => "ReadKey",
+            OpCode::SetCursor { .. } => "SetCursor",
+            OpCode::ClearScreen => "ClearScreen",
+            OpCode::SetColor { .. } => "SetColor",
             OpCode::Exit { .. } => "Exit",
         }
         .to_string()
@@ -1,3 +1,6 @@
+use crate::gc::ObjRef;
+use crate::simd::{Vector, VectorType};
+use std::sync::Arc;
 use varvm_macros::ValueOps;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -6,14 +9,24 @@ pub enum DataType {
     I16,
     I32,
     I64,
+    I128,
     U8,
     U16,
     U32,
     U64,
+    U128,
     F32,
     F64,
     Ptr,
     Void,
+    Bool,
+    Char,
+    // 128-bit SIMD vector
+    Vector(VectorType),
+    // Immutable UTF-8 string
+    Str,
+    // Garbage-collected array or map
+    Ref,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -24,19 +37,60 @@ pub enum Operand {
     Type(DataType),
 }
 
+/// Byte order of a memory access. The VM's native layout is little-endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+/// Memory operand for `load`/`store`: `base + index * scale + offset`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Address {
+    pub base: String,
+    pub index: Option<String>,
+    pub scale: i64,
+    pub offset: i64,
+}
+
+impl Address {
+    pub fn new(base: impl Into<String>) -> Self {
+        Address {
+            base: base.into(),
+            index: None,
+            scale: 1,
+            offset: 0,
+        }
+    }
+
+    pub fn is_plain(&self) -> bool {
+        self.index.is_none() && self.offset == 0
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, ValueOps)]
 pub enum Value {
     I8(i8),
     I16(i16),
     I32(i32),
     I64(i64),
+    I128(i128),
     U8(u8),
     U16(u16),
     U32(u32),
     U64(u64),
+    U128(u128),
     F32(f32),
     F64(f64),
     Ptr(usize),
+    Bool(bool),
+    // A Unicode scalar value
+    Char(char),
+    Vector(Vector),
+    // Shared, so copying a string between variables never copies its text
+    Str(Arc<str>),
+    Ref(ObjRef),
 }
 
 impl From<i32> for Operand {
@@ -59,13 +113,20 @@ impl Value {
             Value::I16(v) => *v == 0,
             Value::I32(v) => *v == 0,
             Value::I64(v) => *v == 0,
+            Value::I128(v) => *v == 0,
             Value::U8(v) => *v == 0,
             Value::U16(v) => *v == 0,
             Value::U32(v) => *v == 0,
             Value::U64(v) => *v == 0,
+            Value::U128(v) => *v == 0,
             Value::F32(v) => *v == 0.0,
             Value::F64(v) => *v == 0.0,
             Value::Ptr(v) => *v == 0,
+            Value::Bool(v) => !*v,
+            Value::Char(v) => *v == '\0',
+            Value::Vector(v) => v.is_zero(),
+            Value::Str(s) => s.is_empty(),
+            Value::Ref(r) => r.is_null(),
         }
     }
 
@@ -75,10 +136,12 @@ impl Value {
             Value::I16(v) if *v >= 0 => Ok(*v as usize),
             Value::I32(v) if *v >= 0 => Ok(*v as usize),
             Value::I64(v) if *v >= 0 => Ok(*v as usize),
+            Value::I128(v) if *v >= 0 => usize::try_from(*v).map_err(|_| format!("Value {} does not fit in usize", v)),
             Value::U8(v) => Ok(*v as usize),
             Value::U16(v) => Ok(*v as usize),
             Value::U32(v) => Ok(*v as usize),
             Value::U64(v) => Ok(*v as usize),
+            Value::U128(v) => usize::try_from(*v).map_err(|_| format!("Value {} does not fit in usize", v)),
             Value::Ptr(v) => Ok(*v),
             _ => Err("Cannot convert to usize (negative or invalid type)".to_string()),
         }
@@ -86,6 +149,22 @@ impl Value {
 
     // Cross-type numeric equality comparison
     pub fn equals(&self, other: &Value) -> bool {
+        // Vectors, strings and references only equal their own kind
+        if let (Value::Vector(a), Value::Vector(b)) = (self, other) {
+            return a == b;
+        }
+        if let (Value::Str(a), Value::Str(b)) = (self, other) {
+            return a == b;
+        }
+        // References are equal when they name the same object
+        if let (Value::Ref(a), Value::Ref(b)) = (self, other) {
+            return a == b;
+        }
+        let is_scalar = |v: &Value| !matches!(v, Value::Vector(_) | Value::Str(_) | Value::Ref(_));
+        if !is_scalar(self) || !is_scalar(other) {
+            return false;
+        }
+
         // For floating point, convert both to f64 and compare
         if matches!(self, Value::F32(_) | Value::F64(_)) || matches!(other, Value::F32(_) | Value::F64(_)) {
             let a = match self {
@@ -93,55 +172,238 @@ impl Value {
                 Value::I16(v) => *v as f64,
                 Value::I32(v) => *v as f64,
                 Value::I64(v) => *v as f64,
+                Value::I128(v) => *v as f64,
                 Value::U8(v) => *v as f64,
                 Value::U16(v) => *v as f64,
                 Value::U32(v) => *v as f64,
                 Value::U64(v) => *v as f64,
+                Value::U128(v) => *v as f64,
                 Value::F32(v) => *v as f64,
                 Value::F64(v) => *v,
                 Value::Ptr(v) => *v as f64,
+                Value::Bool(v) => *v as u8 as f64,
+                Value::Char(v) => *v as u32 as f64,
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             };
             let b = match other {
                 Value::I8(v) => *v as f64,
                 Value::I16(v) => *v as f64,
                 Value::I32(v) => *v as f64,
                 Value::I64(v) => *v as f64,
+                Value::I128(v) => *v as f64,
                 Value::U8(v) => *v as f64,
                 Value::U16(v) => *v as f64,
                 Value::U32(v) => *v as f64,
                 Value::U64(v) => *v as f64,
+                Value::U128(v) => *v as f64,
                 Value::F32(v) => *v as f64,
                 Value::F64(v) => *v,
                 Value::Ptr(v) => *v as f64,
+                Value::Bool(v) => *v as u8 as f64,
+                Value::Char(v) => *v as u32 as f64,
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             };
             return a == b;
         }
 
-        // For integers, convert both to i64 and compare
-        let a = match self {
-            Value::I8(v) => *v as i64,
-            Value::I16(v) => *v as i64,
-            Value::I32(v) => *v as i64,
-            Value::I64(v) => *v,
-            Value::U8(v) => *v as i64,
-            Value::U16(v) => *v as i64,
-            Value::U32(v) => *v as i64,
-            Value::U64(v) => *v as i64,
-            Value::Ptr(v) => *v as i64,
-            _ => return false,
+        // For integers, compare the exact values; a u128 above i128::MAX only equals another u128
+        match (self, other) {
+            (Value::U128(a), Value::U128(b)) => a == b,
+            _ => match (self.exact_int(), other.exact_int()) {
+                (Some(a), Some(b)) => a == b,
+                _ => false,
+            },
+        }
+    }
+
+    fn exact_int(&self) -> Option<i128> {
+        match self {
+            Value::I8(v) => Some(*v as i128),
+            Value::I16(v) => Some(*v as i128),
+            Value::I32(v) => Some(*v as i128),
+            Value::I64(v) => Some(*v as i128),
+            Value::I128(v) => Some(*v),
+            Value::U8(v) => Some(*v as i128),
+            Value::U16(v) => Some(*v as i128),
+            Value::U32(v) => Some(*v as i128),
+            Value::U64(v) => Some(*v as i128),
+            Value::U128(v) => i128::try_from(*v).ok(),
+            Value::Ptr(v) => Some(*v as i128),
+            Value::Bool(v) => Some(*v as i128),
+            Value::Char(v) => Some(*v as i128),
+            _ => None,
+        }
+    }
+}
+
+impl Value {
+    pub fn dtype(&self) -> DataType {
+        match self {
+            Value::I8(_) => DataType::I8,
+            Value::I16(_) => DataType::I16,
+            Value::I32(_) => DataType::I32,
+            Value::I64(_) => DataType::I64,
+            Value::I128(_) => DataType::I128,
+            Value::U8(_) => DataType::U8,
+            Value::U16(_) => DataType::U16,
+            Value::U32(_) => DataType::U32,
+            Value::U64(_) => DataType::U64,
+            Value::U128(_) => DataType::U128,
+            Value::F32(_) => DataType::F32,
+            Value::F64(_) => DataType::F64,
+            Value::Ptr(_) => DataType::Ptr,
+            Value::Bool(_) => DataType::Bool,
+            Value::Char(_) => DataType::Char,
+            Value::Vector(v) => DataType::Vector(v.shape()),
+            Value::Str(_) => DataType::Str,
+            Value::Ref(_) => DataType::Ref,
+        }
+    }
+
+    // Little-endian memory image, used for laying out initialized data blocks
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        match self {
+            Value::I8(v) => v.to_le_bytes().to_vec(),
+            Value::I16(v) => v.to_le_bytes().to_vec(),
+            Value::I32(v) => v.to_le_bytes().to_vec(),
+            Value::I64(v) => v.to_le_bytes().to_vec(),
+            Value::I128(v) => v.to_le_bytes().to_vec(),
+            Value::U8(v) => vec![*v],
+            Value::U16(v) => v.to_le_bytes().to_vec(),
+            Value::U32(v) => v.to_le_bytes().to_vec(),
+            Value::U64(v) => v.to_le_bytes().to_vec(),
+            Value::U128(v) => v.to_le_bytes().to_vec(),
+            Value::F32(v) => v.to_le_bytes().to_vec(),
+            Value::F64(v) => v.to_le_bytes().to_vec(),
+            Value::Ptr(v) => (*v as u64).to_le_bytes().to_vec(),
+            Value::Bool(v) => vec![*v as u8],
+            Value::Char(v) => (*v as u32).to_le_bytes().to_vec(),
+            Value::Vector(v) => v.to_le_bytes(),
+            Value::Str(s) => s.as_bytes().to_vec(),
+            // References have no memory image
+            Value::Ref(_) => Vec::new(),
+        }
+    }
+
+    // Parses text such as "42", "-0x1F", "0b101" or "2.5e3" as a value of `dtype`
+    pub fn parse_as(text: &str, dtype: DataType) -> Result<Value, String> {
+        let text = text.trim();
+        let invalid = || format!("Invalid {:?} value: '{}'", dtype, text);
+
+        match dtype {
+            DataType::F32 => return text.parse::<f32>().map(Value::F32).map_err(|_| invalid()),
+            DataType::F64 => return text.parse::<f64>().map(Value::F64).map_err(|_| invalid()),
+            DataType::Void => return Err("Cannot read a void value".to_string()),
+            DataType::Vector(shape) => return Err(format!("Cannot parse a {} value", shape.name())),
+            DataType::Str => return Ok(Value::Str(text.into())),
+            DataType::Ref => return Err("Cannot parse a reference".to_string()),
+            DataType::Bool => {
+                return match text {
+                    "true" | "1" => Ok(Value::Bool(true)),
+                    "false" | "0" => Ok(Value::Bool(false)),
+                    _ => Err(invalid()),
+                };
+            }
+            DataType::Char => {
+                let mut chars = text.chars();
+                return match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(Value::Char(c)),
+                    _ => Err(invalid()),
+                };
+            }
+            _ => {}
+        }
+
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (radix, digits) = match digits.get(..2) {
+            Some("0x") | Some("0X") => (16, &digits[2..]),
+            Some("0b") | Some("0B") => (2, &digits[2..]),
+            Some("0o") | Some("0O") => (8, &digits[2..]),
+            _ => (10, digits),
+        };
+        let magnitude = u128::from_str_radix(digits, radix).map_err(|_| invalid())?;
+        if dtype == DataType::U128 && !negative {
+            return Ok(Value::U128(magnitude));
+        }
+        let value = if negative && magnitude == i128::MIN.unsigned_abs() {
+            i128::MIN
+        } else if negative {
+            i128::try_from(magnitude).map(|m| -m).map_err(|_| invalid())?
+        } else {
+            i128::try_from(magnitude).map_err(|_| invalid())?
         };
-        let b = match other {
-            Value::I8(v) => *v as i64,
-            Value::I16(v) => *v as i64,
-            Value::I32(v) => *v as i64,
-            Value::I64(v) => *v,
-            Value::U8(v) => *v as i64,
-            Value::U16(v) => *v as i64,
-            Value::U32(v) => *v as i64,
-            Value::U64(v) => *v as i64,
-            Value::Ptr(v) => *v as i64,
-            _ => return false,
+
+        let out_of_range = |_| format!("{:?} value out of range: '{}'", dtype, text);
+        Ok(match dtype {
+            DataType::I8 => Value::I8(i8::try_from(value).map_err(out_of_range)?),
+            DataType::I16 => Value::I16(i16::try_from(value).map_err(out_of_range)?),
+            DataType::I32 => Value::I32(i32::try_from(value).map_err(out_of_range)?),
+            DataType::I64 => Value::I64(i64::try_from(value).map_err(out_of_range)?),
+            DataType::I128 => Value::I128(value),
+            DataType::U8 => Value::U8(u8::try_from(value).map_err(out_of_range)?),
+            DataType::U16 => Value::U16(u16::try_from(value).map_err(out_of_range)?),
+            DataType::U32 => Value::U32(u32::try_from(value).map_err(out_of_range)?),
+            DataType::U64 => Value::U64(u64::try_from(value).map_err(out_of_range)?),
+            DataType::U128 => Value::U128(u128::try_from(value).map_err(out_of_range)?),
+            DataType::Ptr => Value::Ptr(usize::try_from(value).map_err(out_of_range)?),
+            DataType::F32
+            | DataType::F64
+            | DataType::Void
+            | DataType::Bool
+            | DataType::Char
+            | DataType::Vector(_)
+            | DataType::Str
+            | DataType::Ref => unreachable!(),
+        })
+    }
+
+    /// Converts a literal to `dtype` only if nothing is lost: integers must be in
+    /// range (0 or 1 for `bool`), floats integral when the target is an integer, and
+    /// chars stay chars. Narrowing between float widths may round but not overflow.
+    pub fn cast_exact(&self, dtype: DataType) -> Option<Value> {
+        let converted = self.cast(dtype).ok()?;
+        let exact = match (self, &converted) {
+            (Value::Char(_), Value::Char(_)) => true,
+            (Value::Char(_), _) | (_, Value::Char(_)) => false,
+            (Value::F64(v), Value::F32(narrow)) => narrow.is_finite() || !v.is_finite(),
+            (Value::F32(_), Value::F64(_)) => true,
+            _ if self.dtype() == dtype => true,
+            _ => converted.cast(self.dtype()).is_ok_and(|back| back == *self),
         };
-        a == b
+        exact.then_some(converted)
+    }
+
+    pub fn from_le_bytes(bytes: &[u8], dtype: DataType) -> Result<Value, String> {
+        let need = crate::program::Variable::size_of(dtype);
+        if need == 0 || bytes.len() < need {
+            return Err(format!("Insufficient bytes for {:?}", dtype));
+        }
+        let mut buf = [0u8; 16];
+        buf[..need].copy_from_slice(&bytes[..need]);
+        Ok(match dtype {
+            DataType::I8 => Value::I8(buf[0] as i8),
+            DataType::I16 => Value::I16(i16::from_le_bytes([buf[0], buf[1]])),
+            DataType::I32 => Value::I32(i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])),
+            DataType::I64 => Value::I64(i64::from_le_bytes(buf[..8].try_into().unwrap())),
+            DataType::I128 => Value::I128(i128::from_le_bytes(buf)),
+            DataType::U8 => Value::U8(buf[0]),
+            DataType::U16 => Value::U16(u16::from_le_bytes([buf[0], buf[1]])),
+            DataType::U32 => Value::U32(u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])),
+            DataType::U64 => Value::U64(u64::from_le_bytes(buf[..8].try_into().unwrap())),
+            DataType::U128 => Value::U128(u128::from_le_bytes(buf)),
+            DataType::F32 => Value::F32(f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])),
+            DataType::F64 => Value::F64(f64::from_le_bytes(buf[..8].try_into().unwrap())),
+            DataType::Ptr => Value::Ptr(u64::from_le_bytes(buf[..8].try_into().unwrap()) as usize),
+            DataType::Bool => Value::Bool(buf[0] != 0),
+            DataType::Char => {
+                let code = u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]);
+                Value::Char(char::from_u32(code).ok_or_else(|| format!("Invalid character code: {:#x}", code))?)
+            }
+            DataType::Vector(shape) => Value::Vector(Vector::from_le_bytes(&buf, shape)?),
+            DataType::Void | DataType::Str | DataType::Ref => unreachable!(),
+        })
     }
 }
@@ -1,9 +1,19 @@
+use crate::capabilities::Capabilities;
+use crate::console::{AnsiConsole, Console};
 use crate::opcode::OpCode;
-use crate::program::Program;
+use crate::program::{Function, Program, SourceLocation, Variable};
+use crate::gc::{GcStats, ManagedHeap, Map, ObjRef, Object};
+use crate::simd::{CompareOp, Vector};
+use crate::system::SystemServices;
 use crate::tools::profiler::ProfileData;
-use crate::types::{DataType, Operand, Value};
+use crate::types::{Address, DataType, Endian, Operand, Value};
 use std::collections::{HashMap, HashSet};
-use std::io::Write;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 // Macro for binary operations
 macro_rules! binary_op {
@@ -19,7 +29,7 @@ macro_rules! comparison_op {
     ($self:expr, $dest:expr, $left:expr, $right:expr, $method:ident) => {{
         let l = $self.resolve_operand(&$left)?;
         let r = $self.resolve_operand(&$right)?;
-        $self.set_variable(&$dest, Value::I32(if l.$method(&r)? { 1 } else { 0 }))?;
+        $self.set_variable(&$dest, Value::Bool(l.$method(&r)?))?;
     }};
 }
 
@@ -28,12 +38,23 @@ macro_rules! equality_op {
     ($self:expr, $dest:expr, $left:expr, $right:expr, ==) => {{
         let l = $self.resolve_operand(&$left)?;
         let r = $self.resolve_operand(&$right)?;
-        $self.set_variable(&$dest, Value::I32(if l.equals(&r) { 1 } else { 0 }))?;
+        $self.set_variable(&$dest, Value::Bool(l.equals(&r)))?;
     }};
     ($self:expr, $dest:expr, $left:expr, $right:expr, !=) => {{
         let l = $self.resolve_operand(&$left)?;
         let r = $self.resolve_operand(&$right)?;
-        $self.set_variable(&$dest, Value::I32(if !l.equals(&r) { 1 } else { 0 }))?;
+        $self.set_variable(&$dest, Value::Bool(!l.equals(&r)))?;
+    }};
+}
+
+// Macro for fused compare-and-branch
+macro_rules! compare_jump {
+    ($self:expr, $left:expr, $right:expr, $label:expr, $method:ident) => {{
+        let l = $self.resolve_operand(&$left)?;
+        let r = $self.resolve_operand(&$right)?;
+        if l.$method(&r)? {
+            $self.jump_to(&$label)?;
+        }
     }};
 }
 
@@ -45,15 +66,177 @@ macro_rules! unary_op {
     }};
 }
 
+// Macro for float-only math intrinsics
+macro_rules! float_unary_op {
+    ($self:expr, $dest:expr, $source:expr, $name:literal, $method:ident) => {{
+        let val = $self.resolve_operand(&$source)?;
+        let result = match val {
+            Value::F32(v) => Value::F32(v.$method()),
+            Value::F64(v) => Value::F64(v.$method()),
+            _ => return Err(format!(concat!($name, " requires float type, got {:?}"), val)),
+        };
+        $self.set_variable(&$dest, result)?;
+    }};
+}
+
+macro_rules! float_binary_op {
+    ($self:expr, $dest:expr, $a:expr, $b:expr, $name:literal, $method:ident) => {{
+        let a = $self.resolve_operand(&$a)?;
+        let b = $self.resolve_operand(&$b)?;
+        let result = match (a, b) {
+            (Value::F32(x), Value::F32(y)) => Value::F32(x.$method(y)),
+            (Value::F64(x), Value::F64(y)) => Value::F64(x.$method(y)),
+            _ => return Err(concat!($name, " requires matching float types").to_string()),
+        };
+        $self.set_variable(&$dest, result)?;
+    }};
+}
+
+// Macro for NaN/infinity classification, producing 0 or 1
+macro_rules! float_class_op {
+    ($self:expr, $dest:expr, $source:expr, $name:literal, $method:ident) => {{
+        let val = $self.resolve_operand(&$source)?;
+        let result = match val {
+            Value::F32(v) => v.$method(),
+            Value::F64(v) => v.$method(),
+            _ => return Err(format!(concat!($name, " requires float type, got {:?}"), val)),
+        };
+        $self.set_variable(&$dest, Value::Bool(result))?;
+    }};
+}
+
+// Lane-wise comparison producing an all-ones/zero mask vector
+macro_rules! vector_compare_op {
+    ($self:expr, $dest:expr, $left:expr, $right:expr, $op:ident) => {{
+        let left = $self.resolve_vector(&$left, "vector comparison")?;
+        let right = $self.resolve_vector(&$right, "vector comparison")?;
+        let mask = left.compare(&right, CompareOp::$op)?;
+        $self.set_variable(&$dest, Value::Vector(mask))?;
+    }};
+}
+
 #[derive(Debug, Clone)]
 pub struct CallFrame {
     pub function_name: String,
     pub return_ip: usize,
     pub locals: HashMap<String, Value>,
+    // Declared types of `local`s and typed parameters
+    pub local_types: HashMap<String, DataType>,
     pub return_dest: Option<String>,
     pub args: Vec<Value>,
 }
 
+// Status codes written by `input`/`read` when a status variable is given
+const INPUT_OK: i32 = 0;
+const INPUT_EOF: i32 = 1;
+const INPUT_PARSE_ERROR: i32 = 2;
+
+// Negative results of the file and socket operations; sandbox denials are runtime errors instead
+const ERR_IO: i32 = -1;
+const ERR_NOT_FOUND: i32 = -2;
+const ERR_BAD_HANDLE: i32 = -3;
+const ERR_BAD_ARGUMENT: i32 = -4;
+const ERR_HOST_DENIED: i32 = -5;
+const ERR_WOULD_BLOCK: i32 = -6;
+const ERR_CONNECTION_REFUSED: i32 = -7;
+const ERR_ADDR_IN_USE: i32 = -8;
+
+// File modes accepted by `fopen`
+const FILE_MODE_READ: usize = 0;
+const FILE_MODE_WRITE: usize = 1;
+const FILE_MODE_APPEND: usize = 2;
+
+fn io_error_code(e: &io::Error) -> i32 {
+    match e.kind() {
+        io::ErrorKind::NotFound => ERR_NOT_FOUND,
+        io::ErrorKind::PermissionDenied => ERR_HOST_DENIED,
+        io::ErrorKind::InvalidInput => ERR_BAD_ARGUMENT,
+        io::ErrorKind::WouldBlock => ERR_WOULD_BLOCK,
+        io::ErrorKind::ConnectionRefused => ERR_CONNECTION_REFUSED,
+        io::ErrorKind::AddrInUse => ERR_ADDR_IN_USE,
+        _ => ERR_IO,
+    }
+}
+
+fn io_error(e: io::Error) -> String {
+    format!("IO error: {}", e)
+}
+
+// Parses a run of ASCII digits in a printf specifier, advancing `i`
+fn read_decimal(fmt: &[u8], i: &mut usize) -> Option<usize> {
+    let start = *i;
+    while fmt.get(*i).is_some_and(u8::is_ascii_digit) {
+        *i += 1;
+    }
+    std::str::from_utf8(&fmt[start..*i]).ok()?.parse().ok()
+}
+
+// Integer precision is a minimum digit count, as in C
+fn min_digits(digits: String, precision: Option<usize>) -> String {
+    match precision {
+        Some(p) if p > digits.len() => format!("{}{}", "0".repeat(p - digits.len()), digits),
+        _ => digits,
+    }
+}
+
+// Unit that byte order applies to: a vector is swapped lane by lane
+fn element_size(dtype: DataType) -> usize {
+    match dtype {
+        DataType::Vector(shape) => shape.lane_size(),
+        _ => Variable::size_of(dtype),
+    }
+}
+
+// Converts between native (little-endian) order and `endian`, one `size`-byte element at a time
+fn reorder_elements(mut bytes: Vec<u8>, size: usize, endian: Endian) -> Vec<u8> {
+    if endian == Endian::Big && size > 1 {
+        bytes.chunks_exact_mut(size).for_each(|element| element.reverse());
+    }
+    bytes
+}
+
+enum Socket {
+    Listener(TcpListener),
+    Stream(TcpStream),
+}
+
+impl Socket {
+    fn set_nonblocking(&self, enabled: bool) -> io::Result<()> {
+        match self {
+            Socket::Listener(listener) => listener.set_nonblocking(enabled),
+            Socket::Stream(stream) => stream.set_nonblocking(enabled),
+        }
+    }
+}
+
+/// Deliberate failure raised by `assert`, `trap` or `unreachable`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrapKind {
+    AssertionFailed(Option<String>),
+    Code(i32),
+    Unreachable,
+}
+
+impl std::fmt::Display for TrapKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrapKind::AssertionFailed(Some(message)) => write!(f, "Assertion failed: {}", message),
+            TrapKind::AssertionFailed(None) => write!(f, "Assertion failed"),
+            TrapKind::Code(code) => write!(f, "Trap {}", code),
+            TrapKind::Unreachable => write!(f, "Reached unreachable code"),
+        }
+    }
+}
+
+/// The trap that stopped the program, with the instruction that raised it
+/// and its source location when the program carries a source map.
+#[derive(Debug, Clone)]
+pub struct Trap {
+    pub kind: TrapKind,
+    pub ip: usize,
+    pub location: Option<SourceLocation>,
+}
+
 pub type DebugCallback = Box<dyn FnMut(&mut VM, usize, &OpCode) -> Result<(), String>>;
 
 pub struct VM {
@@ -63,6 +246,8 @@ pub struct VM {
     call_stack: Vec<CallFrame>,
     current_frame: CallFrame,
     heap: HashMap<usize, Vec<u8>>,
+    // Element type and count of allocations made by alloc_array or data arrays
+    heap_types: HashMap<usize, (DataType, usize)>,
     next_heap_addr: usize,
     running: bool,
     debug_mode: bool,
@@ -70,6 +255,18 @@ pub struct VM {
     debug_callback: Option<DebugCallback>,
     profile_enabled: bool,
     profile_data: ProfileData,
+    output: Box<dyn Write>,
+    error_output: Box<dyn Write>,
+    input: Box<dyn BufRead>,
+    console: Box<dyn Console>,
+    capabilities: Capabilities,
+    files: HashMap<i32, File>,
+    sockets: HashMap<i32, Socket>,
+    next_handle: i32,
+    system: SystemServices,
+    trap: Option<Trap>,
+    // Garbage-collected arrays and maps, separate from the byte heap
+    objects: ManagedHeap,
 }
 
 impl VM {
@@ -83,10 +280,12 @@ impl VM {
                 function_name: "main".to_string(),
                 return_ip: 0,
                 locals: HashMap::new(),
+                local_types: HashMap::new(),
                 return_dest: None,
                 args: Vec::new(),
             },
             heap: HashMap::new(),
+            heap_types: HashMap::new(),
             next_heap_addr: 0x1000,
             running: true,
             debug_mode: false,
@@ -94,19 +293,46 @@ impl VM {
             debug_callback: None,
             profile_enabled: false,
             profile_data: ProfileData::new(),
+            output: Box::new(io::stdout()),
+            error_output: Box::new(io::stderr()),
+            input: Box::new(io::BufReader::new(io::stdin())),
+            console: Box::new(AnsiConsole::new()),
+            capabilities: Capabilities::new(),
+            files: HashMap::new(),
+            sockets: HashMap::new(),
+            next_handle: 3,
+            system: SystemServices::new(),
+            trap: None,
+            objects: ManagedHeap::new(),
         };
 
-        // Initialize globals
+        // Initialize globals to the zero value of their declared type
         for global in &vm.program.globals {
-            vm.globals.insert(global.name.clone(), Value::I32(0));
+            vm.globals.insert(global.name.clone(), vm.default_value(global.dtype));
         }
 
         // Initialize string literals
         vm.initialize_strings();
 
+        // Initialize array data blocks
+        vm.initialize_data_blocks();
+
         vm
     }
 
+    fn initialize_data_blocks(&mut self) {
+        for block in &self.program.data_blocks.clone() {
+            let addr = self.next_heap_addr;
+            self.next_heap_addr += block.bytes.len();
+
+            self.heap.insert(addr, block.bytes.clone());
+            self.heap_types.insert(addr, (block.dtype, block.count));
+
+            // Bind the array's global name to its memory block
+            self.globals.insert(block.global_name.clone(), Value::Ptr(addr));
+        }
+    }
+
     fn initialize_strings(&mut self) {
         for string_literal in &self.program.strings.clone() {
             // Allocate memory for the string (content + null terminator)
@@ -139,9 +365,21 @@ impl VM {
             .clone();
         self.ip = main_func.start_ip + 1;
 
+        // A typed main receives the command-line arguments as parameters
+        if main_func.typed && !main_func.parameters.is_empty() {
+            let args = std::mem::take(&mut self.current_frame.args);
+            let operands: Vec<Operand> = args.into_iter().map(Operand::Immediate).collect();
+            let params = self.bind_parameters(&main_func, &operands)?;
+            self.current_frame.locals.extend(params);
+            self.current_frame
+                .local_types
+                .extend(main_func.parameters.iter().map(|param| (param.name.clone(), param.dtype)));
+        }
+
         while self.running && self.ip < self.program.instructions.len() {
             self.execute_instruction()?;
         }
+        self.output.flush().map_err(io_error)?;
         Ok(0)
     }
 
@@ -179,6 +417,7 @@ impl VM {
         match instruction {
             OpCode::CreateLocal { dtype, name } => {
                 let value = self.default_value(dtype);
+                self.current_frame.local_types.insert(name.clone(), dtype);
                 self.current_frame.locals.insert(name, value);
             }
 
@@ -188,6 +427,7 @@ impl VM {
             }
 
             OpCode::DeleteLocal { name } => {
+                self.current_frame.local_types.remove(&name);
                 self.current_frame.locals.remove(&name);
             }
 
@@ -203,37 +443,139 @@ impl VM {
 
             OpCode::Alloc { dest, size } => {
                 let size = self.resolve_operand(&size)?.as_usize()?;
-                let addr = self.next_heap_addr;
-                self.heap.insert(addr, vec![0u8; size]);
-                self.next_heap_addr += size;
+                let addr = self.allocate(size)?;
+                self.set_variable(&dest, Value::Ptr(addr))?;
+            }
+
+            OpCode::AllocArray { dest, elem, count } => {
+                let elem = self.resolve_type(&elem)?;
+                let count = self.resolve_operand(&count)?.as_usize()?;
+                let size = count.checked_mul(Variable::size_of(elem)).ok_or_else(|| {
+                    format!("alloc_array size overflow: {} x {:?}", count, elem)
+                })?;
+                let addr = self.allocate(size)?;
+                self.heap_types.insert(addr, (elem, count));
                 self.set_variable(&dest, Value::Ptr(addr))?;
             }
 
+            OpCode::SizeOf { dest, target } => {
+                let size = match &target {
+                    Operand::Type(dtype) => Value::I32(Variable::size_of(*dtype) as i32),
+                    // Struct and array sizes are folded to immediates by the assembler
+                    _ => self.resolve_operand(&target)?.cast(DataType::I32)?,
+                };
+                self.set_variable(&dest, size)?;
+            }
+
             OpCode::Free { ptr } => {
                 let addr = self.get_variable(&ptr)?.as_usize()?;
                 self.heap.remove(&addr);
+                self.heap_types.remove(&addr);
             }
 
-            OpCode::Load { dest, ptr, dtype } => {
-                let addr = self.get_variable(&ptr)?.as_usize()?;
+            OpCode::Load { dest, addr, dtype, endian } => {
+                let addr = self.resolve_address(&addr)?;
                 // Calculate how many bytes we need to read based on dtype
                 let byte_count = match dtype {
-                    DataType::I8 | DataType::U8 => 1,
+                    DataType::I8 | DataType::U8 | DataType::Bool => 1,
                     DataType::I16 | DataType::U16 => 2,
-                    DataType::I32 | DataType::U32 | DataType::F32 => 4,
+                    DataType::I32 | DataType::U32 | DataType::F32 | DataType::Char => 4,
                     DataType::I64 | DataType::U64 | DataType::F64 | DataType::Ptr => 8,
-                    DataType::Void => 0,
+                    DataType::I128 | DataType::U128 | DataType::Vector(_) => 16,
+                    DataType::Void | DataType::Str | DataType::Ref => 0,
                 };
+                // The heap is byte-addressed, so any alignment is fine
                 let bytes = self.load_bytes_from_heap(addr, byte_count)?;
+                let bytes = reorder_elements(bytes, element_size(dtype), endian);
                 let value = self.bytes_to_value(&bytes, dtype)?;
                 self.set_variable(&dest, value)?;
             }
 
-            OpCode::Store { ptr, source, dtype } => {
-                let addr = self.get_variable(&ptr)?.as_usize()?;
-                let value = self.get_variable(&source)?;
+            OpCode::Store { addr, source, dtype, endian } => {
+                let addr = self.resolve_address(&addr)?;
+                let value = match &source {
+                    // Immediates take the width of the store
+                    Operand::Immediate(value) => value.cast(dtype)?,
+                    _ => self.resolve_operand(&source)?,
+                };
                 let bytes = self.value_to_bytes(&value, dtype)?;
-                self.store_bytes_to_heap(addr, bytes)?;
+                self.store_bytes_to_heap(addr, reorder_elements(bytes, element_size(dtype), endian))?;
+            }
+
+            OpCode::LoadVec { buf, addr, count, dtype, endian } => {
+                let src_addr = self.resolve_address(&addr)?;
+                let buf_addr = self.get_variable(&buf)?.as_usize()?;
+                let len = self.vector_len(&count, dtype)?;
+                if len > 0 {
+                    let bytes = self.load_bytes_from_heap(src_addr, len)?;
+                    self.store_bytes_to_heap(buf_addr, reorder_elements(bytes, element_size(dtype), endian))?;
+                }
+            }
+
+            OpCode::StoreVec { addr, buf, count, dtype, endian } => {
+                let dest_addr = self.resolve_address(&addr)?;
+                let buf_addr = self.get_variable(&buf)?.as_usize()?;
+                let len = self.vector_len(&count, dtype)?;
+                if len > 0 {
+                    let bytes = self.load_bytes_from_heap(buf_addr, len)?;
+                    self.store_bytes_to_heap(dest_addr, reorder_elements(bytes, element_size(dtype), endian))?;
+                }
+            }
+
+            OpCode::MemCopy { dest, src, len } => {
+                let dest_addr = self.get_variable(&dest)?.as_usize()?;
+                let src_addr = self.get_variable(&src)?.as_usize()?;
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                if len > 0 {
+                    if dest_addr < src_addr.saturating_add(len) && src_addr < dest_addr.saturating_add(len) {
+                        return Err(format!(
+                            "memcpy ranges overlap ({:#x} and {:#x}, {} bytes); use memmove",
+                            dest_addr, src_addr, len
+                        ));
+                    }
+                    let bytes = self.load_bytes_from_heap(src_addr, len)?;
+                    self.store_bytes_to_heap(dest_addr, bytes)?;
+                }
+            }
+
+            OpCode::MemMove { dest, src, len } => {
+                let dest_addr = self.get_variable(&dest)?.as_usize()?;
+                let src_addr = self.get_variable(&src)?.as_usize()?;
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                if len > 0 {
+                    // Bytes are copied out first, so overlapping ranges are safe
+                    let bytes = self.load_bytes_from_heap(src_addr, len)?;
+                    self.store_bytes_to_heap(dest_addr, bytes)?;
+                }
+            }
+
+            OpCode::MemSet { dest, value, len } => {
+                let dest_addr = self.get_variable(&dest)?.as_usize()?;
+                let byte = match self.resolve_operand(&value)?.cast(DataType::U8)? {
+                    Value::U8(b) => b,
+                    _ => unreachable!(),
+                };
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                if len > 0 {
+                    // Reading first bounds-checks the range before anything is written
+                    let mut bytes = self.load_bytes_from_heap(dest_addr, len)?;
+                    bytes.fill(byte);
+                    self.store_bytes_to_heap(dest_addr, bytes)?;
+                }
+            }
+
+            OpCode::MemCmp { result, a, b, len } => {
+                let a_addr = self.get_variable(&a)?.as_usize()?;
+                let b_addr = self.get_variable(&b)?.as_usize()?;
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                let ordering = if len > 0 {
+                    let a_bytes = self.load_bytes_from_heap(a_addr, len)?;
+                    let b_bytes = self.load_bytes_from_heap(b_addr, len)?;
+                    a_bytes.cmp(&b_bytes)
+                } else {
+                    std::cmp::Ordering::Equal
+                };
+                self.set_variable(&result, Value::I32(ordering as i32))?;
             }
 
             OpCode::GetAddr { dest, var } => {
@@ -258,12 +600,40 @@ impl VM {
             OpCode::Gt { dest, left, right } => comparison_op!(self, dest, left, right, gt),
             OpCode::Ge { dest, left, right } => comparison_op!(self, dest, left, right, ge),
 
+            OpCode::Select { dest, cond, a, b } => {
+                // Only the chosen operand is resolved
+                let chosen = if self.resolve_operand(&cond)?.is_zero() { b } else { a };
+                let value = self.resolve_operand(&chosen)?;
+                self.set_variable(&dest, value)?;
+            }
+
             OpCode::And { dest, left, right } => binary_op!(self, dest, left, right, bitwise_and),
             OpCode::Or { dest, left, right } => binary_op!(self, dest, left, right, bitwise_or),
             OpCode::Xor { dest, left, right } => binary_op!(self, dest, left, right, bitwise_xor),
             OpCode::Not { dest, source } => unary_op!(self, dest, source, bitwise_not),
             OpCode::Shl { dest, left, right } => binary_op!(self, dest, left, right, shift_left),
             OpCode::Shr { dest, left, right } => binary_op!(self, dest, left, right, shift_right),
+            OpCode::Popcnt { dest, source } => unary_op!(self, dest, source, count_ones),
+            OpCode::Clz { dest, source } => unary_op!(self, dest, source, leading_zeros),
+            OpCode::Ctz { dest, source } => unary_op!(self, dest, source, trailing_zeros),
+            OpCode::Bswap { dest, source } => unary_op!(self, dest, source, swap_bytes),
+            OpCode::Rotl { dest, left, right } => binary_op!(self, dest, left, right, rotate_left),
+            OpCode::Rotr { dest, left, right } => binary_op!(self, dest, left, right, rotate_right),
+
+            OpCode::BitExtract { dest, source, start, len } => {
+                let source = self.resolve_operand(&source)?;
+                let start = self.resolve_operand(&start)?.as_usize()?;
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                self.set_variable(&dest, source.extract_bits(start, len)?)?;
+            }
+
+            OpCode::BitInsert { dest, source, field, start, len } => {
+                let source = self.resolve_operand(&source)?;
+                let field = self.resolve_operand(&field)?;
+                let start = self.resolve_operand(&start)?.as_usize()?;
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                self.set_variable(&dest, source.insert_bits(&field, start, len)?)?;
+            }
 
             OpCode::Cast {
                 dest,
@@ -274,26 +644,50 @@ impl VM {
                 self.set_variable(&dest, val.cast(target_type)?)?;
             }
 
-            OpCode::Input { dest } => {
-                use std::io::{self, BufRead};
-                print!("Enter value for {}: ", dest);
-                io::stdout().flush().ok();
-                let stdin = io::stdin();
-                let line = stdin
-                    .lock()
-                    .lines()
-                    .next()
-                    .ok_or_else(|| "Failed to read input".to_string())?
-                    .map_err(|e| format!("IO error: {}", e))?;
-
-                // Try to parse as i32 by default
-                let value = line
-                    .trim()
-                    .parse::<i32>()
-                    .map(Value::I32)
-                    .map_err(|_| format!("Invalid integer: {}", line))?;
+            OpCode::Input { dest, dtype, status, prompt } => {
+                if prompt {
+                    write!(self.output, "Enter value for {}: ", dest).map_err(io_error)?;
+                }
+                self.output.flush().map_err(io_error)?;
 
-                self.set_variable(&dest, value)?;
+                let result = match self.read_input_line()? {
+                    Some(line) => Value::parse_as(&line, dtype).map_err(|e| (INPUT_PARSE_ERROR, e)),
+                    None => Err((INPUT_EOF, "Failed to read input: end of input".to_string())),
+                };
+
+                // With a status variable, failures are reported to the script instead of aborting
+                match (result, status) {
+                    (Ok(value), status) => {
+                        self.set_variable(&dest, value)?;
+                        if let Some(status) = status {
+                            self.set_variable(&status, Value::I32(INPUT_OK))?;
+                        }
+                    }
+                    (Err((code, _)), Some(status)) => self.set_variable(&status, Value::I32(code))?,
+                    (Err((_, message)), None) => return Err(message),
+                }
+            }
+
+            OpCode::ReadLine { dest, buf, cap } => {
+                let buf_addr = self.get_variable(&buf)?.as_usize()?;
+                let cap = self.resolve_operand(&cap)?.as_usize()?;
+                if cap == 0 {
+                    return Err("read_line capacity must leave room for the terminator".to_string());
+                }
+                self.output.flush().map_err(io_error)?;
+                let length = match self.read_input_line()? {
+                    Some(line) => {
+                        // Longer lines are truncated to fit the buffer
+                        let mut bytes = line.into_bytes();
+                        bytes.truncate(cap - 1);
+                        let length = bytes.len() as i32;
+                        bytes.push(0);
+                        self.store_bytes_to_heap(buf_addr, bytes)?;
+                        length
+                    }
+                    None => -1,
+                };
+                self.set_variable(&dest, Value::I32(length))?;
             }
 
             OpCode::PushArg { var } => {
@@ -307,36 +701,69 @@ impl VM {
                 // Labels are no-ops during execution
             }
 
-            OpCode::Jmp { label } => {
-                self.ip = *self
-                    .program
-                    .labels
-                    .get(&label)
-                    .ok_or_else(|| format!("Unknown label: {}", label))?;
-            }
+            OpCode::Jmp { label } => self.jump_to(&label)?,
 
             OpCode::Jz { var, label } => {
                 let val = self.get_variable(&var)?;
                 if val.is_zero() {
-                    self.ip = *self
-                        .program
-                        .labels
-                        .get(&label)
-                        .ok_or_else(|| format!("Unknown label: {}", label))?;
+                    self.jump_to(&label)?;
                 }
             }
 
             OpCode::Jnz { var, label } => {
                 let val = self.get_variable(&var)?;
                 if !val.is_zero() {
-                    self.ip = *self
-                        .program
-                        .labels
-                        .get(&label)
-                        .ok_or_else(|| format!("Unknown label: {}", label))?;
+                    self.jump_to(&label)?;
+                }
+            }
+
+            OpCode::Switch { value, default, targets } => {
+                let value = self.resolve_operand(&value)?;
+                let target = value
+                    .as_usize()
+                    .ok()
+                    .and_then(|index| targets.get(index))
+                    .unwrap_or(&default);
+                self.jump_to(target)?;
+            }
+
+            OpCode::Jeq { left, right, label } => {
+                let l = self.resolve_operand(&left)?;
+                let r = self.resolve_operand(&right)?;
+                if l.equals(&r) {
+                    self.jump_to(&label)?;
+                }
+            }
+
+            OpCode::Jne { left, right, label } => {
+                let l = self.resolve_operand(&left)?;
+                let r = self.resolve_operand(&right)?;
+                if !l.equals(&r) {
+                    self.jump_to(&label)?;
+                }
+            }
+
+            OpCode::Jlt { left, right, label } => compare_jump!(self, left, right, label, lt),
+            OpCode::Jle { left, right, label } => compare_jump!(self, left, right, label, le),
+            OpCode::Jgt { left, right, label } => compare_jump!(self, left, right, label, gt),
+            OpCode::Jge { left, right, label } => compare_jump!(self, left, right, label, ge),
+
+            OpCode::Assert { cond, message } => {
+                if self.resolve_operand(&cond)?.is_zero() {
+                    return Err(self.raise_trap(TrapKind::AssertionFailed(message)));
                 }
             }
 
+            OpCode::Trap { code } => {
+                let code = match self.resolve_operand(&code)?.cast(DataType::I32)? {
+                    Value::I32(code) => code,
+                    other => return Err(format!("Invalid trap code: {:?}", other)),
+                };
+                return Err(self.raise_trap(TrapKind::Code(code)));
+            }
+
+            OpCode::Unreachable => return Err(self.raise_trap(TrapKind::Unreachable)),
+
             OpCode::FuncBegin { name, .. } => {
                 // Skip to end of function if not being called
                 let func = self
@@ -359,11 +786,29 @@ impl VM {
                     .ok_or_else(|| format!("Unknown function: {}", func))?
                     .clone();
 
-                // Get argument values before switching frames
+                if func_def.return_type == DataType::Void && result.is_some() {
+                    return Err(format!("Function '{}' returns void and has no result to store", func));
+                }
+
+                // Typed functions get their parameters bound as locals; untyped ones pop_arg
+                let mut locals = HashMap::new();
+                let mut local_types = HashMap::new();
                 let mut arg_values = Vec::new();
-                for arg in args {
-                    let val = self.resolve_operand(&arg)?;
-                    arg_values.push(val);
+                if func_def.typed {
+                    locals = self.bind_parameters(&func_def, &args)?;
+                    local_types.extend(func_def.parameters.iter().map(|param| (param.name.clone(), param.dtype)));
+                    let dest_type = result.as_deref().and_then(|dest| self.declared_type(dest));
+                    if let Some(dest_type) = dest_type.filter(|dtype| *dtype != func_def.return_type) {
+                        return Err(format!(
+                            "Function '{}' returns {:?}, but the result is stored in a {:?} variable",
+                            func, func_def.return_type, dest_type
+                        ));
+                    }
+                } else {
+                    for arg in args {
+                        let val = self.resolve_operand(&arg)?;
+                        arg_values.push(val);
+                    }
                 }
 
                 // Push current frame with return destination
@@ -372,7 +817,8 @@ impl VM {
                     CallFrame {
                         function_name: func.clone(),
                         return_ip: 0,
-                        locals: HashMap::new(),
+                        locals,
+                        local_types,
                         return_dest: None,
                         args: arg_values,
                     },
@@ -385,7 +831,19 @@ impl VM {
             }
 
             OpCode::Return { value } => {
-                let ret_val = value.map(|op| self.resolve_operand(&op)).transpose()?;
+                let mut ret_val = value.as_ref().map(|op| self.resolve_operand(op)).transpose()?;
+
+                // Typed functions always hand back their declared return type
+                if let (Some(val), Some(func)) = (&ret_val, self.program.functions.get(&self.current_frame.function_name)) {
+                    if func.return_type == DataType::Void {
+                        return Err(format!("Void function '{}' cannot return a value", func.name));
+                    }
+                    if func.typed {
+                        ret_val = Some(val.cast(func.return_type).map_err(|e| {
+                            format!("Function '{}' returns {:?}: {}", func.name, func.return_type, e)
+                        })?);
+                    }
+                }
 
                 if let Some(frame) = self.call_stack.pop() {
                     let return_dest = frame.return_dest.clone();
@@ -411,41 +869,11 @@ impl VM {
                 }
             }
 
-            OpCode::Sqrt { dest, source } => {
-                let val = self.resolve_operand(&source)?;
-                let result = match val {
-                    Value::F32(v) => Value::F32(v.sqrt()),
-                    Value::F64(v) => Value::F64(v.sqrt()),
-                    _ => return Err(format!("sqrt requires float type, got {:?}", val)),
-                };
-                self.set_variable(&dest, result)?;
-            }
+            OpCode::Sqrt { dest, source } => float_unary_op!(self, dest, source, "sqrt", sqrt),
 
-            OpCode::Pow { dest, base, exp } => {
-                let base_val = self.resolve_operand(&base)?;
-                let exp_val = self.resolve_operand(&exp)?;
-                let result = match (base_val, exp_val) {
-                    (Value::F32(b), Value::F32(e)) => Value::F32(b.powf(e)),
-                    (Value::F64(b), Value::F64(e)) => Value::F64(b.powf(e)),
-                    (Value::I32(b), Value::I32(e)) => {
-                        Value::I32((b as f64).powi(e) as i32)
-                    }
-                    _ => return Err("pow requires matching numeric types".to_string()),
-                };
-                self.set_variable(&dest, result)?;
-            }
+            OpCode::Pow { dest, base, exp } => binary_op!(self, dest, base, exp, pow),
 
-            OpCode::Abs { dest, source } => {
-                let val = self.resolve_operand(&source)?;
-                let result = match val {
-                    Value::I32(v) => Value::I32(v.abs()),
-                    Value::I64(v) => Value::I64(v.abs()),
-                    Value::F32(v) => Value::F32(v.abs()),
-                    Value::F64(v) => Value::F64(v.abs()),
-                    _ => return Err(format!("abs requires numeric type, got {:?}", val)),
-                };
-                self.set_variable(&dest, result)?;
-            }
+            OpCode::Abs { dest, source } => unary_op!(self, dest, source, abs),
 
             OpCode::Min { dest, a, b } => {
                 let a_val = self.resolve_operand(&a)?;
@@ -455,6 +883,7 @@ impl VM {
                     (Value::I64(x), Value::I64(y)) => Value::I64(x.min(y)),
                     (Value::F32(x), Value::F32(y)) => Value::F32(x.min(y)),
                     (Value::F64(x), Value::F64(y)) => Value::F64(x.min(y)),
+                    (Value::Vector(x), Value::Vector(y)) => Value::Vector(x.min(&y)?),
                     _ => return Err("min requires matching numeric types".to_string()),
                 };
                 self.set_variable(&dest, result)?;
@@ -468,44 +897,596 @@ impl VM {
                     (Value::I64(x), Value::I64(y)) => Value::I64(x.max(y)),
                     (Value::F32(x), Value::F32(y)) => Value::F32(x.max(y)),
                     (Value::F64(x), Value::F64(y)) => Value::F64(x.max(y)),
+                    (Value::Vector(x), Value::Vector(y)) => Value::Vector(x.max(&y)?),
                     _ => return Err("max requires matching numeric types".to_string()),
                 };
                 self.set_variable(&dest, result)?;
             }
 
-            OpCode::Sin { dest, source } => {
-                let val = self.resolve_operand(&source)?;
-                let result = match val {
-                    Value::F32(v) => Value::F32(v.sin()),
-                    Value::F64(v) => Value::F64(v.sin()),
-                    _ => return Err(format!("sin requires float type, got {:?}", val)),
+            OpCode::Sin { dest, source } => float_unary_op!(self, dest, source, "sin", sin),
+            OpCode::Cos { dest, source } => float_unary_op!(self, dest, source, "cos", cos),
+            OpCode::Tan { dest, source } => float_unary_op!(self, dest, source, "tan", tan),
+            OpCode::Asin { dest, source } => float_unary_op!(self, dest, source, "asin", asin),
+            OpCode::Acos { dest, source } => float_unary_op!(self, dest, source, "acos", acos),
+            OpCode::Atan { dest, source } => float_unary_op!(self, dest, source, "atan", atan),
+            OpCode::Atan2 { dest, y, x } => float_binary_op!(self, dest, y, x, "atan2", atan2),
+            OpCode::Hypot { dest, x, y } => float_binary_op!(self, dest, x, y, "hypot", hypot),
+
+            // Rounding; `round` takes halfway cases away from zero
+            OpCode::Floor { dest, source } => float_unary_op!(self, dest, source, "floor", floor),
+            OpCode::Ceil { dest, source } => float_unary_op!(self, dest, source, "ceil", ceil),
+            OpCode::Round { dest, source } => float_unary_op!(self, dest, source, "round", round),
+            OpCode::Trunc { dest, source } => float_unary_op!(self, dest, source, "trunc", trunc),
+
+            OpCode::Exp { dest, source } => float_unary_op!(self, dest, source, "exp", exp),
+            OpCode::Ln { dest, source } => float_unary_op!(self, dest, source, "ln", ln),
+            OpCode::Log2 { dest, source } => float_unary_op!(self, dest, source, "log2", log2),
+            OpCode::Log10 { dest, source } => float_unary_op!(self, dest, source, "log10", log10),
+
+            OpCode::Fma { dest, a, b, c } => {
+                let a = self.resolve_operand(&a)?;
+                let b = self.resolve_operand(&b)?;
+                let c = self.resolve_operand(&c)?;
+                // Single rounding step, so a * b + c is exact before rounding
+                let result = match (a, b, c) {
+                    (Value::F32(x), Value::F32(y), Value::F32(z)) => Value::F32(x.mul_add(y, z)),
+                    (Value::F64(x), Value::F64(y), Value::F64(z)) => Value::F64(x.mul_add(y, z)),
+                    _ => return Err("fma requires matching float types".to_string()),
                 };
                 self.set_variable(&dest, result)?;
             }
 
-            OpCode::Cos { dest, source } => {
-                let val = self.resolve_operand(&source)?;
-                let result = match val {
-                    Value::F32(v) => Value::F32(v.cos()),
-                    Value::F64(v) => Value::F64(v.cos()),
-                    _ => return Err(format!("cos requires float type, got {:?}", val)),
+            OpCode::CopySign { dest, magnitude, sign } => {
+                float_binary_op!(self, dest, magnitude, sign, "copysign", copysign)
+            }
+
+            OpCode::IsNan { dest, source } => float_class_op!(self, dest, source, "isnan", is_nan),
+            OpCode::IsInf { dest, source } => float_class_op!(self, dest, source, "isinf", is_infinite),
+            OpCode::IsFinite { dest, source } => float_class_op!(self, dest, source, "isfinite", is_finite),
+
+            OpCode::VecSplat { dest, value, dtype } => {
+                let shape = match dtype {
+                    DataType::Vector(shape) => shape,
+                    other => return Err(format!("vsplat requires a vector type, got {:?}", other)),
                 };
-                self.set_variable(&dest, result)?;
+                let value = self.resolve_operand(&value)?;
+                self.set_variable(&dest, Value::Vector(Vector::splat(shape, &value)?))?;
+            }
+
+            OpCode::VecExtract { dest, vector, lane } => {
+                let vector = self.resolve_vector(&vector, "vextract")?;
+                let lane = self.resolve_operand(&lane)?.as_usize()?;
+                let value = vector.lane(lane)?;
+                self.set_variable(&dest, value)?;
+            }
+
+            OpCode::VecInsert { dest, vector, lane, value } => {
+                let vector = self.resolve_vector(&vector, "vinsert")?;
+                let lane = self.resolve_operand(&lane)?.as_usize()?;
+                let value = self.resolve_operand(&value)?;
+                self.set_variable(&dest, Value::Vector(vector.with_lane(lane, &value)?))?;
+            }
+
+            OpCode::VecShuffle { dest, a, b, lanes } => {
+                let a = self.resolve_vector(&a, "vshuffle")?;
+                let b = self.resolve_vector(&b, "vshuffle")?;
+                self.set_variable(&dest, Value::Vector(Vector::shuffle(&a, &b, &lanes)?))?;
+            }
+
+            OpCode::VecEq { dest, left, right } => vector_compare_op!(self, dest, left, right, Eq),
+            OpCode::VecNe { dest, left, right } => vector_compare_op!(self, dest, left, right, Ne),
+            OpCode::VecLt { dest, left, right } => vector_compare_op!(self, dest, left, right, Lt),
+            OpCode::VecLe { dest, left, right } => vector_compare_op!(self, dest, left, right, Le),
+            OpCode::VecGt { dest, left, right } => vector_compare_op!(self, dest, left, right, Gt),
+            OpCode::VecGe { dest, left, right } => vector_compare_op!(self, dest, left, right, Ge),
+
+            OpCode::VecSelect { dest, mask, a, b } => {
+                let mask = self.resolve_vector(&mask, "vselect")?;
+                let a = self.resolve_vector(&a, "vselect")?;
+                let b = self.resolve_vector(&b, "vselect")?;
+                self.set_variable(&dest, Value::Vector(Vector::select(&mask, &a, &b)?))?;
+            }
+
+            OpCode::StrLen { dest, source } => {
+                let text = self.resolve_str(&source, "str_len")?;
+                self.set_variable(&dest, Value::I32(text.chars().count() as i32))?;
+            }
+
+            OpCode::StrConcat { dest, left, right } => {
+                let left = self.resolve_str(&left, "str_concat")?;
+                let right = self.resolve_str(&right, "str_concat")?;
+                self.set_variable(&dest, Value::Str(format!("{}{}", left, right).into()))?;
+            }
+
+            OpCode::StrSub { dest, source, start, len } => {
+                let text = self.resolve_str(&source, "str_sub")?;
+                let start = self.resolve_operand(&start)?.as_usize()?;
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                // Byte offsets of every character boundary, including the end
+                let bounds: Vec<usize> = text.char_indices().map(|(i, _)| i).chain([text.len()]).collect();
+                let end = start.checked_add(len).filter(|end| *end < bounds.len()).ok_or_else(|| {
+                    format!("str_sub range {}..{}+{} out of bounds for length {}", start, start, len, bounds.len() - 1)
+                })?;
+                self.set_variable(&dest, Value::Str(text[bounds[start]..bounds[end]].into()))?;
             }
 
-            OpCode::Tan { dest, source } => {
-                let val = self.resolve_operand(&source)?;
-                let result = match val {
-                    Value::F32(v) => Value::F32(v.tan()),
-                    Value::F64(v) => Value::F64(v.tan()),
-                    _ => return Err(format!("tan requires float type, got {:?}", val)),
+            OpCode::StrFind { dest, haystack, needle } => {
+                let haystack = self.resolve_str(&haystack, "str_find")?;
+                let needle = self.resolve_str(&needle, "str_find")?;
+                let index = match haystack.find(&*needle) {
+                    Some(byte) => haystack[..byte].chars().count() as i32,
+                    None => -1,
                 };
-                self.set_variable(&dest, result)?;
+                self.set_variable(&dest, Value::I32(index))?;
+            }
+
+            OpCode::StrCmp { dest, left, right } => {
+                let left = self.resolve_str(&left, "str_cmp")?;
+                let right = self.resolve_str(&right, "str_cmp")?;
+                self.set_variable(&dest, Value::I32(left.cmp(&right) as i32))?;
+            }
+
+            OpCode::StrToInt { dest, source } => {
+                let text = self.resolve_str(&source, "str_to_int")?;
+                let value = Value::parse_as(&text, DataType::I64)?;
+                self.set_variable(&dest, value)?;
+            }
+
+            OpCode::IntToStr { dest, source } => {
+                let value = self.resolve_operand(&source)?;
+                let text = match value {
+                    Value::I8(v) => v.to_string(),
+                    Value::I16(v) => v.to_string(),
+                    Value::I32(v) => v.to_string(),
+                    Value::I64(v) => v.to_string(),
+                    Value::I128(v) => v.to_string(),
+                    Value::U8(v) => v.to_string(),
+                    Value::U16(v) => v.to_string(),
+                    Value::U32(v) => v.to_string(),
+                    Value::U64(v) => v.to_string(),
+                    Value::U128(v) => v.to_string(),
+                    other => return Err(format!("int_to_str requires an integer, got {:?}", other)),
+                };
+                self.set_variable(&dest, Value::Str(text.into()))?;
+            }
+
+            OpCode::FloatToStr { dest, source } => {
+                let text = match self.resolve_operand(&source)? {
+                    Value::F32(v) => v.to_string(),
+                    Value::F64(v) => v.to_string(),
+                    other => return Err(format!("float_to_str requires a float, got {:?}", other)),
+                };
+                self.set_variable(&dest, Value::Str(text.into()))?;
+            }
+
+            OpCode::PtrToStr { dest, ptr } => {
+                let addr = self.resolve_operand(&ptr)?.as_usize()?;
+                let bytes = self.read_c_string_at(addr)?;
+                let text = String::from_utf8(bytes).map_err(|_| format!("ptr_to_str: invalid UTF-8 at {:#x}", addr))?;
+                self.set_variable(&dest, Value::Str(text.into()))?;
+            }
+
+            OpCode::StrToPtr { dest, source } => {
+                let text = self.resolve_str(&source, "str_to_ptr")?;
+                let addr = self.allocate_c_string(&text)?;
+                self.set_variable(&dest, Value::Ptr(addr))?;
+            }
+
+            OpCode::ArrayNew { dest } => {
+                let obj = self.new_object(Object::Array(Vec::new()));
+                self.set_variable(&dest, Value::Ref(obj))?;
+            }
+
+            OpCode::MapNew { dest } => {
+                let obj = self.new_object(Object::Map(Map::default()));
+                self.set_variable(&dest, Value::Ref(obj))?;
+            }
+
+            OpCode::ArrayPush { array, value } => {
+                let obj = self.resolve_ref(&array, "array_push")?;
+                let value = self.resolve_operand(&value)?;
+                match self.objects.get_mut(obj)? {
+                    Object::Array(items) => items.push(value),
+                    other => return Err(format!("array_push requires an array, got a {}", other.kind())),
+                }
+            }
+
+            OpCode::ArrayPop { dest, array } => {
+                let obj = self.resolve_ref(&array, "array_pop")?;
+                let value = match self.objects.get_mut(obj)? {
+                    Object::Array(items) => items.pop().ok_or_else(|| "array_pop on an empty array".to_string())?,
+                    other => return Err(format!("array_pop requires an array, got a {}", other.kind())),
+                };
+                self.set_variable(&dest, value)?;
+            }
+
+            OpCode::MapHas { dest, map, key } => {
+                let obj = self.resolve_ref(&map, "map_has")?;
+                let key = self.resolve_str(&key, "map_has")?;
+                let found = match self.objects.get(obj)? {
+                    Object::Map(map) => map.contains_key(&key),
+                    other => return Err(format!("map_has requires a map, got a {}", other.kind())),
+                };
+                self.set_variable(&dest, Value::Bool(found))?;
+            }
+
+            OpCode::MapKey { dest, map, index } => {
+                let obj = self.resolve_ref(&map, "map_key")?;
+                let index = self.resolve_operand(&index)?.as_usize()?;
+                let key = match self.objects.get(obj)? {
+                    Object::Map(map) => map
+                        .key_at(index)
+                        .cloned()
+                        .ok_or_else(|| format!("map_key index {} out of bounds for length {}", index, map.len()))?,
+                    other => return Err(format!("map_key requires a map, got a {}", other.kind())),
+                };
+                self.set_variable(&dest, Value::Str(key))?;
+            }
+
+            OpCode::ObjLen { dest, object } => {
+                let obj = self.resolve_ref(&object, "obj_len")?;
+                let len = self.objects.get(obj)?.len();
+                self.set_variable(&dest, Value::I32(len as i32))?;
+            }
+
+            OpCode::ObjGet { dest, object, key } => {
+                let obj = self.resolve_ref(&object, "obj_get")?;
+                let key = self.resolve_operand(&key)?;
+                let value = match (self.objects.get(obj)?, &key) {
+                    (Object::Array(items), _) => {
+                        let index = key.as_usize()?;
+                        items
+                            .get(index)
+                            .cloned()
+                            .ok_or_else(|| format!("Array index {} out of bounds for length {}", index, items.len()))?
+                    }
+                    (Object::Map(map), Value::Str(key)) => {
+                        map.get(key).cloned().ok_or_else(|| format!("Key not found: {:?}", key))?
+                    }
+                    (Object::Map(_), other) => return Err(format!("Map keys must be strings, got {:?}", other)),
+                };
+                self.set_variable(&dest, value)?;
+            }
+
+            OpCode::ObjSet { object, key, value } => {
+                let obj = self.resolve_ref(&object, "obj_set")?;
+                let key = self.resolve_operand(&key)?;
+                let value = self.resolve_operand(&value)?;
+                match (self.objects.get_mut(obj)?, key) {
+                    (Object::Array(items), key) => {
+                        let index = key.as_usize()?;
+                        let len = items.len();
+                        let slot = items
+                            .get_mut(index)
+                            .ok_or_else(|| format!("Array index {} out of bounds for length {}", index, len))?;
+                        *slot = value;
+                    }
+                    (Object::Map(map), Value::Str(key)) => map.insert(key, value),
+                    (Object::Map(_), other) => return Err(format!("Map keys must be strings, got {:?}", other)),
+                }
             }
 
             OpCode::Print { var } => {
                 let val = self.get_variable(&var)?;
-                println!("{}: {:?}", var, val);
+                match val {
+                    Value::Bool(b) => writeln!(self.output, "{}: {}", var, b),
+                    Value::Char(c) => writeln!(self.output, "{}: {:?}", var, c),
+                    Value::Vector(v) => writeln!(self.output, "{}: {}", var, v),
+                    Value::Str(s) => writeln!(self.output, "{}: {}", var, s),
+                    Value::Ref(obj) if obj.is_null() => writeln!(self.output, "{}: null", var),
+                    Value::Ref(obj) => {
+                        let object = self.objects.get(obj)?;
+                        writeln!(self.output, "{}: {}[{}]", var, object.kind(), object.len())
+                    }
+                    _ => writeln!(self.output, "{}: {:?}", var, val),
+                }
+                .map_err(io_error)?;
+            }
+
+            OpCode::PrintStr { ptr } => {
+                let bytes = self.read_c_string(&ptr)?;
+                self.output.write_all(&bytes).map_err(io_error)?;
+            }
+
+            OpCode::EPrintStr { ptr } => {
+                let bytes = self.read_c_string(&ptr)?;
+                self.error_output.write_all(&bytes).map_err(io_error)?;
+            }
+
+            OpCode::Printf { fmt, args } => {
+                let fmt = self.read_c_string(&fmt)?;
+                let values = args
+                    .iter()
+                    .map(|arg| self.resolve_operand(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let text = self.format_printf(&fmt, &values)?;
+                self.output.write_all(&text).map_err(io_error)?;
+            }
+
+            OpCode::FileOpen { dest, path, mode } => {
+                let path = String::from_utf8_lossy(&self.read_c_string(&path)?).into_owned();
+                let mode = self.resolve_operand(&mode)?.as_usize()?;
+                let mut options = OpenOptions::new();
+                let resolved = match mode {
+                    FILE_MODE_READ => {
+                        options.read(true);
+                        self.capabilities.check_read(Path::new(&path))?
+                    }
+                    FILE_MODE_WRITE => {
+                        options.write(true).create(true).truncate(true);
+                        self.capabilities.check_write(Path::new(&path))?
+                    }
+                    FILE_MODE_APPEND => {
+                        options.append(true).create(true);
+                        self.capabilities.check_write(Path::new(&path))?
+                    }
+                    _ => {
+                        self.set_variable(&dest, Value::I32(ERR_BAD_ARGUMENT))?;
+                        return Ok(());
+                    }
+                };
+                let result = match options.open(resolved) {
+                    Ok(file) => {
+                        let handle = self.next_handle();
+                        self.files.insert(handle, file);
+                        handle
+                    }
+                    Err(e) => io_error_code(&e),
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::FileRead { dest, handle, buf, len } => {
+                let handle = self.resolve_handle(&handle)?;
+                let buf_addr = self.get_variable(&buf)?.as_usize()?;
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                // Bounds-check the destination before touching the file
+                self.load_bytes_from_heap(buf_addr, len)?;
+                let mut bytes = vec![0u8; len];
+                let result = match self.files.get_mut(&handle) {
+                    Some(file) => match file.read(&mut bytes) {
+                        Ok(count) => {
+                            bytes.truncate(count);
+                            self.store_bytes_to_heap(buf_addr, bytes)?;
+                            count as i32
+                        }
+                        Err(e) => io_error_code(&e),
+                    },
+                    None => ERR_BAD_HANDLE,
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::FileWrite { dest, handle, buf, len } => {
+                let handle = self.resolve_handle(&handle)?;
+                let buf_addr = self.get_variable(&buf)?.as_usize()?;
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                let bytes = self.load_bytes_from_heap(buf_addr, len)?;
+                let result = match self.files.get_mut(&handle) {
+                    Some(file) => match file.write_all(&bytes) {
+                        Ok(()) => len as i32,
+                        Err(e) => io_error_code(&e),
+                    },
+                    None => ERR_BAD_HANDLE,
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::FileSeek { dest, handle, offset, whence } => {
+                let handle = self.resolve_handle(&handle)?;
+                let offset = match self.resolve_operand(&offset)?.cast(DataType::I64)? {
+                    Value::I64(v) => v,
+                    other => return Err(format!("Invalid seek offset: {:?}", other)),
+                };
+                let target = match self.resolve_operand(&whence)?.as_usize()? {
+                    0 if offset >= 0 => Some(SeekFrom::Start(offset as u64)),
+                    1 => Some(SeekFrom::Current(offset)),
+                    2 => Some(SeekFrom::End(offset)),
+                    _ => None,
+                };
+                let result = match (self.files.get_mut(&handle), target) {
+                    (None, _) => ERR_BAD_HANDLE as i64,
+                    (Some(_), None) => ERR_BAD_ARGUMENT as i64,
+                    (Some(file), Some(target)) => match file.seek(target) {
+                        Ok(position) => position as i64,
+                        Err(e) => io_error_code(&e) as i64,
+                    },
+                };
+                self.set_variable(&dest, Value::I64(result))?;
+            }
+
+            OpCode::FileClose { dest, handle } => {
+                let handle = self.resolve_handle(&handle)?;
+                let result = match self.files.remove(&handle) {
+                    Some(_) => 0,
+                    None => ERR_BAD_HANDLE,
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::TcpConnect { dest, addr } => {
+                let addr = String::from_utf8_lossy(&self.read_c_string(&addr)?).into_owned();
+                self.capabilities.check_net(&addr)?;
+                let result = match TcpStream::connect(&addr) {
+                    Ok(stream) => self.add_socket(Socket::Stream(stream)),
+                    Err(e) => io_error_code(&e),
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::TcpListen { dest, addr } => {
+                let addr = String::from_utf8_lossy(&self.read_c_string(&addr)?).into_owned();
+                self.capabilities.check_net(&addr)?;
+                let result = match TcpListener::bind(&addr) {
+                    Ok(listener) => self.add_socket(Socket::Listener(listener)),
+                    Err(e) => io_error_code(&e),
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::TcpAccept { dest, handle } => {
+                let handle = self.resolve_handle(&handle)?;
+                let accepted = match self.sockets.get(&handle) {
+                    Some(Socket::Listener(listener)) => listener.accept().and_then(|(stream, _)| {
+                        // Accepted connections start blocking regardless of the listener
+                        stream.set_nonblocking(false)?;
+                        Ok(stream)
+                    }),
+                    _ => {
+                        self.set_variable(&dest, Value::I32(ERR_BAD_HANDLE))?;
+                        return Ok(());
+                    }
+                };
+                let result = match accepted {
+                    Ok(stream) => self.add_socket(Socket::Stream(stream)),
+                    Err(e) => io_error_code(&e),
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::TcpSend { dest, handle, buf, len } => {
+                let handle = self.resolve_handle(&handle)?;
+                let buf_addr = self.get_variable(&buf)?.as_usize()?;
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                let bytes = self.load_bytes_from_heap(buf_addr, len)?;
+                let result = match self.sockets.get_mut(&handle) {
+                    // May send fewer bytes than requested, like send(2)
+                    Some(Socket::Stream(stream)) => match stream.write(&bytes) {
+                        Ok(count) => count as i32,
+                        Err(e) => io_error_code(&e),
+                    },
+                    _ => ERR_BAD_HANDLE,
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::TcpRecv { dest, handle, buf, len } => {
+                let handle = self.resolve_handle(&handle)?;
+                let buf_addr = self.get_variable(&buf)?.as_usize()?;
+                let len = self.resolve_operand(&len)?.as_usize()?;
+                self.load_bytes_from_heap(buf_addr, len)?;
+                let mut bytes = vec![0u8; len];
+                let result = match self.sockets.get_mut(&handle) {
+                    Some(Socket::Stream(stream)) => match stream.read(&mut bytes) {
+                        Ok(count) => {
+                            bytes.truncate(count);
+                            self.store_bytes_to_heap(buf_addr, bytes)?;
+                            count as i32
+                        }
+                        Err(e) => io_error_code(&e),
+                    },
+                    _ => ERR_BAD_HANDLE,
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::TcpClose { dest, handle } => {
+                let handle = self.resolve_handle(&handle)?;
+                let result = match self.sockets.remove(&handle) {
+                    Some(_) => 0,
+                    None => ERR_BAD_HANDLE,
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::TcpNonblock { dest, handle, enabled } => {
+                let handle = self.resolve_handle(&handle)?;
+                let enabled = !self.resolve_operand(&enabled)?.is_zero();
+                let result = match self.sockets.get(&handle) {
+                    Some(socket) => match socket.set_nonblocking(enabled) {
+                        Ok(()) => 0,
+                        Err(e) => io_error_code(&e),
+                    },
+                    None => ERR_BAD_HANDLE,
+                };
+                self.set_variable(&dest, Value::I32(result))?;
+            }
+
+            OpCode::ReadKey { dest, wait } => {
+                self.output.flush().map_err(io_error)?;
+                let key = if wait {
+                    self.console.read_key(&mut *self.input)
+                } else {
+                    self.console.poll_key(&mut *self.input)
+                };
+                let key = key.map_err(io_error)?.map_or(-1, i32::from);
+                self.set_variable(&dest, Value::I32(key))?;
+            }
+
+            OpCode::SetCursor { row, col } => {
+                let row = self.resolve_coordinate(&row)?;
+                let col = self.resolve_coordinate(&col)?;
+                // Screen control must not overtake text still buffered in the output
+                self.output.flush().map_err(io_error)?;
+                self.console.move_cursor(row, col).map_err(io_error)?;
+            }
+
+            OpCode::ClearScreen => {
+                self.output.flush().map_err(io_error)?;
+                self.console.clear().map_err(io_error)?;
+            }
+
+            OpCode::SetColor { fg, bg } => {
+                let fg = self.resolve_color(&fg)?;
+                let bg = self.resolve_color(&bg)?;
+                self.output.flush().map_err(io_error)?;
+                self.console.set_color(fg, bg).map_err(io_error)?;
+            }
+
+            OpCode::Clock { dest } => {
+                let now = i64::try_from(self.system.now_ns()).unwrap_or(i64::MAX);
+                self.set_variable(&dest, Value::I64(now))?;
+            }
+
+            OpCode::Sleep { millis } => {
+                let millis = self.resolve_operand(&millis)?.as_usize()?;
+                self.output.flush().map_err(io_error)?;
+                self.system.sleep(Duration::from_millis(millis as u64));
+            }
+
+            OpCode::Srand { seed } => {
+                let seed = match self.resolve_operand(&seed)?.cast(DataType::U64)? {
+                    Value::U64(seed) => seed,
+                    other => return Err(format!("Invalid seed: {:?}", other)),
+                };
+                self.system.seed(seed);
+            }
+
+            OpCode::Rand { dest, bound } => {
+                let value = match bound {
+                    Some(bound) => {
+                        let bound = self.resolve_operand(&bound)?;
+                        match bound.as_usize() {
+                            Ok(b) if b > 0 && b <= i32::MAX as usize => {
+                                self.system.next_below(b as u64) as i32
+                            }
+                            _ => return Err(format!("rand bound must be between 1 and {}, got {:?}", i32::MAX, bound)),
+                        }
+                    }
+                    None => (self.system.next_u64() >> 33) as i32,
+                };
+                self.set_variable(&dest, Value::I32(value))?;
+            }
+
+            OpCode::GetEnv { dest, name } => {
+                let name = String::from_utf8_lossy(&self.read_c_string(&name)?).into_owned();
+                self.capabilities.check_env(&name)?;
+                // Unset variables yield a null pointer
+                let ptr = match std::env::var(&name) {
+                    Ok(value) => self.allocate_c_string(&value)?,
+                    Err(_) => 0,
+                };
+                self.set_variable(&dest, Value::Ptr(ptr))?;
+            }
+
+            OpCode::PrintChar { value } => {
+                let bytes = self.resolve_char(&value)?;
+                self.output.write_all(&bytes).map_err(io_error)?;
+            }
+
+            OpCode::EPrintChar { value } => {
+                let bytes = self.resolve_char(&value)?;
+                self.error_output.write_all(&bytes).map_err(io_error)?;
             }
 
             OpCode::Exit { code: _ } => {
@@ -517,6 +1498,27 @@ impl VM {
         Ok(())
     }
 
+    fn resolve_ref(&self, operand: &Operand, name: &str) -> Result<ObjRef, String> {
+        match self.resolve_operand(operand)? {
+            Value::Ref(obj) => Ok(obj),
+            other => Err(format!("{} requires a reference, got {:?}", name, other)),
+        }
+    }
+
+    fn resolve_str(&self, operand: &Operand, name: &str) -> Result<Arc<str>, String> {
+        match self.resolve_operand(operand)? {
+            Value::Str(s) => Ok(s),
+            other => Err(format!("{} requires a string, got {:?}", name, other)),
+        }
+    }
+
+    fn resolve_vector(&self, operand: &Operand, name: &str) -> Result<Vector, String> {
+        match self.resolve_operand(operand)? {
+            Value::Vector(v) => Ok(v),
+            other => Err(format!("{} requires a vector, got {:?}", name, other)),
+        }
+    }
+
     fn resolve_operand(&self, operand: &Operand) -> Result<Value, String> {
         match operand {
             Operand::Variable(name) => self.get_variable(name),
@@ -526,6 +1528,309 @@ impl VM {
         }
     }
 
+    // Bytes of the NUL-terminated string `ptr` points at, without the terminator
+    fn read_c_string(&self, ptr: &str) -> Result<Vec<u8>, String> {
+        match self.get_variable(ptr)? {
+            // A string value stands in for a pointer to its text
+            Value::Str(s) => Ok(s.as_bytes().to_vec()),
+            value => self.read_c_string_at(value.as_usize()?),
+        }
+    }
+
+    fn read_c_string_at(&self, addr: usize) -> Result<Vec<u8>, String> {
+        let (base_addr, offset) = self.find_heap_allocation(addr)?;
+        let allocation = &self.heap[&base_addr][offset..];
+        let len = allocation
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| format!("Unterminated string at {:#x}", addr))?;
+        Ok(allocation[..len].to_vec())
+    }
+
+    // Expands `%[-0][width][.precision]conv` specifiers, where conv is one of
+    // d u x X f s c or %. Each conversion only accepts matching Value types.
+    fn format_printf(&self, fmt: &[u8], args: &[Value]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        let mut args = args.iter();
+        let mut i = 0;
+
+        while i < fmt.len() {
+            if fmt[i] != b'%' {
+                out.push(fmt[i]);
+                i += 1;
+                continue;
+            }
+            i += 1;
+
+            let (mut left_align, mut zero_pad) = (false, false);
+            while let Some(flag @ (b'-' | b'0')) = fmt.get(i) {
+                if *flag == b'-' {
+                    left_align = true;
+                } else {
+                    zero_pad = true;
+                }
+                i += 1;
+            }
+            let width = read_decimal(fmt, &mut i);
+            let precision = if fmt.get(i) == Some(&b'.') {
+                i += 1;
+                Some(read_decimal(fmt, &mut i).unwrap_or(0))
+            } else {
+                None
+            };
+            let conv = *fmt
+                .get(i)
+                .ok_or_else(|| "printf: incomplete format specifier".to_string())?;
+            i += 1;
+
+            if conv == b'%' {
+                out.push(b'%');
+                continue;
+            }
+
+            let arg = args
+                .next()
+                .ok_or_else(|| format!("printf: missing argument for %{}", conv as char))?;
+            let mismatch = || format!("printf: %{} cannot format {:?}", conv as char, arg);
+
+            // (sign, body, numeric) - numeric fields honour zero padding
+            let (sign, body, numeric) = match conv {
+                b'd' => {
+                    let v = match arg {
+                        Value::I8(v) => *v as i128,
+                        Value::I16(v) => *v as i128,
+                        Value::I32(v) => *v as i128,
+                        Value::I64(v) => *v as i128,
+                        Value::I128(v) => *v,
+                        _ => return Err(mismatch()),
+                    };
+                    let sign = if v < 0 { "-" } else { "" };
+                    (sign, min_digits(v.unsigned_abs().to_string(), precision), true)
+                }
+                b'u' => {
+                    let v = match arg {
+                        Value::U8(v) => *v as u128,
+                        Value::U16(v) => *v as u128,
+                        Value::U32(v) => *v as u128,
+                        Value::U64(v) => *v as u128,
+                        Value::U128(v) => *v,
+                        _ => return Err(mismatch()),
+                    };
+                    ("", min_digits(v.to_string(), precision), true)
+                }
+                b'x' | b'X' => {
+                    // Signed values print as their two's complement bit pattern
+                    let v = match arg {
+                        Value::I8(v) => *v as u8 as u128,
+                        Value::I16(v) => *v as u16 as u128,
+                        Value::I32(v) => *v as u32 as u128,
+                        Value::I64(v) => *v as u64 as u128,
+                        Value::I128(v) => *v as u128,
+                        Value::U8(v) => *v as u128,
+                        Value::U16(v) => *v as u128,
+                        Value::U32(v) => *v as u128,
+                        Value::U64(v) => *v as u128,
+                        Value::U128(v) => *v,
+                        Value::Ptr(v) => *v as u128,
+                        _ => return Err(mismatch()),
+                    };
+                    let digits = if conv == b'x' { format!("{:x}", v) } else { format!("{:X}", v) };
+                    ("", min_digits(digits, precision), true)
+                }
+                b'f' => {
+                    let v = match arg {
+                        Value::F32(v) => *v as f64,
+                        Value::F64(v) => *v,
+                        _ => return Err(mismatch()),
+                    };
+                    let sign = if v.is_sign_negative() && !v.is_nan() { "-" } else { "" };
+                    (sign, format!("{:.*}", precision.unwrap_or(6), v.abs()), v.is_finite())
+                }
+                b's' => {
+                    let mut bytes = match arg {
+                        Value::Ptr(addr) => self.read_c_string_at(*addr)?,
+                        Value::Str(s) => s.as_bytes().to_vec(),
+                        _ => return Err(mismatch()),
+                    };
+                    if let Some(max) = precision {
+                        bytes.truncate(max);
+                    }
+                    ("", String::from_utf8_lossy(&bytes).into_owned(), false)
+                }
+                b'c' => {
+                    let ch = match arg {
+                        Value::Char(c) => *c,
+                        Value::F32(_) | Value::F64(_) | Value::Ptr(_) => return Err(mismatch()),
+                        _ => arg
+                            .as_usize()
+                            .ok()
+                            .and_then(|v| u8::try_from(v).ok())
+                            .ok_or_else(mismatch)? as char,
+                    };
+                    ("", ch.to_string(), false)
+                }
+                _ => return Err(format!("printf: unknown conversion %{}", conv as char)),
+            };
+
+            let len = sign.len() + body.chars().count();
+            let pad = width.unwrap_or(0).saturating_sub(len);
+            if left_align {
+                out.extend_from_slice(sign.as_bytes());
+                out.extend_from_slice(body.as_bytes());
+                out.extend(std::iter::repeat_n(b' ', pad));
+            } else if zero_pad && numeric {
+                out.extend_from_slice(sign.as_bytes());
+                out.extend(std::iter::repeat_n(b'0', pad));
+                out.extend_from_slice(body.as_bytes());
+            } else {
+                out.extend(std::iter::repeat_n(b' ', pad));
+                out.extend_from_slice(sign.as_bytes());
+                out.extend_from_slice(body.as_bytes());
+            }
+        }
+
+        if args.next().is_some() {
+            return Err("printf: more arguments than format specifiers".to_string());
+        }
+        Ok(out)
+    }
+
+    // Files and sockets share one handle space
+    fn next_handle(&mut self) -> i32 {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        handle
+    }
+
+    fn add_socket(&mut self, socket: Socket) -> i32 {
+        let handle = self.next_handle();
+        self.sockets.insert(handle, socket);
+        handle
+    }
+
+    fn resolve_handle(&self, operand: &Operand) -> Result<i32, String> {
+        match self.resolve_operand(operand)?.cast(DataType::I32)? {
+            Value::I32(handle) => Ok(handle),
+            other => Err(format!("Invalid file handle: {:?}", other)),
+        }
+    }
+
+    // A `char` is written as UTF-8; integer operands are a single raw byte
+    fn resolve_char(&self, operand: &Operand) -> Result<Vec<u8>, String> {
+        match self.resolve_operand(operand)? {
+            Value::Char(c) => Ok(c.encode_utf8(&mut [0; 4]).as_bytes().to_vec()),
+            value => match value.cast_exact(DataType::U8) {
+                Some(Value::U8(byte)) => Ok(vec![byte]),
+                _ => Err(format!("Character value out of byte range: {:?}", value)),
+            },
+        }
+    }
+
+    fn jump_to(&mut self, label: &str) -> Result<(), String> {
+        self.ip = *self
+            .program
+            .labels
+            .get(label)
+            .ok_or_else(|| format!("Unknown label: {}", label))?;
+        Ok(())
+    }
+
+    // Byte length of `count` elements of `dtype` for the vector loads and stores
+    fn vector_len(&self, count: &Operand, dtype: DataType) -> Result<usize, String> {
+        let size = Variable::size_of(dtype);
+        if size == 0 {
+            return Err(format!("Cannot transfer {:?} elements", dtype));
+        }
+        let count = self.resolve_operand(count)?.as_usize()?;
+        count
+            .checked_mul(size)
+            .ok_or_else(|| format!("Vector of {} {:?} elements is too large", count, dtype))
+    }
+
+    fn resolve_coordinate(&self, operand: &Operand) -> Result<u16, String> {
+        let value = self.resolve_operand(operand)?.as_usize()?;
+        u16::try_from(value).map_err(|_| format!("Cursor position out of range: {}", value))
+    }
+
+    // Palette index 0-15, or -1 for the terminal default
+    fn resolve_color(&self, operand: &Operand) -> Result<Option<u8>, String> {
+        match self.resolve_operand(operand)?.cast(DataType::I32)? {
+            Value::I32(-1) => Ok(None),
+            Value::I32(color @ 0..=15) => Ok(Some(color as u8)),
+            other => Err(format!("Invalid color: {:?} (expected 0-15 or -1)", other)),
+        }
+    }
+
+    // Next input line without its line ending, or None at end of input
+    fn read_input_line(&mut self) -> Result<Option<String>, String> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line).map_err(io_error)? == 0 {
+            return Ok(None);
+        }
+        let trimmed = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(trimmed);
+        Ok(Some(line))
+    }
+
+    fn resolve_type(&self, operand: &Operand) -> Result<DataType, String> {
+        match operand {
+            Operand::Type(DataType::Void) => Err("Cannot use void as an element type".to_string()),
+            Operand::Type(dtype) => Ok(*dtype),
+            other => Err(format!("Expected type operand, got {:?}", other)),
+        }
+    }
+
+    fn allocate_c_string(&mut self, text: &str) -> Result<usize, String> {
+        let mut bytes = text.as_bytes().to_vec();
+        bytes.push(0);
+        let addr = self.allocate(bytes.len())?;
+        self.store_bytes_to_heap(addr, bytes)?;
+        Ok(addr)
+    }
+
+    // Allocation is the only point where a collection can run, so every live
+    // reference is held by a global, local or argument at that moment
+    fn new_object(&mut self, object: Object) -> ObjRef {
+        if self.objects.should_collect() {
+            self.collect_garbage();
+        }
+        self.objects.alloc(object)
+    }
+
+    fn allocate(&mut self, size: usize) -> Result<usize, String> {
+        let addr = self.next_heap_addr;
+        self.next_heap_addr = addr
+            .checked_add(size)
+            .ok_or_else(|| format!("Heap exhausted allocating {} bytes", size))?;
+        // A huge request is a runtime error rather than an aborting OOM
+        let mut block = Vec::new();
+        block
+            .try_reserve_exact(size)
+            .map_err(|_| format!("Heap exhausted allocating {} bytes", size))?;
+        block.resize(size, 0);
+        self.heap.insert(addr, block);
+        Ok(addr)
+    }
+
+    fn resolve_address(&self, addr: &Address) -> Result<usize, String> {
+        let base = self.get_variable(&addr.base)?.as_usize()? as i64;
+        let index = match &addr.index {
+            Some(name) => match self.get_variable(name)?.cast(DataType::I64)? {
+                Value::I64(v) => v,
+                other => return Err(format!("Invalid index value: {:?}", other)),
+            },
+            None => 0,
+        };
+        index
+            .checked_mul(addr.scale)
+            .and_then(|scaled| scaled.checked_add(addr.offset))
+            .and_then(|displacement| base.checked_add(displacement))
+            .filter(|effective| *effective >= 0)
+            .map(|effective| effective as usize)
+            .ok_or_else(|| format!("Address overflow computing {}[{} * {} + {}]",
+                addr.base, addr.index.as_deref().unwrap_or("0"), addr.scale, addr.offset))
+    }
+
     fn get_variable(&self, name: &str) -> Result<Value, String> {
         self.current_frame
             .locals
@@ -535,6 +1840,48 @@ impl VM {
             .ok_or_else(|| format!("Unknown variable: {}", name))
     }
 
+    // Declared type of a variable. Locals bound by `pop_arg` have none and are
+    // unconstrained, as they are for the assembler.
+    fn declared_type(&self, name: &str) -> Option<DataType> {
+        if self.current_frame.locals.contains_key(name) {
+            return self.current_frame.local_types.get(name).copied();
+        }
+        self.program
+            .globals
+            .iter()
+            .find(|var| var.name == name)
+            .map(|var| var.dtype)
+    }
+
+    // Check call arguments against a typed function's parameters and bind them by name.
+    // Declared argument types were checked by the assembler; here values are converted.
+    fn bind_parameters(&self, func: &Function, args: &[Operand]) -> Result<HashMap<String, Value>, String> {
+        if args.len() != func.parameters.len() {
+            return Err(format!(
+                "Function '{}' expects {} argument(s), got {}",
+                func.name,
+                func.parameters.len(),
+                args.len()
+            ));
+        }
+
+        let mut locals = HashMap::new();
+        for (arg, param) in args.iter().zip(&func.parameters) {
+            let value = self.resolve_operand(arg)?;
+            let converted = match arg {
+                // Immediates must convert without loss, as the assembler requires
+                Operand::Immediate(_) => value.cast_exact(param.dtype).ok_or_else(|| {
+                    format!("Argument '{}' of '{}' expects {:?}, got {:?}", param.name, func.name, param.dtype, value)
+                })?,
+                _ => value.cast(param.dtype).map_err(|e| {
+                    format!("Argument '{}' of '{}' expects {:?}: {}", param.name, func.name, param.dtype, e)
+                })?,
+            };
+            locals.insert(param.name.clone(), converted);
+        }
+        Ok(locals)
+    }
+
     fn set_variable(&mut self, name: &str, value: Value) -> Result<(), String> {
         if self.current_frame.locals.contains_key(name) {
             self.current_frame.locals.insert(name.to_string(), value);
@@ -552,14 +1899,21 @@ impl VM {
             DataType::I16 => Value::I16(0),
             DataType::I32 => Value::I32(0),
             DataType::I64 => Value::I64(0),
+            DataType::I128 => Value::I128(0),
             DataType::U8 => Value::U8(0),
             DataType::U16 => Value::U16(0),
             DataType::U32 => Value::U32(0),
             DataType::U64 => Value::U64(0),
+            DataType::U128 => Value::U128(0),
             DataType::F32 => Value::F32(0.0),
             DataType::F64 => Value::F64(0.0),
             DataType::Ptr => Value::Ptr(0),
             DataType::Void => Value::I32(0),
+            DataType::Bool => Value::Bool(false),
+            DataType::Char => Value::Char('\0'),
+            DataType::Vector(shape) => Value::Vector(Vector::zero(shape)),
+            DataType::Str => Value::Str("".into()),
+            DataType::Ref => Value::Ref(ObjRef::NULL),
         }
     }
 
@@ -630,6 +1984,12 @@ impl VM {
                     bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
                 ])))
             }
+            DataType::I128 => {
+                if bytes.len() < 16 {
+                    return Err("Insufficient bytes for I128".to_string());
+                }
+                Ok(Value::I128(i128::from_le_bytes(bytes[..16].try_into().unwrap())))
+            }
             DataType::U8 => {
                 if bytes.is_empty() {
                     return Err("Insufficient bytes for U8".to_string());
@@ -658,6 +2018,12 @@ impl VM {
                     bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
                 ])))
             }
+            DataType::U128 => {
+                if bytes.len() < 16 {
+                    return Err("Insufficient bytes for U128".to_string());
+                }
+                Ok(Value::U128(u128::from_le_bytes(bytes[..16].try_into().unwrap())))
+            }
             DataType::F32 => {
                 if bytes.len() < 4 {
                     return Err("Insufficient bytes for F32".to_string());
@@ -682,6 +2048,16 @@ impl VM {
                     bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
                 ])))
             }
+            DataType::Bool => {
+                if bytes.is_empty() {
+                    return Err("Insufficient bytes for Bool".to_string());
+                }
+                Ok(Value::Bool(bytes[0] != 0))
+            }
+            DataType::Char => Value::from_le_bytes(bytes, DataType::Char),
+            DataType::Vector(_) => Value::from_le_bytes(bytes, dtype),
+            DataType::Str => Err("Strings have no memory image; use ptr_to_str".to_string()),
+            DataType::Ref => Err("References cannot be loaded from raw memory".to_string()),
             DataType::Void => Err("Cannot read Void type from memory".to_string()),
         }
     }
@@ -692,13 +2068,21 @@ impl VM {
             (Value::I16(v), DataType::I16) => Ok(v.to_le_bytes().to_vec()),
             (Value::I32(v), DataType::I32) => Ok(v.to_le_bytes().to_vec()),
             (Value::I64(v), DataType::I64) => Ok(v.to_le_bytes().to_vec()),
+            (Value::I128(v), DataType::I128) => Ok(v.to_le_bytes().to_vec()),
             (Value::U8(v), DataType::U8) => Ok(vec![*v]),
             (Value::U16(v), DataType::U16) => Ok(v.to_le_bytes().to_vec()),
             (Value::U32(v), DataType::U32) => Ok(v.to_le_bytes().to_vec()),
             (Value::U64(v), DataType::U64) => Ok(v.to_le_bytes().to_vec()),
+            (Value::U128(v), DataType::U128) => Ok(v.to_le_bytes().to_vec()),
             (Value::F32(v), DataType::F32) => Ok(v.to_le_bytes().to_vec()),
             (Value::F64(v), DataType::F64) => Ok(v.to_le_bytes().to_vec()),
             (Value::Ptr(v), DataType::Ptr) => Ok(v.to_le_bytes().to_vec()),
+            (Value::Bool(v), DataType::Bool) => Ok(vec![*v as u8]),
+            (Value::Char(v), DataType::Char) => Ok((*v as u32).to_le_bytes().to_vec()),
+            (Value::Vector(v), DataType::Vector(shape)) if v.shape() == shape => Ok(v.to_le_bytes()),
+            (Value::Str(_), _) | (_, DataType::Str) => Err("Strings have no memory image; use str_to_ptr".to_string()),
+            // The collector cannot trace raw bytes, so references stay out of them
+            (Value::Ref(_), _) | (_, DataType::Ref) => Err("References cannot be stored in raw memory".to_string()),
             _ => Err(format!(
                 "Type mismatch: cannot store {:?} as {:?}",
                 value, dtype
@@ -706,6 +2090,45 @@ impl VM {
         }
     }
 
+    // Records the trap for `trap()` and returns its error message; `ip` has already moved past it
+    fn raise_trap(&mut self, kind: TrapKind) -> String {
+        let ip = self.ip - 1;
+        let location = self
+            .program
+            .source_map
+            .as_ref()
+            .and_then(|source_map| source_map.instruction_locations.get(&ip))
+            .cloned();
+        let message = kind.to_string();
+        self.trap = Some(Trap { kind, ip, location });
+        message
+    }
+
+    /// The trap that ended the last `run`, if it was stopped by one.
+    pub fn trap(&self) -> Option<&Trap> {
+        self.trap.as_ref()
+    }
+
+    /// Run a full mark-and-sweep collection of the managed heap, tracing from
+    /// globals and every call frame. Returns the number of objects freed.
+    pub fn collect_garbage(&mut self) -> usize {
+        let frames = self.call_stack.iter().chain(std::iter::once(&self.current_frame));
+        let roots = self
+            .globals
+            .values()
+            .chain(frames.flat_map(|frame| frame.locals.values().chain(frame.args.iter())));
+        self.objects.collect(roots)
+    }
+
+    pub fn gc_stats(&self) -> GcStats {
+        self.objects.stats()
+    }
+
+    /// Look up a managed array or map, e.g. one held by a global.
+    pub fn object(&self, obj: ObjRef) -> Option<&Object> {
+        self.objects.get(obj).ok()
+    }
+
     fn format_error(&self, error: &str, ip: usize) -> String {
         if let Some(source_map) = &self.program.source_map {
             if let Some(location) = source_map.instruction_locations.get(&ip) {
@@ -723,6 +2146,52 @@ impl VM {
     }
 
     // Debug methods
+    /// Redirect program output (`print`, `print_str`, `print_char`).
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = output;
+    }
+
+    /// Redirect the stderr variants (`eprint_str`, `eprint_char`).
+    pub fn set_error_output(&mut self, output: Box<dyn Write>) {
+        self.error_output = output;
+    }
+
+    /// Replace the input source used by `input` and `read_line`.
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        self.input = input;
+    }
+
+    /// Replace the terminal used by the key, cursor and color instructions,
+    /// e.g. with a `MockConsole` in tests.
+    pub fn set_console(&mut self, console: Box<dyn Console>) {
+        self.console = console;
+    }
+
+    /// Replace the clock and RNG, e.g. with `SystemServices::deterministic`.
+    pub fn set_system(&mut self, system: SystemServices) {
+        self.system = system;
+    }
+
+    /// Pass command-line arguments to `main`, which receives them as
+    /// `pop_arg argc` (i32) and `pop_arg argv` (ptr to an array of string pointers).
+    pub fn set_args(&mut self, args: &[String]) -> Result<(), String> {
+        let mut pointers = Vec::with_capacity(args.len() * 8);
+        for arg in args {
+            let addr = self.allocate_c_string(arg)?;
+            pointers.extend_from_slice(&Value::Ptr(addr).to_le_bytes());
+        }
+        let argv = self.allocate(pointers.len())?;
+        self.store_bytes_to_heap(argv, pointers)?;
+        self.heap_types.insert(argv, (DataType::Ptr, args.len()));
+        self.current_frame.args = vec![Value::I32(args.len() as i32), Value::Ptr(argv)];
+        Ok(())
+    }
+
+    /// Set the host access policy. The default grants nothing.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
     pub fn set_debug_mode(&mut self, enabled: bool) {
         self.debug_mode = enabled;
     }
@@ -763,6 +2232,19 @@ impl VM {
         &self.current_frame
     }
 
+    /// Element type and contents of an allocation made with `alloc_array`
+    /// (or a data-section array), for display in the debugger.
+    pub fn get_array_allocation(&self, addr: usize) -> Option<(DataType, Vec<Value>)> {
+        let (dtype, count) = *self.heap_types.get(&addr)?;
+        let bytes = self.heap.get(&addr)?;
+        let size = Variable::size_of(dtype);
+        let values = (0..count)
+            .map_while(|i| bytes.get(i * size..(i + 1) * size))
+            .filter_map(|chunk| Value::from_le_bytes(chunk, dtype).ok())
+            .collect();
+        Some((dtype, values))
+    }
+
     pub fn get_call_stack(&self) -> &Vec<CallFrame> {
         &self.call_stack
     }
@@ -790,3 +2272,1602 @@ impl VM {
         &self.profile_data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::assemble;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn run_source(source: &str) -> Result<VM, String> {
+        let program = assemble(source, "test.vasm".to_string()).map_err(|e| e.to_string())?;
+        let mut vm = VM::new(program);
+        vm.run()?;
+        Ok(vm)
+    }
+
+    // Write target whose contents stay readable after the VM takes ownership
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8_lossy(&self.0.borrow()).into_owned()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Runs `source` with `input` as stdin, returning the VM, stdout and stderr
+    fn run_with_io(source: &str, input: &str) -> Result<(VM, String, String), String> {
+        let program = assemble(source, "test.vasm".to_string()).map_err(|e| e.to_string())?;
+        let (stdout, stderr) = (SharedBuffer::default(), SharedBuffer::default());
+        let mut vm = VM::new(program);
+        vm.set_output(Box::new(stdout.clone()));
+        vm.set_error_output(Box::new(stderr.clone()));
+        vm.set_input(Box::new(io::Cursor::new(input.as_bytes().to_vec())));
+        vm.run()?;
+        Ok((vm, stdout.contents(), stderr.contents()))
+    }
+
+    #[test]
+    fn test_bulk_memory_ops() {
+        let vm = run_source(
+            r#"
+include "strings"
+
+section .data
+    hello: str "Hello"
+    world: str ", World"
+    joined: ptr
+    cmp_result: i32
+    length: i32
+    first: i32
+
+section .text
+main:
+    func_begin i32
+    local buf: ptr
+    alloc buf, 32
+    memset buf, 0x41, 32
+    load first, buf, i8
+    call buf, str_clear, buf, 32
+    call buf, strcpy, buf, hello
+    call buf, strcat, buf, world
+    call joined, strdup, buf
+    call length, strlen, joined
+    memcmp cmp_result, joined, buf, 13
+    ret 0
+    func_end
+"#,
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("first"), Some(&Value::I8(0x41)));
+        assert_eq!(globals.get("length"), Some(&Value::I32(12)));
+        assert_eq!(globals.get("cmp_result"), Some(&Value::I32(0)));
+    }
+
+    #[test]
+    fn test_bulk_memory_bounds_checked() {
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local a: ptr
+    local b: ptr
+    alloc a, 8
+    alloc b, 4
+    memcpy b, a, 8
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("Write out of bounds"), "{}", err);
+    }
+
+    #[test]
+    fn test_indexed_addressing() {
+        let vm = run_source(
+            r#"
+struct Point { x: f64, y: f64 }
+
+section .data
+    nums: i32[4] = {10, 20, 30, 40}
+    scaled: i32
+    implicit: i32
+    low_byte: i32
+    y: f64
+
+section .text
+main:
+    func_begin i32
+    local i: i32
+    local p: ptr
+    set i, 2
+    load scaled, nums[i*4+4], i32
+    load implicit, nums, i, i32
+    store nums[i*4-4], 0x41, u8
+    load low_byte, nums[4], u8
+    alloc p, Point.size
+    store p[Point.y], 2.5, f64
+    load y, p.Point.y, f64
+    ret 0
+    func_end
+"#,
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("scaled"), Some(&Value::I32(40)));
+        assert_eq!(globals.get("implicit"), Some(&Value::I32(30)));
+        assert_eq!(globals.get("low_byte"), Some(&Value::U8(0x41)));
+        assert_eq!(globals.get("y"), Some(&Value::F64(2.5)));
+    }
+
+    #[test]
+    fn test_sizeof_and_alloc_array() {
+        let vm = run_source(
+            r#"
+struct Point { x: f64, y: f64 }
+
+section .data
+    ptr_size: i32
+    point_size: i32
+    values: ptr
+
+section .text
+main:
+    func_begin i32
+    sizeof ptr_size, ptr
+    sizeof point_size, Point
+    alloc_array values, f64, 3
+    store values, 2, 1.5, f64
+    ret 0
+    func_end
+"#,
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("ptr_size"), Some(&Value::I32(8)));
+        assert_eq!(globals.get("point_size"), Some(&Value::I32(16)));
+        let addr = globals.get("values").unwrap().as_usize().unwrap();
+        assert_eq!(
+            vm.get_array_allocation(addr),
+            Some((DataType::F64, vec![Value::F64(0.0), Value::F64(0.0), Value::F64(1.5)]))
+        );
+
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local count: i32
+    local huge: u64
+    local values: ptr
+    set count, -1
+    cast huge, count, u64
+    alloc_array values, f64, huge
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("size overflow"), "{}", err);
+
+        // A size that fits in usize but not in memory fails without aborting
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local one: i32
+    local huge: u64
+    local values: ptr
+    set one, 1
+    cast huge, one, u64
+    shl huge, huge, 58
+    alloc_array values, f64, huge
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("Heap exhausted"), "{}", err);
+    }
+
+    #[test]
+    fn test_string_output_and_read_line() {
+        let (vm, stdout, stderr) = run_with_io(
+            r#"
+section .data
+    prompt: str "Name? "
+    first_len: i32
+    eof_len: i32
+
+section .text
+main:
+    func_begin i32
+    local buf: ptr
+    alloc buf, 8
+    print_str prompt
+    read_line first_len, buf, 8
+    print_str buf
+    print_char 10
+    eprint_str prompt
+    eprint_char 0x21
+    read_line eof_len, buf, 8
+    ret 0
+    func_end
+"#,
+            "abcdefghijkl\n",
+        )
+        .unwrap();
+
+        // The line is truncated to leave room for the terminator
+        assert_eq!(stdout, "Name? abcdefg\n");
+        assert_eq!(stderr, "Name? !");
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("first_len"), Some(&Value::I32(7)));
+        assert_eq!(globals.get("eof_len"), Some(&Value::I32(-1)));
+    }
+
+    #[test]
+    fn test_printf_formatting() {
+        let (_, stdout, _) = run_with_io(
+            r#"
+section .data
+    fmt: str "%d|%5d|%-4d|%05d|%x|%.2f|%8.3f|%.3s|%c|%u|100%%\n"
+    name: str "VarVM"
+
+section .text
+main:
+    func_begin i32
+    local neg: i32
+    local f: f64
+    local byte: u8
+    set neg, -42
+    cast f, neg, f64
+    cast byte, neg, u8
+    printf fmt, neg, 7, 7, neg, 255, 3.14159, f, name, 65, byte
+    ret 0
+    func_end
+"#,
+            "",
+        )
+        .unwrap();
+        assert_eq!(stdout, "-42|    7|7   |-0042|ff|3.14| -42.000|Var|A|214|100%\n");
+
+        let err = run_with_io(
+            r#"
+section .data
+    fmt: str "%u\n"
+
+section .text
+main:
+    func_begin i32
+    printf fmt, 5
+    ret 0
+    func_end
+"#,
+            "",
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("%u cannot format I32(5)"), "{}", err);
+    }
+
+    #[test]
+    fn test_typed_input_with_status() {
+        let (vm, stdout, _) = run_with_io(
+            r#"
+section .data
+    hex: u16
+    float: f64
+    bad_status: i32
+    eof_status: i32
+    untouched: i32
+
+section .text
+main:
+    func_begin i32
+    read hex, u16, bad_status
+    read float, f64
+    set untouched, 7
+    read untouched, i8, bad_status
+    read untouched, i32, eof_status
+    ret 0
+    func_end
+"#,
+            "0xBEEF\n-2.5e1\n300\n",
+        )
+        .unwrap();
+
+        // read never prompts
+        assert_eq!(stdout, "");
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("hex"), Some(&Value::U16(0xBEEF)));
+        assert_eq!(globals.get("float"), Some(&Value::F64(-25.0)));
+        assert_eq!(globals.get("untouched"), Some(&Value::I32(7)));
+        assert_eq!(globals.get("bad_status"), Some(&Value::I32(INPUT_PARSE_ERROR)));
+        assert_eq!(globals.get("eof_status"), Some(&Value::I32(INPUT_EOF)));
+    }
+
+    #[test]
+    fn test_sandboxed_file_io() {
+        let dir = std::env::temp_dir().join(format!("varvm-file-io-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.txt");
+        let source = format!(
+            r#"
+section .data
+    path: str "{}"
+    message: str "sandboxed"
+    written: i32
+    read_back: i32
+    position: i64
+    closed: i32
+    bad_handle: i32
+
+section .text
+main:
+    func_begin i32
+    local fd: i32
+    local buf: ptr
+    alloc buf, 16
+    fopen fd, path, 1
+    fwrite written, fd, message, 9
+    fclose closed, fd
+    fopen fd, path, 0
+    fseek position, fd, 3, 0
+    fread read_back, fd, buf, 16
+    fclose closed, fd
+    fclose bad_handle, fd
+    ret 0
+    func_end
+"#,
+            path.display()
+        );
+        let program = assemble(&source, "test.vasm".to_string()).unwrap();
+
+        // Nothing is granted by default
+        let mut vm = VM::new(program.clone());
+        let err = vm.run().err().unwrap();
+        assert!(err.contains("Permission denied: write access"), "{}", err);
+
+        let mut capabilities = Capabilities::new();
+        capabilities.allow_read(&dir).unwrap();
+        capabilities.allow_write(&dir).unwrap();
+        let mut vm = VM::new(program);
+        vm.set_capabilities(capabilities);
+        vm.run().unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("written"), Some(&Value::I32(9)));
+        assert_eq!(globals.get("position"), Some(&Value::I64(3)));
+        assert_eq!(globals.get("read_back"), Some(&Value::I32(6)));
+        assert_eq!(globals.get("closed"), Some(&Value::I32(0)));
+        assert_eq!(globals.get("bad_handle"), Some(&Value::I32(ERR_BAD_HANDLE)));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "sandboxed");
+
+        // A dangling symlink inside a granted directory must not lead outside it
+        #[cfg(unix)]
+        {
+            let outside = std::env::temp_dir().join(format!("varvm-outside-{}", std::process::id()));
+            std::fs::create_dir_all(&outside).unwrap();
+            let link = dir.join("escape.txt");
+            std::os::unix::fs::symlink(outside.join("created.txt"), &link).unwrap();
+            let mut capabilities = Capabilities::new();
+            capabilities.allow_write(&dir).unwrap();
+            let err = capabilities.check_write(&link).unwrap_err();
+            assert!(err.contains("Permission denied"), "{}", err);
+            std::fs::remove_dir_all(&outside).ok();
+        }
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_system_services_deterministic() {
+        let source = r#"
+section .data
+    roll: i32
+    elapsed: i64
+    first_arg: ptr
+    arg_count: i32
+
+section .text
+main:
+    func_begin i32
+    pop_arg argc
+    pop_arg argv
+    local start: i64
+    local stop: i64
+    copy arg_count, argc
+    load first_arg, argv, 1, ptr
+    clock start
+    sleep 250
+    clock stop
+    sub elapsed, stop, start
+    rand roll, 6
+    ret 0
+    func_end
+"#;
+        let run = |seed| {
+            let program = assemble(source, "test.vasm".to_string()).unwrap();
+            let mut vm = VM::new(program);
+            vm.set_system(SystemServices::deterministic(1_000, seed));
+            vm.set_args(&["prog".to_string(), "hello".to_string()]).unwrap();
+            vm.run().unwrap();
+            vm
+        };
+
+        let vm = run(42);
+        let globals = vm.get_globals();
+        // The virtual clock advances by exactly the slept time, without blocking
+        assert_eq!(globals.get("elapsed"), Some(&Value::I64(250_000_000)));
+        assert_eq!(globals.get("arg_count"), Some(&Value::I32(2)));
+        let first_arg = globals.get("first_arg").unwrap().as_usize().unwrap();
+        assert_eq!(vm.read_c_string_at(first_arg).unwrap(), b"hello");
+
+        let roll = globals.get("roll").cloned();
+        assert!(matches!(roll, Some(Value::I32(0..=5))), "{:?}", roll);
+        assert_eq!(run(42).get_globals().get("roll").cloned(), roll);
+    }
+
+    #[test]
+    fn test_tcp_loopback_echo() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        // Echo server on an ephemeral loopback port
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = server.local_addr().unwrap().port();
+        let echo = std::thread::spawn(move || {
+            let (mut stream, _) = server.accept().unwrap();
+            let mut buf = [0u8; 16];
+            let n = stream.read(&mut buf).unwrap();
+            stream.write_all(&buf[..n]).unwrap();
+        });
+
+        let source = format!(
+            r#"
+section .data
+    server: str "127.0.0.1:{}"
+    local_addr: str "127.0.0.1:0"
+    message: str "ping"
+    sent: i32
+    received: i32
+    reply: ptr
+    closed: i32
+    accepted: i32
+
+section .text
+main:
+    func_begin i32
+    local sock: i32
+    local listener: i32
+    local status: i32
+    alloc reply, 8
+    memset reply, 0, 8
+    tcp_connect sock, server
+    tcp_send sent, sock, message, 4
+    tcp_recv received, sock, reply, 8
+    tcp_close closed, sock
+    tcp_listen listener, local_addr
+    tcp_nonblock status, listener, 1
+    tcp_accept accepted, listener
+    tcp_close status, listener
+    ret 0
+    func_end
+"#,
+            port
+        );
+        let program = assemble(&source, "test.vasm".to_string()).unwrap();
+
+        // Networking is denied without the capability
+        let mut vm = VM::new(program.clone());
+        let err = vm.run().err().unwrap();
+        assert!(err.contains("Permission denied: network access"), "{}", err);
+
+        let mut capabilities = Capabilities::new();
+        capabilities.allow_net();
+        let mut vm = VM::new(program);
+        vm.set_capabilities(capabilities);
+        vm.run().unwrap();
+        echo.join().unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("sent"), Some(&Value::I32(4)));
+        assert_eq!(globals.get("received"), Some(&Value::I32(4)));
+        assert_eq!(globals.get("closed"), Some(&Value::I32(0)));
+        // Nobody is connecting, so a non-blocking accept has nothing to return
+        assert_eq!(globals.get("accepted"), Some(&Value::I32(ERR_WOULD_BLOCK)));
+        let reply = globals.get("reply").unwrap().as_usize().unwrap();
+        assert_eq!(vm.read_c_string_at(reply).unwrap(), b"ping");
+    }
+
+    #[test]
+    fn test_console_with_mock() {
+        use crate::console::{ConsoleEvent, MockConsole};
+
+        let program = assemble(
+            r#"
+section .data
+    first: i32
+    second: i32
+    none_left: i32
+
+section .text
+main:
+    func_begin i32
+    clear
+    color 2, -1
+    cursor 3, 10
+    getkey first
+    pollkey second
+    pollkey none_left
+    color -1, -1
+    ret 0
+    func_end
+"#,
+            "test.vasm".to_string(),
+        )
+        .unwrap();
+
+        let console = MockConsole::new();
+        console.push_keys(b"qx");
+        let mut vm = VM::new(program);
+        vm.set_console(Box::new(console.clone()));
+        vm.set_input(Box::new(io::Cursor::new(Vec::new())));
+        vm.run().unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("first"), Some(&Value::I32(b'q' as i32)));
+        assert_eq!(globals.get("second"), Some(&Value::I32(b'x' as i32)));
+        assert_eq!(globals.get("none_left"), Some(&Value::I32(-1)));
+        assert_eq!(
+            console.events(),
+            vec![
+                ConsoleEvent::Clear,
+                ConsoleEvent::SetColor(Some(2), None),
+                ConsoleEvent::MoveCursor(3, 10),
+                ConsoleEvent::SetColor(None, None),
+            ]
+        );
+
+        // Key reads continue from the same buffered input as line reads
+        let (vm, _, _) = run_with_io(
+            r#"
+section .data
+    n: i32
+    key: i32
+
+section .text
+main:
+    func_begin i32
+    read n, i32
+    getkey key
+    ret 0
+    func_end
+"#,
+            "5\nab",
+        )
+        .unwrap();
+        assert_eq!(vm.get_globals().get("n"), Some(&Value::I32(5)));
+        assert_eq!(vm.get_globals().get("key"), Some(&Value::I32(b'a' as i32)));
+    }
+
+    #[test]
+    fn test_math_intrinsics() {
+        let vm = run_source(
+            r#"
+section .data
+    big: i64
+    magnitude: i16
+    floored: f64
+    rounded: f64
+    angle: f64
+    fused: f64
+    signed: f64
+    nan_check: i32
+    inf_check: i32
+    logged: f64
+
+section .text
+main:
+    func_begin i32
+    local x: f64
+    local zero: f64
+    local base: i64
+    local neg: i16
+    local seven: f64
+    local thousand: f64
+    local scratch: ptr
+    alloc_array scratch, f64, 4
+    store scratch, 0, -2.5, f64
+    store scratch, 2, 7, f64
+    store scratch, 3, 1000, f64
+    load x, scratch, 0, f64
+    load zero, scratch, 1, f64
+    load seven, scratch, 2, f64
+    load thousand, scratch, 3, f64
+    set base, 3
+    cast base, base, i64
+    set neg, -300
+    cast neg, neg, i16
+    pow big, base, 39
+    abs magnitude, neg
+    floor floored, x
+    round rounded, x
+    atan2 angle, x, x
+    fma fused, x, x, x
+    copysign signed, seven, x
+    div zero, zero, zero
+    isnan nan_check, zero
+    isinf inf_check, zero
+    log10 logged, thousand
+    ret 0
+    func_end
+"#,
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        // Exact, where an f64 round trip would lose the low digits
+        assert_eq!(globals.get("big"), Some(&Value::I64(4052555153018976267)));
+        assert_eq!(globals.get("magnitude"), Some(&Value::I16(300)));
+        assert_eq!(globals.get("floored"), Some(&Value::F64(-3.0)));
+        assert_eq!(globals.get("rounded"), Some(&Value::F64(-3.0)));
+        assert_eq!(globals.get("angle"), Some(&Value::F64(-3.0 * std::f64::consts::FRAC_PI_4)));
+        assert_eq!(globals.get("fused"), Some(&Value::F64(3.75)));
+        assert_eq!(globals.get("signed"), Some(&Value::F64(-7.0)));
+        assert_eq!(globals.get("nan_check"), Some(&Value::Bool(true)));
+        assert_eq!(globals.get("inf_check"), Some(&Value::Bool(false)));
+        assert_eq!(globals.get("logged"), Some(&Value::F64(3.0)));
+
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local b: u8
+    set b, 16
+    cast b, b, u8
+    pow b, b, 2
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("Integer overflow in pow"), "{}", err);
+    }
+
+    #[test]
+    fn test_bit_manipulation() {
+        let vm = run_source(
+            r#"
+section .data
+    ones: i32
+    zero_clz: i32
+    zero_ctz: i32
+    lead: i32
+    rotated: u8
+    wrapped: u8
+    back: u8
+    swapped: u32
+    field: u32
+    merged: u32
+    sign_field: i8
+
+section .text
+main:
+    func_begin i32
+    local word: u32
+    local byte: u8
+    local zero: u16
+    local small: i8
+    set word, 0x12345678
+    cast word, word, u32
+    set byte, 0x81
+    cast byte, byte, u8
+    set zero, 0
+    cast zero, zero, u16
+    set small, -1
+    cast small, small, i8
+    popcnt ones, word
+    clz zero_clz, zero
+    ctz zero_ctz, zero
+    clz lead, word
+    rotl rotated, byte, 1
+    rotl wrapped, byte, 9
+    rotr back, byte, -1
+    bswap swapped, word
+    bfext field, word, 8, 12
+    bfins merged, word, 0xABC, 4, 8
+    bfext sign_field, small, 4, 4
+    ret 0
+    func_end
+"#,
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("ones"), Some(&Value::I32(13)));
+        // A zero input counts every bit of its width
+        assert_eq!(globals.get("zero_clz"), Some(&Value::I32(16)));
+        assert_eq!(globals.get("zero_ctz"), Some(&Value::I32(16)));
+        assert_eq!(globals.get("lead"), Some(&Value::I32(3)));
+        assert_eq!(globals.get("rotated"), Some(&Value::U8(0x03)));
+        // Over-wide and negative counts are reduced modulo the width
+        assert_eq!(globals.get("wrapped"), Some(&Value::U8(0x03)));
+        assert_eq!(globals.get("back"), Some(&Value::U8(0x03)));
+        assert_eq!(globals.get("swapped"), Some(&Value::U32(0x78563412)));
+        assert_eq!(globals.get("field"), Some(&Value::U32(0x456)));
+        assert_eq!(globals.get("merged"), Some(&Value::U32(0x12345BC8)));
+        assert_eq!(globals.get("sign_field"), Some(&Value::I8(0x0F)));
+
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local byte: u8
+    local out: u8
+    set byte, 1
+    cast byte, byte, u8
+    bfext out, byte, 4, 8
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("Bit field of 8 bits at bit 4 does not fit in 8 bits"), "{}", err);
+
+        // A start near usize::MAX must not overflow while checking the range
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local x: u64
+    local s: u64
+    local out: u64
+    set s, -1
+    cast s, s, u64
+    bfext out, x, s, 2
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("does not fit in 64 bits"), "{}", err);
+    }
+
+    #[test]
+    fn test_endian_and_vector_access() {
+        let vm = run_source(
+            r#"
+section .data
+    packet: u8[7] = {0xCA, 0xFE, 0x12, 0x34, 0x56, 0x78, 0x00}
+    magic: u16
+    unaligned: u32
+    little: u32
+    words: ptr
+    out: ptr
+    second: u16
+
+section .text
+main:
+    func_begin i32
+    load.be magic, packet, u16
+    load.be unaligned, packet[1], u32
+    load little, packet[2], u32
+    alloc_array words, u16, 3
+    loadv.be words, packet, 3, u16
+    load second, words, 1, u16
+    alloc out, 6
+    storev.be out, words, 3, u16
+    store.be packet[5], 0xBEEF, u16
+    ret 0
+    func_end
+"#,
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("magic"), Some(&Value::U16(0xCAFE)));
+        assert_eq!(globals.get("unaligned"), Some(&Value::U32(0xFE123456)));
+        assert_eq!(globals.get("little"), Some(&Value::U32(0x78563412)));
+        // Vector loads convert each element to native order
+        assert_eq!(globals.get("second"), Some(&Value::U16(0x1234)));
+
+        let out = globals.get("out").unwrap().as_usize().unwrap();
+        assert_eq!(vm.load_bytes_from_heap(out, 6).unwrap(), [0xCA, 0xFE, 0x12, 0x34, 0x56, 0x78]);
+        let packet = globals.get("packet").unwrap().as_usize().unwrap();
+        assert_eq!(vm.load_bytes_from_heap(packet + 5, 2).unwrap(), [0xBE, 0xEF]);
+    }
+
+    #[test]
+    fn test_compare_branch_and_select() {
+        let vm = run_source(
+            r#"
+include "math"
+
+section .data
+    sum: i32
+    larger: i32
+    clamped: f64
+    primes: i32
+    fib: i32
+
+section .text
+main:
+    func_begin i32
+    local i: i32
+    local a: i32
+    local b: i32
+    local prime: i32
+    local x: f64
+    local limit: f64
+    set i, 0
+.loop:
+    jge i, 10, .done
+    add sum, sum, i
+    add i, i, 1
+    jmp .loop
+.done:
+    set a, 7
+    set b, 12
+    lt larger, a, b
+    select larger, larger, b, a
+
+    cast x, b, f64
+    cast limit, a, f64
+    gt prime, x, limit
+    select clamped, prime, limit, x
+
+    set i, 2
+.count_primes:
+    jgt i, 30, .counted
+    call prime, is_prime, i
+    add primes, primes, prime
+    add i, i, 1
+    jmp .count_primes
+.counted:
+    call fib, fibonacci, 10
+    ret 0
+    func_end
+"#,
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("sum"), Some(&Value::I32(45)));
+        assert_eq!(globals.get("larger"), Some(&Value::I32(12)));
+        assert_eq!(globals.get("clamped"), Some(&Value::F64(7.0)));
+        assert_eq!(globals.get("primes"), Some(&Value::I32(10)));
+        assert_eq!(globals.get("fib"), Some(&Value::I32(55)));
+    }
+
+    #[test]
+    fn test_switch_jump_table() {
+        let source = r#"
+section .data
+    trace: i32
+
+section .text
+classify:
+    func_begin i32
+    pop_arg n
+    switch n, .other, [
+        .zero, .one,
+        .one, .three
+    ]
+.zero:
+    ret 10
+.one:
+    ret 11
+.three:
+    ret 13
+.other:
+    ret 99
+    func_end
+
+main:
+    func_begin i32
+    local r: i32
+    call r, classify, 0
+    mul trace, trace, 100
+    add trace, trace, r
+    call r, classify, 2
+    mul trace, trace, 100
+    add trace, trace, r
+    call r, classify, 3
+    mul trace, trace, 100
+    add trace, trace, r
+    call r, classify, 4
+    mul trace, trace, 100
+    add trace, trace, r
+    call r, classify, -1
+    mul trace, trace, 100
+    add trace, trace, r
+    ret 0
+    func_end
+"#;
+        let program = assemble(source, "test.vasm".to_string()).unwrap();
+        // The table survives a bytecode round trip
+        let program = crate::bytecode::decode(&crate::bytecode::encode(&program).unwrap()).unwrap();
+        let mut vm = VM::new(program);
+        vm.run().unwrap();
+
+        // Out-of-range and negative values take the default
+        assert_eq!(vm.get_globals().get("trace"), Some(&Value::I32(1011139999)));
+    }
+
+    #[test]
+    fn test_assert_trap_and_unreachable() {
+        let source = r#"section .text
+main:
+    func_begin i32
+    local n: i32
+    set n, 3
+    assert n, "n must be set"
+    sub n, n, 3
+    assert n, "n must stay non-zero"
+    ret 0
+    func_end
+"#;
+        let program = assemble(source, "test.vasm".to_string()).unwrap();
+        let mut vm = VM::new(program);
+        let error = vm.run().unwrap_err();
+        assert!(error.contains("test.vasm:8:"), "{}", error);
+        assert!(error.contains("Assertion failed: n must stay non-zero"), "{}", error);
+        let trap = vm.trap().unwrap();
+        assert_eq!(trap.kind, TrapKind::AssertionFailed(Some("n must stay non-zero".to_string())));
+        assert_eq!(trap.location.as_ref().map(|location| location.line), Some(8));
+
+        // Release builds drop the asserts entirely
+        let options = crate::asm::AssembleOptions { strip_asserts: true };
+        let program = crate::asm::assemble_with_options(source, "test.vasm".to_string(), &options).unwrap();
+        assert!(!program.instructions.iter().any(|op| matches!(op, OpCode::Assert { .. })));
+        let mut vm = VM::new(program);
+        vm.run().unwrap();
+        assert!(vm.trap().is_none());
+
+        let mut vm = VM::new(
+            assemble("section .text\nmain:\n    func_begin i32\n    trap 42\n    func_end\n", "test.vasm".to_string()).unwrap(),
+        );
+        assert!(vm.run().unwrap_err().contains("Trap 42"));
+        assert_eq!(vm.trap().map(|trap| &trap.kind), Some(&TrapKind::Code(42)));
+
+        let mut vm = VM::new(
+            assemble("section .text\nmain:\n    func_begin i32\n    unreachable\n    func_end\n", "test.vasm".to_string()).unwrap(),
+        );
+        assert!(vm.run().unwrap_err().contains("Reached unreachable code"));
+        assert_eq!(vm.trap().map(|trap| &trap.kind), Some(&TrapKind::Unreachable));
+    }
+
+    #[test]
+    fn test_128_bit_integers() {
+        let source = r#"
+section .data
+    seeds: u64[2] = {0x7FFFFFFFFFFFFFFF, 6}
+    product: u128
+    top: u128
+    negative: i128
+    buf: ptr
+    reloaded: u128
+    narrowed: u64
+    fmt: str "%x %d\n"
+
+section .text
+main:
+    func_begin i32
+    local a: u128
+    local b: u128
+    local one: i128
+    load a, seeds, u64
+    cast a, a, u128
+    load b, seeds[8], u64
+    cast b, b, u128
+    mul product, a, b
+    set one, 1
+    cast one, one, i128
+    shl negative, one, 100
+    neg negative, negative
+    cast top, one, u128
+    shl top, top, 127
+    alloc_array buf, u128, 1
+    store.be buf, top, u128
+    load.be reloaded, buf, u128
+    cast narrowed, product, u64
+    printf fmt, top, negative
+    ret 0
+    func_end
+"#;
+        let program = assemble(source, "test.vasm".to_string()).unwrap();
+        let program = crate::bytecode::decode(&crate::bytecode::encode(&program).unwrap()).unwrap();
+        let output = SharedBuffer::default();
+        let mut vm = VM::new(program);
+        vm.set_output(Box::new(output.clone()));
+        vm.run().unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("product"), Some(&Value::U128(i64::MAX as u128 * 6)));
+        assert_eq!(globals.get("reloaded"), Some(&Value::U128(1 << 127)));
+        assert_eq!(globals.get("narrowed"), Some(&Value::U64((i64::MAX as u128 * 6) as u64)));
+        assert_eq!(output.contents(), format!("{:x} {}\n", 1u128 << 127, -(1i128 << 100)));
+
+        let buf = globals.get("buf").unwrap().as_usize().unwrap();
+        assert_eq!(vm.load_bytes_from_heap(buf, 16).unwrap()[0], 0x80);
+        assert_eq!(Value::parse_as("340282366920938463463374607431768211455", DataType::U128), Ok(Value::U128(u128::MAX)));
+        assert_eq!(Value::parse_as("-0x80000000000000000000000000000000", DataType::I128), Ok(Value::I128(i128::MIN)));
+
+        // Counts and indices beyond usize are errors, not truncated
+        assert!(Value::U128(1 << 64).as_usize().is_err());
+        assert!(Value::I128(1 << 64).as_usize().is_err());
+        assert_eq!(Value::U128(42).as_usize(), Ok(42));
+
+        // Overflow is a runtime error instead of a panic
+        for (dtype, setup) in [
+            ("u128", "sub a, a, b"),
+            ("i128", "shl a, b, 126\n    add a, a, a"),
+            ("u128", "shl a, b, 127\n    mul a, a, a"),
+            ("i128", "shl a, b, 127\n    neg a, a"),
+        ] {
+            let source = format!(
+                r#"
+section .text
+main:
+    func_begin i32
+    local a: {dtype}
+    local b: {dtype}
+    local n: i32
+    set n, 1
+    cast b, n, {dtype}
+    cast a, a, {dtype}
+    {setup}
+    ret 0
+    func_end
+"#
+            );
+            let err = run_source(&source).err().unwrap();
+            assert!(err.contains("Integer overflow"), "{}: {}", setup, err);
+        }
+    }
+
+    #[test]
+    fn test_bool_and_char_types() {
+        let (vm, stdout, _) = run_with_io(
+            r#"
+section .data
+    letters: char[3] = {'a', '\n', '\u{e9}'}
+    fmt: str "%c%c|"
+    picked: i32
+    third: char
+    code: char
+
+section .text
+main:
+    func_begin i32
+    local flag: bool
+    local other: bool
+    local c: char
+    local n: i32
+    set n, 7
+    lt flag, n, 10
+    ge other, n, 10
+    or flag, flag, other
+    select picked, flag, 1, 2
+    jz other, .skip
+    set picked, 99
+.skip:
+    set c, 'Z'
+    print flag
+    print c
+    load third, letters[8], char
+    set n, 0x263A
+    cast code, n, char
+    printf fmt, c, code
+    print_char third
+    print_char 33
+    ret 0
+    func_end
+"#,
+            "",
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("picked"), Some(&Value::I32(1)));
+        assert_eq!(globals.get("third"), Some(&Value::Char('é')));
+        assert_eq!(globals.get("code"), Some(&Value::Char('☺')));
+        assert_eq!(stdout, "flag: true\nc: 'Z'\nZ☺|é!");
+
+        // Integer operands are raw bytes and must fit in one
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    print_char 300
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("out of byte range"), "{}", err);
+
+        // Flags are not numbers
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local a: bool
+    eq a, 1, 1
+    add a, a, a
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("Type mismatch in addition"), "{}", err);
+
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local n: i32
+    local c: char
+    set n, 0xD800
+    cast c, n, char
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("Invalid character code"), "{}", err);
+    }
+
+    #[test]
+    fn test_simd_vectors() {
+        let (vm, stdout, _) = run_with_io(
+            r#"
+section .data
+    nums: f32[8] = {1.0, 2.0, 3.0, 4.0, 10.0, 20.0, 30.0, 40.0}
+    lane2: f32
+    stored: f32
+    mask_lane: i32
+    wrapped: u8
+
+section .text
+main:
+    func_begin i32
+    local a: v4f32
+    local b: v4f32
+    local sum: v4f32
+    local half: v4f32
+    local mixed: v4f32
+    local mask: v4i32
+    local bytes: v16u8
+    load a, nums, v4f32
+    load b, nums[16], v4f32
+    add sum, a, b
+    vsplat half, 0.5, v4f32
+    mul sum, sum, half
+    vextract lane2, sum, 2
+    vshuffle mixed, a, b, [0, 4, 1, 5]
+    vgt mask, mixed, a
+    vextract mask_lane, mask, 1
+    vselect mixed, mask, b, a
+    vinsert mixed, mixed, 0, 7
+    store nums, mixed, v4f32
+    load stored, nums[4], f32
+    vsplat bytes, 200, v16u8
+    add bytes, bytes, bytes
+    vextract wrapped, bytes, 15
+    print sum
+    print mixed
+    ret 0
+    func_end
+"#,
+            "",
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("lane2"), Some(&Value::F32(16.5)));
+        assert_eq!(globals.get("mask_lane"), Some(&Value::I32(-1)));
+        assert_eq!(globals.get("stored"), Some(&Value::F32(20.0)));
+        // Integer lanes wrap like hardware SIMD
+        assert_eq!(globals.get("wrapped"), Some(&Value::U8(144)));
+        assert_eq!(stdout, "sum: v4f32 [5.5, 11.0, 16.5, 22.0]\nmixed: v4f32 [7.0, 20.0, 3.0, 40.0]\n");
+
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local a: v4f32
+    local b: v4i32
+    add a, a, b
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("requires matching shapes"), "{}", err);
+    }
+
+    #[test]
+    fn test_string_values() {
+        let (vm, stdout, _) = run_with_io(
+            r#"
+section .data
+    raw: str "héllo"
+    fmt: str "[%s]"
+    length: i32
+    found: i32
+    missing: i32
+    order: i32
+    parsed: i64
+    buf: ptr
+    equal: bool
+
+section .text
+main:
+    func_begin i32
+    local s: string
+    local word: string
+    local n: string
+    local back: string
+    ptr_to_str s, raw
+    str_len length, s
+    str_concat s, s, ", wörld"
+    str_find found, s, "wörld"
+    str_find missing, s, "xyz"
+    str_sub word, s, 7, 5
+    str_cmp order, "apple", word
+    str_to_int parsed, "-0x2A"
+    int_to_str n, parsed
+    float_to_str back, 2.5
+    str_concat n, n, back
+    str_to_ptr buf, word
+    ptr_to_str back, buf
+    eq equal, back, word
+    print s
+    print_str word
+    printf fmt, n
+    ret 0
+    func_end
+"#,
+            "",
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("length"), Some(&Value::I32(5)));
+        assert_eq!(globals.get("found"), Some(&Value::I32(7)));
+        assert_eq!(globals.get("missing"), Some(&Value::I32(-1)));
+        assert_eq!(globals.get("order"), Some(&Value::I32(-1)));
+        assert_eq!(globals.get("parsed"), Some(&Value::I64(-42)));
+        assert_eq!(globals.get("equal"), Some(&Value::Bool(true)));
+        assert_eq!(stdout, "s: héllo, wörld\nwörld[-422.5]");
+
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local s: string
+    set s, "abc"
+    str_sub s, s, 2, 2
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("out of bounds"), "{}", err);
+    }
+
+    #[test]
+    fn test_unset_globals_start_at_zero_value() {
+        let (vm, stdout, _) = run_with_io(
+            r#"
+section .data
+    name: string
+    obj: ref
+    lanes: v4i32
+    flag: bool
+    wide: u128
+    len: i32
+
+section .text
+main:
+    func_begin i32
+    str_len len, name
+    print obj
+    ret 0
+    func_end
+"#,
+            "",
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("name"), Some(&Value::Str("".into())));
+        assert_eq!(globals.get("obj"), Some(&Value::Ref(ObjRef::NULL)));
+        assert_eq!(globals.get("lanes"), Some(&Value::Vector(Vector::zero(crate::simd::VectorType::V4I32))));
+        assert_eq!(globals.get("flag"), Some(&Value::Bool(false)));
+        assert_eq!(globals.get("wide"), Some(&Value::U128(0)));
+        assert_eq!(globals.get("len"), Some(&Value::I32(0)));
+        assert_eq!(stdout, "obj: null\n");
+    }
+
+    #[test]
+    fn test_managed_arrays_and_maps() {
+        let (mut vm, stdout, _) = run_with_io(
+            r#"
+section .data
+    scores: ref
+    total: i32
+    keys: string
+
+section .text
+main:
+    func_begin i32
+    local names: ref
+    local tmp: ref
+    local i: i32
+    local n: i32
+    local v: i32
+    local key: string
+    local acc: string
+    map_new scores
+    obj_set scores, "bob", 7
+    obj_set scores, "alice", 5
+    obj_set scores, "bob", 9
+    array_new names
+    array_push names, "x"
+    array_push names, scores
+    array_pop tmp, names
+    obj_len n, tmp
+.sum:
+    map_key key, scores, i
+    obj_get v, scores, key
+    add total, total, v
+    str_concat acc, acc, key
+    add i, i, 1
+    jlt i, n, .sum
+    set keys, acc
+    set i, 0
+.churn:
+    array_new tmp
+    array_push tmp, i
+    add i, i, 1
+    jlt i, 3000, .churn
+    print scores
+    print names
+    ret 0
+    func_end
+"#,
+            "",
+        )
+        .unwrap();
+
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("total"), Some(&Value::I32(14)));
+        assert_eq!(globals.get("keys"), Some(&Value::Str("bobalice".into())));
+        assert_eq!(stdout, "scores: map[2]\nnames: array[1]\n");
+
+        // Churn garbage was reclaimed along the way
+        let stats = vm.gc_stats();
+        assert!(stats.collections >= 1, "{:?}", stats);
+        assert_eq!(stats.allocated, 3002);
+        assert!(stats.live_objects < 1100, "{:?}", stats);
+
+        // Only the map (a global) survives once main's frame is gone
+        vm.current_frame.locals.clear();
+        vm.collect_garbage();
+        assert_eq!(vm.gc_stats().live_objects, 1);
+        let Some(Value::Ref(scores)) = vm.get_globals().get("scores").cloned() else {
+            panic!("scores is not a reference");
+        };
+        assert!(matches!(vm.object(scores), Some(Object::Map(map)) if map.len() == 2));
+
+        let err = run_source(
+            r#"
+section .text
+main:
+    func_begin i32
+    local a: ref
+    local p: ptr
+    array_new a
+    alloc p, 8
+    store p, a, ref
+    ret 0
+    func_end
+"#,
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("cannot be stored in raw memory"), "{}", err);
+    }
+
+    #[test]
+    fn test_typed_function_signatures() {
+        let source = r#"
+section .data
+    total: i64
+    label: string
+
+section .text
+scale:
+    func_begin i64 (x: i64, factor: i64)
+    local r: i64
+    mul r, x, factor
+    ret r
+    func_end
+
+greet:
+    func_begin string (name: string)
+    local s: string
+    str_concat s, "hi ", name
+    ret s
+    func_end
+
+legacy:
+    func_begin i32
+    local a: i32
+    pop_arg a
+    ret a
+    func_end
+
+main:
+    func_begin i32 ()
+    local n: i64
+    local k: i32
+    set n, 6
+    call total, scale, n, 7
+    call label, greet, "bob"
+    call k, legacy, 3
+    ret 0
+    func_end
+"#;
+        let program = assemble(source, "test.vasm".to_string()).unwrap();
+        let scale = &program.functions["scale"];
+        assert!(scale.typed);
+        assert_eq!(scale.return_type, DataType::I64);
+        assert_eq!(scale.parameters.len(), 2);
+        assert!(!program.functions["legacy"].typed);
+
+        let mut vm = VM::new(program.clone());
+        vm.run().unwrap();
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("total"), Some(&Value::I64(42)));
+        assert_eq!(globals.get("label"), Some(&Value::Str("hi bob".into())));
+
+        // Signatures survive a bytecode round trip
+        let bytes = crate::bytecode::encode(&program).unwrap();
+        let decoded = crate::bytecode::decode(&bytes).unwrap();
+        assert_eq!(decoded.functions["greet"].parameters[0].dtype, DataType::Str);
+
+        // Mismatched calls are rejected at assembly time
+        for (call, expected) in [
+            ("call total, scale, n", "expects 2 argument(s), got 1"),
+            ("call total, scale, k, 7", "expects I64, got I32"),
+            ("call k, scale, n, 7", "returns I64"),
+            ("call label, greet, 5", "expects Str, got I32"),
+            ("call total, scale, n, 4.5", "expects I64, got F32"),
+            ("call total, scale, n, 'c'", "expects I64, got Char"),
+        ] {
+            let bad = source.replace("call total, scale, n, 7", call);
+            let err = assemble(&bad, "test.vasm".to_string()).unwrap_err().to_string();
+            assert!(err.contains(expected), "{}: {}", call, err);
+        }
+
+        // Bytecode that skipped the assembler is still checked when the call runs
+        let mut tampered = program.clone();
+        tampered.functions.get_mut("scale").unwrap().parameters.pop();
+        let err = VM::new(tampered).run().unwrap_err();
+        assert!(err.contains("expects 1 argument(s), got 2"), "{}", err);
+
+        // including the declared type of a local destination
+        let mut tampered = program;
+        for instr in &mut tampered.instructions {
+            match instr {
+                OpCode::Call { result, func, .. } if func == "scale" => *result = Some("k".to_string()),
+                _ => {}
+            }
+        }
+        let err = VM::new(tampered).run().unwrap_err();
+        assert!(err.contains("returns I64, but the result is stored in a I32 variable"), "{}", err);
+
+        // A `pop_arg` local has no declared type, so it may receive any result
+        let untyped = source.replace("call k, legacy, 3", "call k, wrap, 3").replace(
+            "main:",
+            "wrap:\n    func_begin i32\n    pop_arg v\n    call v, scale, 2, 3\n    ret v\n    func_end\n\nmain:",
+        );
+        let vm = run_source(&untyped).unwrap();
+        assert_eq!(vm.get_globals().get("total"), Some(&Value::I64(42)));
+    }
+
+    #[test]
+    fn test_void_functions_and_discarded_results() {
+        let source = r#"
+section .data
+    count: i32
+    last: i32
+
+section .text
+main:
+    func_begin i32
+    call bump
+    call bump_by, 4
+    call next
+    call last, next
+    ret 0
+    func_end
+
+bump:
+    func_begin void
+    add count, count, 1
+    ret
+    func_end
+
+bump_by:
+    func_begin void (n: i32)
+    add count, count, n
+    ret
+    func_end
+
+next:
+    func_begin i32 ()
+    add count, count, 1
+    ret count
+    func_end
+"#;
+        let vm = run_source(source).unwrap();
+        let globals = vm.get_globals();
+        assert_eq!(globals.get("count"), Some(&Value::I32(7)));
+        assert_eq!(globals.get("last"), Some(&Value::I32(7)));
+
+        for (from, to, expected) in [
+            ("call bump\n", "call last, bump\n", "test.vasm:9:5: Function 'bump' returns void"),
+            ("add count, count, 1\n    ret\n", "ret 1\n", "test.vasm:18:5: Void function 'bump' cannot return a value"),
+            ("ret count", "ret", "Function 'next' must return a I32 value"),
+            ("call bump\n", "call missing, count\n", "test.vasm:9:5: Unknown function: neither 'missing' nor 'count'"),
+        ] {
+            let bad = source.replacen(from, to, 1);
+            let err = assemble(&bad, "test.vasm".to_string()).unwrap_err().to_string();
+            assert!(err.contains(expected), "{}", err);
+        }
+    }
+}
@@ -8,7 +8,9 @@ pub fn derive_value_ops(_input: TokenStream) -> TokenStream {
     let comparison_ops = generate_comparison_ops();
     let bitwise_ops = generate_bitwise_ops();
     let shift_ops = generate_shift_ops();
+    let bit_ops = generate_bit_ops();
     let cast_methods = generate_cast_methods();
+    let math_ops = generate_math_ops();
 
     let expanded = quote! {
         impl Value {
@@ -16,7 +18,9 @@ pub fn derive_value_ops(_input: TokenStream) -> TokenStream {
             #comparison_ops
             #bitwise_ops
             #shift_ops
+            #bit_ops
             #cast_methods
+            #math_ops
         }
     };
 
@@ -29,8 +33,11 @@ fn generate_binary_ops() -> proc_macro2::TokenStream {
             match (self, other) {
                 (Value::I32(a), Value::I32(b)) => Ok(Value::I32(a + b)),
                 (Value::I64(a), Value::I64(b)) => Ok(Value::I64(a + b)),
+                (Value::I128(a), Value::I128(b)) => a.checked_add(*b).map(Value::I128).ok_or_else(|| "Integer overflow in addition".to_string()),
+                (Value::U128(a), Value::U128(b)) => a.checked_add(*b).map(Value::U128).ok_or_else(|| "Integer overflow in addition".to_string()),
                 (Value::F32(a), Value::F32(b)) => Ok(Value::F32(a + b)),
                 (Value::F64(a), Value::F64(b)) => Ok(Value::F64(a + b)),
+                (Value::Vector(a), Value::Vector(b)) => a.add(b).map(Value::Vector),
                 // Pointer arithmetic: Ptr + Integer
                 (Value::Ptr(ptr), Value::I32(offset)) => Ok(Value::Ptr((*ptr as isize + *offset as isize) as usize)),
                 (Value::Ptr(ptr), Value::I64(offset)) => Ok(Value::Ptr((*ptr as isize + *offset as isize) as usize)),
@@ -44,8 +51,11 @@ fn generate_binary_ops() -> proc_macro2::TokenStream {
             match (self, other) {
                 (Value::I32(a), Value::I32(b)) => Ok(Value::I32(a - b)),
                 (Value::I64(a), Value::I64(b)) => Ok(Value::I64(a - b)),
+                (Value::I128(a), Value::I128(b)) => a.checked_sub(*b).map(Value::I128).ok_or_else(|| "Integer overflow in subtraction".to_string()),
+                (Value::U128(a), Value::U128(b)) => a.checked_sub(*b).map(Value::U128).ok_or_else(|| "Integer overflow in subtraction".to_string()),
                 (Value::F32(a), Value::F32(b)) => Ok(Value::F32(a - b)),
                 (Value::F64(a), Value::F64(b)) => Ok(Value::F64(a - b)),
+                (Value::Vector(a), Value::Vector(b)) => a.sub(b).map(Value::Vector),
                 // Pointer arithmetic: Ptr - Integer
                 (Value::Ptr(ptr), Value::I32(offset)) => Ok(Value::Ptr((*ptr as isize - *offset as isize) as usize)),
                 (Value::Ptr(ptr), Value::I64(offset)) => Ok(Value::Ptr((*ptr as isize - *offset as isize) as usize)),
@@ -61,8 +71,11 @@ fn generate_binary_ops() -> proc_macro2::TokenStream {
             match (self, other) {
                 (Value::I32(a), Value::I32(b)) => Ok(Value::I32(a * b)),
                 (Value::I64(a), Value::I64(b)) => Ok(Value::I64(a * b)),
+                (Value::I128(a), Value::I128(b)) => a.checked_mul(*b).map(Value::I128).ok_or_else(|| "Integer overflow in multiplication".to_string()),
+                (Value::U128(a), Value::U128(b)) => a.checked_mul(*b).map(Value::U128).ok_or_else(|| "Integer overflow in multiplication".to_string()),
                 (Value::F32(a), Value::F32(b)) => Ok(Value::F32(a * b)),
                 (Value::F64(a), Value::F64(b)) => Ok(Value::F64(a * b)),
+                (Value::Vector(a), Value::Vector(b)) => a.mul(b).map(Value::Vector),
                 _ => Err("Type mismatch in multiplication".to_string()),
             }
         }
@@ -71,8 +84,11 @@ fn generate_binary_ops() -> proc_macro2::TokenStream {
             match (self, other) {
                 (Value::I32(a), Value::I32(b)) if *b != 0 => Ok(Value::I32(a / b)),
                 (Value::I64(a), Value::I64(b)) if *b != 0 => Ok(Value::I64(a / b)),
+                (Value::I128(a), Value::I128(b)) if *b != 0 => a.checked_div(*b).map(Value::I128).ok_or_else(|| "Integer overflow in division".to_string()),
+                (Value::U128(a), Value::U128(b)) if *b != 0 => Ok(Value::U128(a / b)),
                 (Value::F32(a), Value::F32(b)) => Ok(Value::F32(a / b)),
                 (Value::F64(a), Value::F64(b)) => Ok(Value::F64(a / b)),
+                (Value::Vector(a), Value::Vector(b)) => a.div(b).map(Value::Vector),
                 _ => Err("Type mismatch or division by zero".to_string()),
             }
         }
@@ -81,6 +97,8 @@ fn generate_binary_ops() -> proc_macro2::TokenStream {
             match (self, other) {
                 (Value::I32(a), Value::I32(b)) if *b != 0 => Ok(Value::I32(a % b)),
                 (Value::I64(a), Value::I64(b)) if *b != 0 => Ok(Value::I64(a % b)),
+                (Value::I128(a), Value::I128(b)) if *b != 0 => a.checked_rem(*b).map(Value::I128).ok_or_else(|| "Integer overflow in modulo".to_string()),
+                (Value::U128(a), Value::U128(b)) if *b != 0 => Ok(Value::U128(a % b)),
                 _ => Err("Type mismatch or modulo by zero".to_string()),
             }
         }
@@ -89,6 +107,7 @@ fn generate_binary_ops() -> proc_macro2::TokenStream {
             match self {
                 Value::I32(a) => Ok(Value::I32(-a)),
                 Value::I64(a) => Ok(Value::I64(-a)),
+                Value::I128(a) => a.checked_neg().map(Value::I128).ok_or_else(|| "Integer overflow in negation".to_string()),
                 Value::F32(a) => Ok(Value::F32(-a)),
                 Value::F64(a) => Ok(Value::F64(-a)),
                 _ => Err("Cannot negate this type".to_string()),
@@ -105,12 +124,16 @@ fn generate_comparison_ops() -> proc_macro2::TokenStream {
                 (Value::I16(a), Value::I16(b)) => Ok(a < b),
                 (Value::I32(a), Value::I32(b)) => Ok(a < b),
                 (Value::I64(a), Value::I64(b)) => Ok(a < b),
+                (Value::I128(a), Value::I128(b)) => Ok(a < b),
                 (Value::U8(a), Value::U8(b)) => Ok(a < b),
                 (Value::U16(a), Value::U16(b)) => Ok(a < b),
                 (Value::U32(a), Value::U32(b)) => Ok(a < b),
                 (Value::U64(a), Value::U64(b)) => Ok(a < b),
+                (Value::U128(a), Value::U128(b)) => Ok(a < b),
                 (Value::F32(a), Value::F32(b)) => Ok(a < b),
                 (Value::F64(a), Value::F64(b)) => Ok(a < b),
+                (Value::Char(a), Value::Char(b)) => Ok(a < b),
+                (Value::Str(a), Value::Str(b)) => Ok(a < b),
                 _ => Err("Type mismatch in comparison".to_string()),
             }
         }
@@ -121,12 +144,16 @@ fn generate_comparison_ops() -> proc_macro2::TokenStream {
                 (Value::I16(a), Value::I16(b)) => Ok(a <= b),
                 (Value::I32(a), Value::I32(b)) => Ok(a <= b),
                 (Value::I64(a), Value::I64(b)) => Ok(a <= b),
+                (Value::I128(a), Value::I128(b)) => Ok(a <= b),
                 (Value::U8(a), Value::U8(b)) => Ok(a <= b),
                 (Value::U16(a), Value::U16(b)) => Ok(a <= b),
                 (Value::U32(a), Value::U32(b)) => Ok(a <= b),
                 (Value::U64(a), Value::U64(b)) => Ok(a <= b),
+                (Value::U128(a), Value::U128(b)) => Ok(a <= b),
                 (Value::F32(a), Value::F32(b)) => Ok(a <= b),
                 (Value::F64(a), Value::F64(b)) => Ok(a <= b),
+                (Value::Char(a), Value::Char(b)) => Ok(a <= b),
+                (Value::Str(a), Value::Str(b)) => Ok(a <= b),
                 _ => Err("Type mismatch in comparison".to_string()),
             }
         }
@@ -137,12 +164,16 @@ fn generate_comparison_ops() -> proc_macro2::TokenStream {
                 (Value::I16(a), Value::I16(b)) => Ok(a > b),
                 (Value::I32(a), Value::I32(b)) => Ok(a > b),
                 (Value::I64(a), Value::I64(b)) => Ok(a > b),
+                (Value::I128(a), Value::I128(b)) => Ok(a > b),
                 (Value::U8(a), Value::U8(b)) => Ok(a > b),
                 (Value::U16(a), Value::U16(b)) => Ok(a > b),
                 (Value::U32(a), Value::U32(b)) => Ok(a > b),
                 (Value::U64(a), Value::U64(b)) => Ok(a > b),
+                (Value::U128(a), Value::U128(b)) => Ok(a > b),
                 (Value::F32(a), Value::F32(b)) => Ok(a > b),
                 (Value::F64(a), Value::F64(b)) => Ok(a > b),
+                (Value::Char(a), Value::Char(b)) => Ok(a > b),
+                (Value::Str(a), Value::Str(b)) => Ok(a > b),
                 _ => Err("Type mismatch in comparison".to_string()),
             }
         }
@@ -153,12 +184,16 @@ fn generate_comparison_ops() -> proc_macro2::TokenStream {
                 (Value::I16(a), Value::I16(b)) => Ok(a >= b),
                 (Value::I32(a), Value::I32(b)) => Ok(a >= b),
                 (Value::I64(a), Value::I64(b)) => Ok(a >= b),
+                (Value::I128(a), Value::I128(b)) => Ok(a >= b),
                 (Value::U8(a), Value::U8(b)) => Ok(a >= b),
                 (Value::U16(a), Value::U16(b)) => Ok(a >= b),
                 (Value::U32(a), Value::U32(b)) => Ok(a >= b),
                 (Value::U64(a), Value::U64(b)) => Ok(a >= b),
+                (Value::U128(a), Value::U128(b)) => Ok(a >= b),
                 (Value::F32(a), Value::F32(b)) => Ok(a >= b),
                 (Value::F64(a), Value::F64(b)) => Ok(a >= b),
+                (Value::Char(a), Value::Char(b)) => Ok(a >= b),
+                (Value::Str(a), Value::Str(b)) => Ok(a >= b),
                 _ => Err("Type mismatch in comparison".to_string()),
             }
         }
@@ -173,10 +208,14 @@ fn generate_bitwise_ops() -> proc_macro2::TokenStream {
                 (Value::I16(a), Value::I16(b)) => Ok(Value::I16(a & b)),
                 (Value::I32(a), Value::I32(b)) => Ok(Value::I32(a & b)),
                 (Value::I64(a), Value::I64(b)) => Ok(Value::I64(a & b)),
+                (Value::I128(a), Value::I128(b)) => Ok(Value::I128(a & b)),
                 (Value::U8(a), Value::U8(b)) => Ok(Value::U8(a & b)),
                 (Value::U16(a), Value::U16(b)) => Ok(Value::U16(a & b)),
                 (Value::U32(a), Value::U32(b)) => Ok(Value::U32(a & b)),
                 (Value::U64(a), Value::U64(b)) => Ok(Value::U64(a & b)),
+                (Value::U128(a), Value::U128(b)) => Ok(Value::U128(a & b)),
+                (Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(a & b)),
+                (Value::Vector(a), Value::Vector(b)) => a.bitwise_and(b).map(Value::Vector),
                 _ => Err("Type mismatch or invalid type for bitwise AND".to_string()),
             }
         }
@@ -187,10 +226,14 @@ fn generate_bitwise_ops() -> proc_macro2::TokenStream {
                 (Value::I16(a), Value::I16(b)) => Ok(Value::I16(a | b)),
                 (Value::I32(a), Value::I32(b)) => Ok(Value::I32(a | b)),
                 (Value::I64(a), Value::I64(b)) => Ok(Value::I64(a | b)),
+                (Value::I128(a), Value::I128(b)) => Ok(Value::I128(a | b)),
                 (Value::U8(a), Value::U8(b)) => Ok(Value::U8(a | b)),
                 (Value::U16(a), Value::U16(b)) => Ok(Value::U16(a | b)),
                 (Value::U32(a), Value::U32(b)) => Ok(Value::U32(a | b)),
                 (Value::U64(a), Value::U64(b)) => Ok(Value::U64(a | b)),
+                (Value::U128(a), Value::U128(b)) => Ok(Value::U128(a | b)),
+                (Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(a | b)),
+                (Value::Vector(a), Value::Vector(b)) => a.bitwise_or(b).map(Value::Vector),
                 _ => Err("Type mismatch or invalid type for bitwise OR".to_string()),
             }
         }
@@ -201,10 +244,14 @@ fn generate_bitwise_ops() -> proc_macro2::TokenStream {
                 (Value::I16(a), Value::I16(b)) => Ok(Value::I16(a ^ b)),
                 (Value::I32(a), Value::I32(b)) => Ok(Value::I32(a ^ b)),
                 (Value::I64(a), Value::I64(b)) => Ok(Value::I64(a ^ b)),
+                (Value::I128(a), Value::I128(b)) => Ok(Value::I128(a ^ b)),
                 (Value::U8(a), Value::U8(b)) => Ok(Value::U8(a ^ b)),
                 (Value::U16(a), Value::U16(b)) => Ok(Value::U16(a ^ b)),
                 (Value::U32(a), Value::U32(b)) => Ok(Value::U32(a ^ b)),
                 (Value::U64(a), Value::U64(b)) => Ok(Value::U64(a ^ b)),
+                (Value::U128(a), Value::U128(b)) => Ok(Value::U128(a ^ b)),
+                (Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(a ^ b)),
+                (Value::Vector(a), Value::Vector(b)) => a.bitwise_xor(b).map(Value::Vector),
                 _ => Err("Type mismatch or invalid type for bitwise XOR".to_string()),
             }
         }
@@ -215,10 +262,14 @@ fn generate_bitwise_ops() -> proc_macro2::TokenStream {
                 Value::I16(a) => Ok(Value::I16(!a)),
                 Value::I32(a) => Ok(Value::I32(!a)),
                 Value::I64(a) => Ok(Value::I64(!a)),
+                Value::I128(a) => Ok(Value::I128(!a)),
                 Value::U8(a) => Ok(Value::U8(!a)),
                 Value::U16(a) => Ok(Value::U16(!a)),
                 Value::U32(a) => Ok(Value::U32(!a)),
                 Value::U64(a) => Ok(Value::U64(!a)),
+                Value::U128(a) => Ok(Value::U128(!a)),
+                Value::Bool(a) => Ok(Value::Bool(!a)),
+                Value::Vector(a) => a.bitwise_not().map(Value::Vector),
                 _ => Err("Invalid type for bitwise NOT".to_string()),
             }
         }
@@ -239,10 +290,12 @@ fn generate_shift_ops() -> proc_macro2::TokenStream {
                 Value::I16(a) => Ok(Value::I16(a.wrapping_shl(shift))),
                 Value::I32(a) => Ok(Value::I32(a.wrapping_shl(shift))),
                 Value::I64(a) => Ok(Value::I64(a.wrapping_shl(shift))),
+                Value::I128(a) => Ok(Value::I128(a.wrapping_shl(shift))),
                 Value::U8(a) => Ok(Value::U8(a.wrapping_shl(shift))),
                 Value::U16(a) => Ok(Value::U16(a.wrapping_shl(shift))),
                 Value::U32(a) => Ok(Value::U32(a.wrapping_shl(shift))),
                 Value::U64(a) => Ok(Value::U64(a.wrapping_shl(shift))),
+                Value::U128(a) => Ok(Value::U128(a.wrapping_shl(shift))),
                 _ => Err("Invalid type for shift left".to_string()),
             }
         }
@@ -259,32 +312,242 @@ fn generate_shift_ops() -> proc_macro2::TokenStream {
                 Value::I16(a) => Ok(Value::I16(a.wrapping_shr(shift))),
                 Value::I32(a) => Ok(Value::I32(a.wrapping_shr(shift))),
                 Value::I64(a) => Ok(Value::I64(a.wrapping_shr(shift))),
+                Value::I128(a) => Ok(Value::I128(a.wrapping_shr(shift))),
                 Value::U8(a) => Ok(Value::U8(a.wrapping_shr(shift))),
                 Value::U16(a) => Ok(Value::U16(a.wrapping_shr(shift))),
                 Value::U32(a) => Ok(Value::U32(a.wrapping_shr(shift))),
                 Value::U64(a) => Ok(Value::U64(a.wrapping_shr(shift))),
+                Value::U128(a) => Ok(Value::U128(a.wrapping_shr(shift))),
                 _ => Err("Invalid type for shift right".to_string()),
             }
         }
     }
 }
 
+fn generate_bit_ops() -> proc_macro2::TokenStream {
+    quote! {
+        // Counts are I32; a zero input has as many leading/trailing zeros as its width
+        pub fn count_ones(&self) -> Result<Value, String> {
+            match self {
+                Value::I8(a) => Ok(Value::I32(a.count_ones() as i32)),
+                Value::I16(a) => Ok(Value::I32(a.count_ones() as i32)),
+                Value::I32(a) => Ok(Value::I32(a.count_ones() as i32)),
+                Value::I64(a) => Ok(Value::I32(a.count_ones() as i32)),
+                Value::I128(a) => Ok(Value::I32(a.count_ones() as i32)),
+                Value::U8(a) => Ok(Value::I32(a.count_ones() as i32)),
+                Value::U16(a) => Ok(Value::I32(a.count_ones() as i32)),
+                Value::U32(a) => Ok(Value::I32(a.count_ones() as i32)),
+                Value::U64(a) => Ok(Value::I32(a.count_ones() as i32)),
+                Value::U128(a) => Ok(Value::I32(a.count_ones() as i32)),
+                _ => Err("Invalid type for popcnt".to_string()),
+            }
+        }
+
+        pub fn leading_zeros(&self) -> Result<Value, String> {
+            match self {
+                Value::I8(a) => Ok(Value::I32(a.leading_zeros() as i32)),
+                Value::I16(a) => Ok(Value::I32(a.leading_zeros() as i32)),
+                Value::I32(a) => Ok(Value::I32(a.leading_zeros() as i32)),
+                Value::I64(a) => Ok(Value::I32(a.leading_zeros() as i32)),
+                Value::I128(a) => Ok(Value::I32(a.leading_zeros() as i32)),
+                Value::U8(a) => Ok(Value::I32(a.leading_zeros() as i32)),
+                Value::U16(a) => Ok(Value::I32(a.leading_zeros() as i32)),
+                Value::U32(a) => Ok(Value::I32(a.leading_zeros() as i32)),
+                Value::U64(a) => Ok(Value::I32(a.leading_zeros() as i32)),
+                Value::U128(a) => Ok(Value::I32(a.leading_zeros() as i32)),
+                _ => Err("Invalid type for clz".to_string()),
+            }
+        }
+
+        pub fn trailing_zeros(&self) -> Result<Value, String> {
+            match self {
+                Value::I8(a) => Ok(Value::I32(a.trailing_zeros() as i32)),
+                Value::I16(a) => Ok(Value::I32(a.trailing_zeros() as i32)),
+                Value::I32(a) => Ok(Value::I32(a.trailing_zeros() as i32)),
+                Value::I64(a) => Ok(Value::I32(a.trailing_zeros() as i32)),
+                Value::I128(a) => Ok(Value::I32(a.trailing_zeros() as i32)),
+                Value::U8(a) => Ok(Value::I32(a.trailing_zeros() as i32)),
+                Value::U16(a) => Ok(Value::I32(a.trailing_zeros() as i32)),
+                Value::U32(a) => Ok(Value::I32(a.trailing_zeros() as i32)),
+                Value::U64(a) => Ok(Value::I32(a.trailing_zeros() as i32)),
+                Value::U128(a) => Ok(Value::I32(a.trailing_zeros() as i32)),
+                _ => Err("Invalid type for ctz".to_string()),
+            }
+        }
+
+        pub fn swap_bytes(&self) -> Result<Value, String> {
+            match self {
+                Value::I8(a) => Ok(Value::I8(a.swap_bytes())),
+                Value::I16(a) => Ok(Value::I16(a.swap_bytes())),
+                Value::I32(a) => Ok(Value::I32(a.swap_bytes())),
+                Value::I64(a) => Ok(Value::I64(a.swap_bytes())),
+                Value::I128(a) => Ok(Value::I128(a.swap_bytes())),
+                Value::U8(a) => Ok(Value::U8(a.swap_bytes())),
+                Value::U16(a) => Ok(Value::U16(a.swap_bytes())),
+                Value::U32(a) => Ok(Value::U32(a.swap_bytes())),
+                Value::U64(a) => Ok(Value::U64(a.swap_bytes())),
+                Value::U128(a) => Ok(Value::U128(a.swap_bytes())),
+                _ => Err("Invalid type for bswap".to_string()),
+            }
+        }
+
+        // Rotate counts are taken modulo the width; negative counts rotate the other way
+        pub fn rotate_left(&self, other: &Value) -> Result<Value, String> {
+            let (_, width) = self.int_bits().ok_or_else(|| "Invalid type for rotate".to_string())?;
+            let count = Self::rotate_count(other, width)?;
+            match self {
+                Value::I8(a) => Ok(Value::I8(a.rotate_left(count))),
+                Value::I16(a) => Ok(Value::I16(a.rotate_left(count))),
+                Value::I32(a) => Ok(Value::I32(a.rotate_left(count))),
+                Value::I64(a) => Ok(Value::I64(a.rotate_left(count))),
+                Value::I128(a) => Ok(Value::I128(a.rotate_left(count))),
+                Value::U8(a) => Ok(Value::U8(a.rotate_left(count))),
+                Value::U16(a) => Ok(Value::U16(a.rotate_left(count))),
+                Value::U32(a) => Ok(Value::U32(a.rotate_left(count))),
+                Value::U64(a) => Ok(Value::U64(a.rotate_left(count))),
+                Value::U128(a) => Ok(Value::U128(a.rotate_left(count))),
+                _ => unreachable!(),
+            }
+        }
+
+        pub fn rotate_right(&self, other: &Value) -> Result<Value, String> {
+            let (_, width) = self.int_bits().ok_or_else(|| "Invalid type for rotate".to_string())?;
+            let count = Self::rotate_count(other, width)?;
+            match self {
+                Value::I8(a) => Ok(Value::I8(a.rotate_right(count))),
+                Value::I16(a) => Ok(Value::I16(a.rotate_right(count))),
+                Value::I32(a) => Ok(Value::I32(a.rotate_right(count))),
+                Value::I64(a) => Ok(Value::I64(a.rotate_right(count))),
+                Value::I128(a) => Ok(Value::I128(a.rotate_right(count))),
+                Value::U8(a) => Ok(Value::U8(a.rotate_right(count))),
+                Value::U16(a) => Ok(Value::U16(a.rotate_right(count))),
+                Value::U32(a) => Ok(Value::U32(a.rotate_right(count))),
+                Value::U64(a) => Ok(Value::U64(a.rotate_right(count))),
+                Value::U128(a) => Ok(Value::U128(a.rotate_right(count))),
+                _ => unreachable!(),
+            }
+        }
+
+        fn rotate_count(count: &Value, width: u32) -> Result<u32, String> {
+            let count = match count {
+                Value::I8(c) => *c as i64,
+                Value::I16(c) => *c as i64,
+                Value::I32(c) => *c as i64,
+                Value::I64(c) => *c,
+                Value::I128(c) => c.rem_euclid(width as i128) as i64,
+                Value::U8(c) => *c as i64,
+                Value::U16(c) => *c as i64,
+                Value::U32(c) => *c as i64,
+                Value::U64(c) => (*c % width as u64) as i64,
+                Value::U128(c) => (*c % width as u128) as i64,
+                _ => return Err("Rotate count must be an integer".to_string()),
+            };
+            Ok(count.rem_euclid(width as i64) as u32)
+        }
+
+        /// Unsigned field of `len` bits starting at bit `start`, zero-extended
+        /// into the source's type.
+        pub fn extract_bits(&self, start: usize, len: usize) -> Result<Value, String> {
+            let (bits, width) = self.int_bits().ok_or_else(|| "Invalid type for bit field extract".to_string())?;
+            let mask = Self::field_mask(start, len, width)?;
+            Ok(self.with_int_bits(bits.checked_shr(start as u32).unwrap_or(0) & mask))
+        }
+
+        /// The source with bits `start..start + len` replaced by the low bits of `field`.
+        pub fn insert_bits(&self, field: &Value, start: usize, len: usize) -> Result<Value, String> {
+            let (bits, width) = self.int_bits().ok_or_else(|| "Invalid type for bit field insert".to_string())?;
+            let (field, _) = field.int_bits().ok_or_else(|| "Bit field value must be an integer".to_string())?;
+            let mask = Self::field_mask(start, len, width)?;
+            let placed = mask.checked_shl(start as u32).unwrap_or(0);
+            let field = (field & mask).checked_shl(start as u32).unwrap_or(0);
+            Ok(self.with_int_bits((bits & !placed) | field))
+        }
+
+        fn field_mask(start: usize, len: usize, width: u32) -> Result<u128, String> {
+            if start.checked_add(len).filter(|&end| end <= width as usize).is_none() {
+                return Err(format!("Bit field of {} bits at bit {} does not fit in {} bits", len, start, width));
+            }
+            Ok(if len == 128 { u128::MAX } else { (1u128 << len) - 1 })
+        }
+
+        // Raw two's complement bits and width of an integer value
+        fn int_bits(&self) -> Option<(u128, u32)> {
+            match self {
+                Value::I8(a) => Some((*a as u8 as u128, 8)),
+                Value::I16(a) => Some((*a as u16 as u128, 16)),
+                Value::I32(a) => Some((*a as u32 as u128, 32)),
+                Value::I64(a) => Some((*a as u64 as u128, 64)),
+                Value::I128(a) => Some((*a as u128, 128)),
+                Value::U8(a) => Some((*a as u128, 8)),
+                Value::U16(a) => Some((*a as u128, 16)),
+                Value::U32(a) => Some((*a as u128, 32)),
+                Value::U64(a) => Some((*a as u128, 64)),
+                Value::U128(a) => Some((*a, 128)),
+                _ => None,
+            }
+        }
+
+        fn with_int_bits(&self, bits: u128) -> Value {
+            match self {
+                Value::I8(_) => Value::I8(bits as u8 as _),
+                Value::I16(_) => Value::I16(bits as u16 as _),
+                Value::I32(_) => Value::I32(bits as u32 as _),
+                Value::I64(_) => Value::I64(bits as u64 as _),
+                Value::I128(_) => Value::I128(bits as _),
+                Value::U8(_) => Value::U8(bits as u8 as _),
+                Value::U16(_) => Value::U16(bits as u16 as _),
+                Value::U32(_) => Value::U32(bits as u32 as _),
+                Value::U64(_) => Value::U64(bits as u64 as _),
+                Value::U128(_) => Value::U128(bits),
+                _ => self.clone(),
+            }
+        }
+    }
+}
+
 fn generate_cast_methods() -> proc_macro2::TokenStream {
     quote! {
         pub fn cast(&self, target_type: DataType) -> Result<Value, String> {
+            // Strings only convert through the str_* intrinsics
+            if let Value::Str(_) = self {
+                return match target_type {
+                    DataType::Str => Ok(self.clone()),
+                    _ => Err(format!("Cannot cast a string to {:?}; use str_to_int", target_type)),
+                };
+            }
+            if let Value::Ref(_) = self {
+                return match target_type {
+                    DataType::Ref => Ok(self.clone()),
+                    _ => Err(format!("Cannot cast a reference to {:?}", target_type)),
+                };
+            }
+            if let Value::Vector(v) = self {
+                return match target_type {
+                    DataType::Vector(shape) if shape == v.shape() => Ok(self.clone()),
+                    _ => Err(format!("Cannot cast {} to {:?}", v.shape().name(), target_type)),
+                };
+            }
             match target_type {
                 DataType::I8 => self.as_i8(),
                 DataType::I16 => self.as_i16(),
                 DataType::I32 => self.as_i32(),
                 DataType::I64 => self.as_i64(),
+                DataType::I128 => self.as_i128(),
                 DataType::U8 => self.as_u8(),
                 DataType::U16 => self.as_u16(),
                 DataType::U32 => self.as_u32(),
                 DataType::U64 => self.as_u64(),
+                DataType::U128 => self.as_u128(),
                 DataType::F32 => self.as_f32(),
                 DataType::F64 => self.as_f64(),
                 DataType::Ptr => Ok(Value::Ptr(self.as_usize()?)),
                 DataType::Void => Err("Cannot cast to Void type".to_string()),
+                // Any non-zero value is true
+                DataType::Bool => Ok(Value::Bool(!self.is_zero())),
+                DataType::Char => self.as_char(),
+                DataType::Vector(shape) => Err(format!("Cannot cast a scalar to {}; use vsplat", shape.name())),
+                DataType::Str => Err(format!("Cannot cast {:?} to a string; use int_to_str or float_to_str", self)),
+                DataType::Ref => Err(format!("Cannot cast {:?} to a reference", self)),
             }
         }
 
@@ -294,13 +557,18 @@ fn generate_cast_methods() -> proc_macro2::TokenStream {
                 Value::I16(v) => Ok(Value::I8(*v as i8)),
                 Value::I32(v) => Ok(Value::I8(*v as i8)),
                 Value::I64(v) => Ok(Value::I8(*v as i8)),
+                Value::I128(v) => Ok(Value::I8(*v as i8)),
                 Value::U8(v) => Ok(Value::I8(*v as i8)),
                 Value::U16(v) => Ok(Value::I8(*v as i8)),
                 Value::U32(v) => Ok(Value::I8(*v as i8)),
                 Value::U64(v) => Ok(Value::I8(*v as i8)),
+                Value::U128(v) => Ok(Value::I8(*v as i8)),
                 Value::F32(v) => Ok(Value::I8(*v as i8)),
                 Value::F64(v) => Ok(Value::I8(*v as i8)),
                 Value::Ptr(v) => Ok(Value::I8(*v as i8)),
+                Value::Bool(v) => Ok(Value::I8(*v as i8)),
+                Value::Char(v) => Ok(Value::I8(*v as i8)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             }
         }
 
@@ -310,13 +578,18 @@ fn generate_cast_methods() -> proc_macro2::TokenStream {
                 Value::I16(v) => Ok(Value::I16(*v)),
                 Value::I32(v) => Ok(Value::I16(*v as i16)),
                 Value::I64(v) => Ok(Value::I16(*v as i16)),
+                Value::I128(v) => Ok(Value::I16(*v as i16)),
                 Value::U8(v) => Ok(Value::I16(*v as i16)),
                 Value::U16(v) => Ok(Value::I16(*v as i16)),
                 Value::U32(v) => Ok(Value::I16(*v as i16)),
                 Value::U64(v) => Ok(Value::I16(*v as i16)),
+                Value::U128(v) => Ok(Value::I16(*v as i16)),
                 Value::F32(v) => Ok(Value::I16(*v as i16)),
                 Value::F64(v) => Ok(Value::I16(*v as i16)),
                 Value::Ptr(v) => Ok(Value::I16(*v as i16)),
+                Value::Bool(v) => Ok(Value::I16(*v as i16)),
+                Value::Char(v) => Ok(Value::I16(*v as i16)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             }
         }
 
@@ -326,13 +599,18 @@ fn generate_cast_methods() -> proc_macro2::TokenStream {
                 Value::I16(v) => Ok(Value::I32(*v as i32)),
                 Value::I32(v) => Ok(Value::I32(*v)),
                 Value::I64(v) => Ok(Value::I32(*v as i32)),
+                Value::I128(v) => Ok(Value::I32(*v as i32)),
                 Value::U8(v) => Ok(Value::I32(*v as i32)),
                 Value::U16(v) => Ok(Value::I32(*v as i32)),
                 Value::U32(v) => Ok(Value::I32(*v as i32)),
                 Value::U64(v) => Ok(Value::I32(*v as i32)),
+                Value::U128(v) => Ok(Value::I32(*v as i32)),
                 Value::F32(v) => Ok(Value::I32(*v as i32)),
                 Value::F64(v) => Ok(Value::I32(*v as i32)),
                 Value::Ptr(v) => Ok(Value::I32(*v as i32)),
+                Value::Bool(v) => Ok(Value::I32(*v as i32)),
+                Value::Char(v) => Ok(Value::I32(*v as i32)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             }
         }
 
@@ -342,13 +620,39 @@ fn generate_cast_methods() -> proc_macro2::TokenStream {
                 Value::I16(v) => Ok(Value::I64(*v as i64)),
                 Value::I32(v) => Ok(Value::I64(*v as i64)),
                 Value::I64(v) => Ok(Value::I64(*v)),
+                Value::I128(v) => Ok(Value::I64(*v as i64)),
                 Value::U8(v) => Ok(Value::I64(*v as i64)),
                 Value::U16(v) => Ok(Value::I64(*v as i64)),
                 Value::U32(v) => Ok(Value::I64(*v as i64)),
                 Value::U64(v) => Ok(Value::I64(*v as i64)),
+                Value::U128(v) => Ok(Value::I64(*v as i64)),
                 Value::F32(v) => Ok(Value::I64(*v as i64)),
                 Value::F64(v) => Ok(Value::I64(*v as i64)),
                 Value::Ptr(v) => Ok(Value::I64(*v as i64)),
+                Value::Bool(v) => Ok(Value::I64(*v as i64)),
+                Value::Char(v) => Ok(Value::I64(*v as i64)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
+            }
+        }
+
+        fn as_i128(&self) -> Result<Value, String> {
+            match self {
+                Value::I8(v) => Ok(Value::I128(*v as i128)),
+                Value::I16(v) => Ok(Value::I128(*v as i128)),
+                Value::I32(v) => Ok(Value::I128(*v as i128)),
+                Value::I64(v) => Ok(Value::I128(*v as i128)),
+                Value::I128(v) => Ok(Value::I128(*v)),
+                Value::U8(v) => Ok(Value::I128(*v as i128)),
+                Value::U16(v) => Ok(Value::I128(*v as i128)),
+                Value::U32(v) => Ok(Value::I128(*v as i128)),
+                Value::U64(v) => Ok(Value::I128(*v as i128)),
+                Value::U128(v) => Ok(Value::I128(*v as i128)),
+                Value::F32(v) => Ok(Value::I128(*v as i128)),
+                Value::F64(v) => Ok(Value::I128(*v as i128)),
+                Value::Ptr(v) => Ok(Value::I128(*v as i128)),
+                Value::Bool(v) => Ok(Value::I128(*v as i128)),
+                Value::Char(v) => Ok(Value::I128(*v as i128)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             }
         }
 
@@ -358,13 +662,18 @@ fn generate_cast_methods() -> proc_macro2::TokenStream {
                 Value::I16(v) => Ok(Value::U8(*v as u8)),
                 Value::I32(v) => Ok(Value::U8(*v as u8)),
                 Value::I64(v) => Ok(Value::U8(*v as u8)),
+                Value::I128(v) => Ok(Value::U8(*v as u8)),
                 Value::U8(v) => Ok(Value::U8(*v)),
                 Value::U16(v) => Ok(Value::U8(*v as u8)),
                 Value::U32(v) => Ok(Value::U8(*v as u8)),
                 Value::U64(v) => Ok(Value::U8(*v as u8)),
+                Value::U128(v) => Ok(Value::U8(*v as u8)),
                 Value::F32(v) => Ok(Value::U8(*v as u8)),
                 Value::F64(v) => Ok(Value::U8(*v as u8)),
                 Value::Ptr(v) => Ok(Value::U8(*v as u8)),
+                Value::Bool(v) => Ok(Value::U8(*v as u8)),
+                Value::Char(v) => Ok(Value::U8(*v as u8)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             }
         }
 
@@ -374,13 +683,18 @@ fn generate_cast_methods() -> proc_macro2::TokenStream {
                 Value::I16(v) => Ok(Value::U16(*v as u16)),
                 Value::I32(v) => Ok(Value::U16(*v as u16)),
                 Value::I64(v) => Ok(Value::U16(*v as u16)),
+                Value::I128(v) => Ok(Value::U16(*v as u16)),
                 Value::U8(v) => Ok(Value::U16(*v as u16)),
                 Value::U16(v) => Ok(Value::U16(*v)),
                 Value::U32(v) => Ok(Value::U16(*v as u16)),
                 Value::U64(v) => Ok(Value::U16(*v as u16)),
+                Value::U128(v) => Ok(Value::U16(*v as u16)),
                 Value::F32(v) => Ok(Value::U16(*v as u16)),
                 Value::F64(v) => Ok(Value::U16(*v as u16)),
                 Value::Ptr(v) => Ok(Value::U16(*v as u16)),
+                Value::Bool(v) => Ok(Value::U16(*v as u16)),
+                Value::Char(v) => Ok(Value::U16(*v as u16)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             }
         }
 
@@ -390,13 +704,18 @@ fn generate_cast_methods() -> proc_macro2::TokenStream {
                 Value::I16(v) => Ok(Value::U32(*v as u32)),
                 Value::I32(v) => Ok(Value::U32(*v as u32)),
                 Value::I64(v) => Ok(Value::U32(*v as u32)),
+                Value::I128(v) => Ok(Value::U32(*v as u32)),
                 Value::U8(v) => Ok(Value::U32(*v as u32)),
                 Value::U16(v) => Ok(Value::U32(*v as u32)),
                 Value::U32(v) => Ok(Value::U32(*v)),
                 Value::U64(v) => Ok(Value::U32(*v as u32)),
+                Value::U128(v) => Ok(Value::U32(*v as u32)),
                 Value::F32(v) => Ok(Value::U32(*v as u32)),
                 Value::F64(v) => Ok(Value::U32(*v as u32)),
                 Value::Ptr(v) => Ok(Value::U32(*v as u32)),
+                Value::Bool(v) => Ok(Value::U32(*v as u32)),
+                Value::Char(v) => Ok(Value::U32(*v as u32)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             }
         }
 
@@ -406,13 +725,39 @@ fn generate_cast_methods() -> proc_macro2::TokenStream {
                 Value::I16(v) => Ok(Value::U64(*v as u64)),
                 Value::I32(v) => Ok(Value::U64(*v as u64)),
                 Value::I64(v) => Ok(Value::U64(*v as u64)),
+                Value::I128(v) => Ok(Value::U64(*v as u64)),
                 Value::U8(v) => Ok(Value::U64(*v as u64)),
                 Value::U16(v) => Ok(Value::U64(*v as u64)),
                 Value::U32(v) => Ok(Value::U64(*v as u64)),
                 Value::U64(v) => Ok(Value::U64(*v)),
+                Value::U128(v) => Ok(Value::U64(*v as u64)),
                 Value::F32(v) => Ok(Value::U64(*v as u64)),
                 Value::F64(v) => Ok(Value::U64(*v as u64)),
                 Value::Ptr(v) => Ok(Value::U64(*v as u64)),
+                Value::Bool(v) => Ok(Value::U64(*v as u64)),
+                Value::Char(v) => Ok(Value::U64(*v as u64)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
+            }
+        }
+
+        fn as_u128(&self) -> Result<Value, String> {
+            match self {
+                Value::I8(v) => Ok(Value::U128(*v as u128)),
+                Value::I16(v) => Ok(Value::U128(*v as u128)),
+                Value::I32(v) => Ok(Value::U128(*v as u128)),
+                Value::I64(v) => Ok(Value::U128(*v as u128)),
+                Value::I128(v) => Ok(Value::U128(*v as u128)),
+                Value::U8(v) => Ok(Value::U128(*v as u128)),
+                Value::U16(v) => Ok(Value::U128(*v as u128)),
+                Value::U32(v) => Ok(Value::U128(*v as u128)),
+                Value::U64(v) => Ok(Value::U128(*v as u128)),
+                Value::U128(v) => Ok(Value::U128(*v)),
+                Value::F32(v) => Ok(Value::U128(*v as u128)),
+                Value::F64(v) => Ok(Value::U128(*v as u128)),
+                Value::Ptr(v) => Ok(Value::U128(*v as u128)),
+                Value::Bool(v) => Ok(Value::U128(*v as u128)),
+                Value::Char(v) => Ok(Value::U128(*v as u128)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             }
         }
 
@@ -422,13 +767,18 @@ fn generate_cast_methods() -> proc_macro2::TokenStream {
                 Value::I16(v) => Ok(Value::F32(*v as f32)),
                 Value::I32(v) => Ok(Value::F32(*v as f32)),
                 Value::I64(v) => Ok(Value::F32(*v as f32)),
+                Value::I128(v) => Ok(Value::F32(*v as f32)),
                 Value::U8(v) => Ok(Value::F32(*v as f32)),
                 Value::U16(v) => Ok(Value::F32(*v as f32)),
                 Value::U32(v) => Ok(Value::F32(*v as f32)),
                 Value::U64(v) => Ok(Value::F32(*v as f32)),
+                Value::U128(v) => Ok(Value::F32(*v as f32)),
                 Value::F32(v) => Ok(Value::F32(*v)),
                 Value::F64(v) => Ok(Value::F32(*v as f32)),
                 Value::Ptr(v) => Ok(Value::F32(*v as f32)),
+                Value::Bool(v) => Ok(Value::F32(*v as u8 as f32)),
+                Value::Char(v) => Ok(Value::F32(*v as u32 as f32)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
             }
         }
 
@@ -438,13 +788,86 @@ fn generate_cast_methods() -> proc_macro2::TokenStream {
                 Value::I16(v) => Ok(Value::F64(*v as f64)),
                 Value::I32(v) => Ok(Value::F64(*v as f64)),
                 Value::I64(v) => Ok(Value::F64(*v as f64)),
+                Value::I128(v) => Ok(Value::F64(*v as f64)),
                 Value::U8(v) => Ok(Value::F64(*v as f64)),
                 Value::U16(v) => Ok(Value::F64(*v as f64)),
                 Value::U32(v) => Ok(Value::F64(*v as f64)),
                 Value::U64(v) => Ok(Value::F64(*v as f64)),
+                Value::U128(v) => Ok(Value::F64(*v as f64)),
                 Value::F32(v) => Ok(Value::F64(*v as f64)),
                 Value::F64(v) => Ok(Value::F64(*v)),
                 Value::Ptr(v) => Ok(Value::F64(*v as f64)),
+                Value::Bool(v) => Ok(Value::F64(*v as u8 as f64)),
+                Value::Char(v) => Ok(Value::F64(*v as u32 as f64)),
+                Value::Vector(_) | Value::Str(_) | Value::Ref(_) => unreachable!(),
+            }
+        }
+
+        // Integers must be valid Unicode scalar values
+        fn as_char(&self) -> Result<Value, String> {
+            match self {
+                Value::Char(v) => Ok(Value::Char(*v)),
+                Value::F32(_) | Value::F64(_) | Value::Bool(_) | Value::Ptr(_) => {
+                    Err(format!("Cannot cast {:?} to Char", self))
+                }
+                _ => self
+                    .exact_int()
+                    .and_then(|code| u32::try_from(code).ok())
+                    .and_then(char::from_u32)
+                    .map(Value::Char)
+                    .ok_or_else(|| format!("Invalid character code: {:?}", self)),
+            }
+        }
+    }
+}
+
+fn generate_math_ops() -> proc_macro2::TokenStream {
+    quote! {
+        // Integer powers are computed exactly and fail on overflow
+        pub fn pow(&self, exp: &Value) -> Result<Value, String> {
+            match (self, exp) {
+                (Value::F32(a), Value::F32(b)) => Ok(Value::F32(a.powf(*b))),
+                (Value::F64(a), Value::F64(b)) => Ok(Value::F64(a.powf(*b))),
+                (Value::F32(_) | Value::F64(_), _) => Err("pow requires matching float types".to_string()),
+                _ => {
+                    let exp = exp
+                        .as_usize()
+                        .ok()
+                        .and_then(|e| u32::try_from(e).ok())
+                        .ok_or_else(|| "pow requires a non-negative integer exponent".to_string())?;
+                    let overflow = || "Integer overflow in pow".to_string();
+                    match self {
+                        Value::I8(a) => a.checked_pow(exp).map(Value::I8).ok_or_else(overflow),
+                        Value::I16(a) => a.checked_pow(exp).map(Value::I16).ok_or_else(overflow),
+                        Value::I32(a) => a.checked_pow(exp).map(Value::I32).ok_or_else(overflow),
+                        Value::I64(a) => a.checked_pow(exp).map(Value::I64).ok_or_else(overflow),
+                        Value::I128(a) => a.checked_pow(exp).map(Value::I128).ok_or_else(overflow),
+                        Value::U8(a) => a.checked_pow(exp).map(Value::U8).ok_or_else(overflow),
+                        Value::U16(a) => a.checked_pow(exp).map(Value::U16).ok_or_else(overflow),
+                        Value::U32(a) => a.checked_pow(exp).map(Value::U32).ok_or_else(overflow),
+                        Value::U64(a) => a.checked_pow(exp).map(Value::U64).ok_or_else(overflow),
+                        Value::U128(a) => a.checked_pow(exp).map(Value::U128).ok_or_else(overflow),
+                        _ => Err("pow requires numeric types".to_string()),
+                    }
+                }
+            }
+        }
+
+        pub fn abs(&self) -> Result<Value, String> {
+            match self {
+                Value::I8(a) => a.checked_abs().map(Value::I8).ok_or_else(|| "Integer overflow in abs".to_string()),
+                Value::I16(a) => a.checked_abs().map(Value::I16).ok_or_else(|| "Integer overflow in abs".to_string()),
+                Value::I32(a) => a.checked_abs().map(Value::I32).ok_or_else(|| "Integer overflow in abs".to_string()),
+                Value::I64(a) => a.checked_abs().map(Value::I64).ok_or_else(|| "Integer overflow in abs".to_string()),
+                Value::I128(a) => a.checked_abs().map(Value::I128).ok_or_else(|| "Integer overflow in abs".to_string()),
+                Value::U8(a) => Ok(Value::U8(*a)),
+                Value::U16(a) => Ok(Value::U16(*a)),
+                Value::U32(a) => Ok(Value::U32(*a)),
+                Value::U64(a) => Ok(Value::U64(*a)),
+                Value::U128(a) => Ok(Value::U128(*a)),
+                Value::F32(a) => Ok(Value::F32(a.abs())),
+                Value::F64(a) => Ok(Value::F64(a.abs())),
+                _ => Err("Cannot take the absolute value of this type".to_string()),
             }
         }
     }